@@ -10,6 +10,7 @@ struct FinalConfig {
     timer_enabled: Option<bool>,
     threads: Option<u32>,
     driver: DriverType,
+    hooks: Vec<(&'static str, syn::Path)>,
 }
 
 struct Configuration {
@@ -17,8 +18,17 @@ struct Configuration {
     timer_enabled: Option<(bool, Span)>,
     threads: Option<(u32, Span)>,
     driver: Option<(DriverType, Span)>,
+    hooks: Vec<(&'static str, syn::Path)>,
 }
 
+// Lifecycle hooks of the runtime builder, set with the path of a function.
+const HOOKS: [&str; 4] = [
+    "on_thread_start",
+    "before_park",
+    "after_unpark",
+    "on_task_spawn",
+];
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum DriverType {
     Legacy,
@@ -33,9 +43,28 @@ impl Configuration {
             timer_enabled: None,
             threads: None,
             driver: None,
+            hooks: Vec::new(),
         }
     }
 
+    fn set_hook(
+        &mut self,
+        hook: &'static str,
+        path: syn::Lit,
+        span: Span,
+    ) -> Result<(), syn::Error> {
+        if self.hooks.iter().any(|(h, _)| *h == hook) {
+            return Err(syn::Error::new(
+                span,
+                format!("`{}` set multiple times.", hook),
+            ));
+        }
+
+        let path = parse_path(path, span, hook)?;
+        self.hooks.push((hook, path));
+        Ok(())
+    }
+
     fn set_driver(&mut self, driver: syn::Lit, span: Span) -> Result<(), syn::Error> {
         if self.driver.is_some() {
             return Err(syn::Error::new(span, "`driver` set multiple times."));
@@ -88,6 +117,7 @@ impl Configuration {
             timer_enabled: self.timer_enabled.map(|(t, _)| t),
             threads: self.threads.map(|(t, _)| t),
             driver: self.driver.map(|(d, _)| d).unwrap_or(DriverType::Fusion),
+            hooks: self.hooks.clone(),
         })
     }
 }
@@ -121,6 +151,16 @@ fn parse_string(lit: syn::Lit, span: Span, field: &str) -> Result<String, syn::E
     }
 }
 
+fn parse_path(lit: syn::Lit, span: Span, field: &str) -> Result<syn::Path, syn::Error> {
+    let val = parse_string(lit, span, field)?;
+    syn::parse_str(&val).map_err(|e| {
+        syn::Error::new(
+            span,
+            format!("Failed to parse value of `{}` as path: {}", field, e),
+        )
+    })
+}
+
 #[allow(unused)]
 fn parse_driver(lit: syn::Lit, span: Span, field: &str) -> Result<DriverType, syn::Error> {
     let val = parse_string(lit, span, field)?;
@@ -185,9 +225,14 @@ fn parse_knobs(
                         namevalue.lit.clone(),
                         syn::spanned::Spanned::span(&namevalue.lit),
                     )?,
+                    name if HOOKS.contains(&name) => config.set_hook(
+                        HOOKS.iter().find(|h| **h == name).unwrap(),
+                        namevalue.lit.clone(),
+                        syn::spanned::Spanned::span(&namevalue.lit),
+                    )?,
                     name => {
                         let msg = format!(
-                            "Unknown attribute {} is specified; expected one of: `worker_threads`, `entries`, `timer_enabled`",
+                            "Unknown attribute {} is specified; expected one of: `worker_threads`, `entries`, `timer_enabled`, `driver`, `on_thread_start`, `before_park`, `after_unpark`, `on_task_spawn`",
                             name,
                         );
                         return Err(syn::Error::new_spanned(namevalue, msg));
@@ -200,7 +245,7 @@ fn parse_knobs(
                     .ok_or_else(|| syn::Error::new_spanned(&path, "Must have specified ident"))?
                     .to_string()
                     .to_lowercase();
-                let msg = format!("Unknown attribute {} is specified; expected one of: `worker_threads`, `entries`, `timer_enabled`", name);
+                let msg = format!("Unknown attribute {} is specified; expected one of: `worker_threads`, `entries`, `timer_enabled`, `driver`, `on_thread_start`, `before_park`, `after_unpark`, `on_task_spawn`", name);
                 return Err(syn::Error::new_spanned(path, msg));
             }
             other => {
@@ -247,6 +292,10 @@ fn parse_knobs(
     if let Some(entries) = config.entries {
         rt = quote! { #rt.with_entries(#entries) }
     }
+    for (hook, path) in config.hooks.iter() {
+        let hook = proc_macro2::Ident::new(hook, Span::call_site());
+        rt = quote! { #rt.#hook(#path) }
+    }
     if Some(true) == config.timer_enabled {
         rt = quote! { #rt.enable_timer() }
     }
@@ -0,0 +1,214 @@
+use std::{
+    alloc::{alloc, dealloc, handle_alloc_error, Layout},
+    fmt, ops,
+    ptr::NonNull,
+};
+
+use super::{IoBuf, IoBufMut};
+
+/// A heap buffer whose start address is aligned to a given power of two.
+///
+/// Files opened with `O_DIRECT` require the buffer address, the transfer
+/// length and the file offset to be aligned to the logical block size of the
+/// underlying device. `Vec<u8>` gives no guarantee about its address, so this
+/// type should be used for such files instead.
+///
+/// The alignment of a file can be queried with
+/// [`File::logical_block_size`](crate::fs::File::logical_block_size).
+///
+/// Like `Vec<u8>`, the buffer tracks the number of initialized bytes, which is
+/// what will be written and what `Deref` exposes, and a fixed capacity, which
+/// is what will be read into.
+pub struct AlignedBuf {
+    ptr: NonNull<u8>,
+    len: usize,
+    layout: Layout,
+}
+
+impl AlignedBuf {
+    /// Create an empty buffer with the given capacity and alignment.
+    ///
+    /// The capacity is rounded up to a multiple of the alignment.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `align` is not a power of two or the rounded capacity
+    /// overflows `isize`.
+    pub fn new(capacity: usize, align: usize) -> Self {
+        assert!(align.is_power_of_two(), "alignment must be a power of two");
+        let size = capacity.checked_add(align - 1).expect("capacity overflow") & !(align - 1);
+        let layout = Layout::from_size_align(size.max(align), align).expect("invalid layout");
+        // Safety: the layout size is never zero.
+        let ptr = unsafe { alloc(layout) };
+        let ptr = match NonNull::new(ptr) {
+            Some(ptr) => ptr,
+            None => handle_alloc_error(layout),
+        };
+        Self {
+            ptr,
+            len: 0,
+            layout,
+        }
+    }
+
+    /// Create a buffer of `capacity` zeroed bytes, all of them initialized.
+    pub fn zeroed(capacity: usize, align: usize) -> Self {
+        let mut buf = Self::new(capacity, align);
+        unsafe {
+            buf.ptr.as_ptr().write_bytes(0, buf.capacity());
+            buf.set_len(buf.capacity());
+        }
+        buf
+    }
+
+    /// Alignment of the buffer address.
+    #[inline]
+    pub fn alignment(&self) -> usize {
+        self.layout.align()
+    }
+
+    /// Number of bytes the buffer can hold.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.layout.size()
+    }
+
+    /// Number of initialized bytes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if there are no initialized bytes.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Set the number of initialized bytes.
+    ///
+    /// # Safety
+    /// `len` must not exceed the capacity and the bytes up to `len` must be
+    /// initialized.
+    #[inline]
+    pub unsafe fn set_len(&mut self, len: usize) {
+        debug_assert!(len <= self.capacity());
+        self.len = len;
+    }
+
+    /// Mark all bytes as uninitialized.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Append bytes to the initialized part of the buffer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is not enough capacity left.
+    pub fn extend_from_slice(&mut self, data: &[u8]) {
+        assert!(
+            self.capacity() - self.len >= data.len(),
+            "not enough capacity"
+        );
+        unsafe {
+            self.ptr
+                .as_ptr()
+                .add(self.len)
+                .copy_from_nonoverlapping(data.as_ptr(), data.len());
+        }
+        self.len += data.len();
+    }
+}
+
+impl Drop for AlignedBuf {
+    fn drop(&mut self) {
+        unsafe { dealloc(self.ptr.as_ptr(), self.layout) }
+    }
+}
+
+// Safety: AlignedBuf owns its memory exclusively, just like Vec.
+unsafe impl Send for AlignedBuf {}
+unsafe impl Sync for AlignedBuf {}
+
+impl ops::Deref for AlignedBuf {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl ops::DerefMut for AlignedBuf {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl fmt::Debug for AlignedBuf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AlignedBuf")
+            .field("len", &self.len)
+            .field("capacity", &self.capacity())
+            .field("alignment", &self.alignment())
+            .finish()
+    }
+}
+
+unsafe impl IoBuf for AlignedBuf {
+    #[inline]
+    fn read_ptr(&self) -> *const u8 {
+        self.ptr.as_ptr()
+    }
+
+    #[inline]
+    fn bytes_init(&self) -> usize {
+        self.len
+    }
+}
+
+unsafe impl IoBufMut for AlignedBuf {
+    #[inline]
+    fn write_ptr(&mut self) -> *mut u8 {
+        self.ptr.as_ptr()
+    }
+
+    #[inline]
+    fn bytes_total(&self) -> usize {
+        self.capacity()
+    }
+
+    #[inline]
+    unsafe fn set_init(&mut self, init_len: usize) {
+        self.set_len(init_len);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aligned_buf() {
+        let mut buf = AlignedBuf::new(1000, 512);
+        assert_eq!(buf.read_ptr() as usize % 512, 0);
+        assert_eq!(buf.capacity(), 1024);
+        assert_eq!(buf.alignment(), 512);
+        assert!(buf.is_empty());
+
+        buf.extend_from_slice(b"hello");
+        assert_eq!(&buf[..], b"hello");
+        assert_eq!(buf.bytes_init(), 5);
+        assert_eq!(buf.bytes_total(), 1024);
+
+        unsafe { buf.set_init(0) };
+        assert!(buf.is_empty());
+
+        let buf = AlignedBuf::zeroed(4096, 4096);
+        assert_eq!(buf.read_ptr() as usize % 4096, 0);
+        assert_eq!(&buf[..], &[0; 4096][..]);
+    }
+}
@@ -18,6 +18,9 @@ pub use slice::{Slice, SliceMut};
 mod raw_buf;
 pub use raw_buf::RawBuf;
 
+mod aligned_buf;
+pub use aligned_buf::AlignedBuf;
+
 mod vec_wrapper;
 pub(crate) use vec_wrapper::{read_vec_meta, write_vec_meta};
 
@@ -1,16 +1,18 @@
-use std::{io, marker::PhantomData};
+use std::{io, marker::PhantomData, os::unix::prelude::RawFd, time::Duration};
 
 use scoped_tls::scoped_thread_local;
 
 use crate::driver::Driver;
 use crate::time::driver::TimeDriver;
 
-use crate::{time::Clock, Runtime};
+use crate::{task::TaskMeta, time::Clock, Runtime};
 
 #[cfg(all(target_os = "linux", feature = "iouring"))]
 use crate::driver::IoUringDriver;
 #[cfg(feature = "legacy")]
 use crate::driver::LegacyDriver;
+#[cfg(feature = "sim")]
+use crate::driver::SimDriver;
 
 // ===== basic builder structure definition =====
 
@@ -18,18 +20,49 @@ use crate::driver::LegacyDriver;
 pub struct RuntimeBuilder<D> {
     // iouring entries
     entries: Option<u32>,
+    // lifecycle hooks
+    hooks: Hooks,
+    // iouring setup options
+    uring: UringConfig,
+    // simulation options
+    #[cfg(feature = "sim")]
+    sim: crate::sim::SimConfig,
     // driver mark
     _mark: PhantomData<D>,
 }
 
 scoped_thread_local!(pub(crate) static BUILD_THREAD_ID: usize);
 
+type Hook = std::sync::Arc<dyn Fn() + Send + Sync>;
+type TaskHook = std::sync::Arc<dyn Fn(&TaskMeta<'_>) + Send + Sync>;
+
+/// Callbacks called by the runtime at some points of its lifecycle.
+#[derive(Clone, Default)]
+pub(crate) struct Hooks {
+    pub(crate) on_thread_start: Option<Hook>,
+    pub(crate) before_park: Option<Hook>,
+    pub(crate) after_unpark: Option<Hook>,
+    pub(crate) on_task_spawn: Option<TaskHook>,
+}
+
+impl Hooks {
+    pub(crate) fn call(hook: &Option<Hook>) {
+        if let Some(hook) = hook {
+            hook();
+        }
+    }
+}
+
 impl<T> Default for RuntimeBuilder<T> {
     /// Create a default runtime builder
     #[must_use]
     fn default() -> Self {
         Self {
             entries: None,
+            hooks: Hooks::default(),
+            uring: UringConfig::default(),
+            #[cfg(feature = "sim")]
+            sim: crate::sim::SimConfig::default(),
             _mark: PhantomData,
         }
     }
@@ -41,11 +74,125 @@ impl<T> RuntimeBuilder<T> {
     pub fn new() -> Self {
         Self {
             entries: None,
+            hooks: Hooks::default(),
+            uring: UringConfig::default(),
+            #[cfg(feature = "sim")]
+            sim: crate::sim::SimConfig::default(),
             _mark: PhantomData,
         }
     }
 }
 
+/// io_uring setup options, see `io_uring_setup(2)` for the kernel versions
+/// supporting each of them. Building the runtime fails if the kernel does not
+/// support an option.
+///
+/// # Examples
+///
+/// ```no_run
+/// use monoio::{IoUringDriver, RuntimeBuilder, UringConfig};
+///
+/// let config = UringConfig::new().single_issuer().defer_taskrun();
+/// let mut rt = RuntimeBuilder::<IoUringDriver>::new()
+///     .with_uring_config(config)
+///     .build()
+///     .unwrap();
+/// rt.block_on(async {
+///     // ...
+/// });
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UringConfig {
+    pub(crate) sqpoll_idle: Option<u32>,
+    pub(crate) sqpoll_cpu: Option<u32>,
+    pub(crate) cq_entries: Option<u32>,
+    pub(crate) coop_taskrun: bool,
+    pub(crate) single_issuer: bool,
+    pub(crate) defer_taskrun: bool,
+    pub(crate) attach_wq: Option<RawFd>,
+    // Bitmap of the opcodes run with their fallback.
+    pub(crate) unsupported_opcodes: [u64; 4],
+}
+
+impl UringConfig {
+    /// Create a config with the default options of the kernel.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Poll the submission queue with a kernel thread, which goes to sleep
+    /// after being idle for `idle`. Submitting does not need a syscall while
+    /// the thread is awake, at the cost of the thread spinning.
+    #[must_use]
+    pub fn sqpoll(mut self, idle: Duration) -> Self {
+        self.sqpoll_idle = Some(idle.as_millis().try_into().unwrap_or(u32::MAX));
+        self
+    }
+
+    /// Bind the submission queue polling thread to `cpu`. Only used with
+    /// [`sqpoll`](Self::sqpoll).
+    #[must_use]
+    pub fn sqpoll_cpu(mut self, cpu: u32) -> Self {
+        self.sqpoll_cpu = Some(cpu);
+        self
+    }
+
+    /// Set the size of the completion queue, which is twice the entries by
+    /// default.
+    #[must_use]
+    pub fn cq_entries(mut self, entries: u32) -> Self {
+        self.cq_entries = Some(entries);
+        self
+    }
+
+    /// Do not interrupt the runtime thread when a completion is ready. The
+    /// completion is posted on the next syscall instead, which the runtime
+    /// does at least when parking.
+    #[must_use]
+    pub fn coop_taskrun(mut self) -> Self {
+        self.coop_taskrun = true;
+        self
+    }
+
+    /// Tell the kernel only the runtime thread submits to the ring, which
+    /// is always the case with a thread-per-core runtime.
+    #[must_use]
+    pub fn single_issuer(mut self) -> Self {
+        self.single_issuer = true;
+        self
+    }
+
+    /// Defer posting completions until the runtime gets them, which batches
+    /// the kernel work. Implies [`single_issuer`](Self::single_issuer), and
+    /// can not be used with [`sqpoll`](Self::sqpoll).
+    #[must_use]
+    pub fn defer_taskrun(mut self) -> Self {
+        self.single_issuer = true;
+        self.defer_taskrun = true;
+        self
+    }
+
+    /// Share the kernel async workers of the ring `fd`, e.g. the one of
+    /// another runtime got with `AsRawFd`, instead of creating new ones. The
+    /// ring only has to be alive while building the runtime.
+    #[must_use]
+    pub fn attach_wq(mut self, fd: RawFd) -> Self {
+        self.attach_wq = Some(fd);
+        self
+    }
+
+    /// Treat the `opcodes` as not supported by the kernel, so their ops run
+    /// with the fallback of older kernels. Mostly useful to test it.
+    #[must_use]
+    pub fn unsupported_opcodes(mut self, opcodes: &[u8]) -> Self {
+        for &code in opcodes {
+            self.unsupported_opcodes[code as usize / 64] |= 1 << (code % 64);
+        }
+        self
+    }
+}
+
 // ===== buildable trait and forward methods =====
 
 /// Buildable trait.
@@ -74,6 +221,10 @@ direct_build!(TimeDriver<IoUringDriver>);
 direct_build!(LegacyDriver);
 #[cfg(feature = "legacy")]
 direct_build!(TimeDriver<LegacyDriver>);
+#[cfg(feature = "sim")]
+direct_build!(SimDriver);
+#[cfg(feature = "sim")]
+direct_build!(TimeDriver<SimDriver>);
 
 // ===== builder impl =====
 
@@ -85,12 +236,14 @@ impl Buildable for LegacyDriver {
         #[cfg(feature = "sync")]
         let thread_id = crate::utils::thread_id::gen_id();
 
+        Hooks::call(&this.hooks.on_thread_start);
         BUILD_THREAD_ID.set(&thread_id, || {
             let driver = match this.entries {
                 Some(entries) => LegacyDriver::new_with_entries(entries)?,
                 None => LegacyDriver::new()?,
             };
-            let context = crate::runtime::Context::default();
+            let mut context = crate::runtime::Context::default();
+            context.hooks = this.hooks.clone();
             Ok(Runtime { driver, context })
         })
     }
@@ -104,12 +257,33 @@ impl Buildable for IoUringDriver {
         #[cfg(feature = "sync")]
         let thread_id = crate::utils::thread_id::gen_id();
 
+        Hooks::call(&this.hooks.on_thread_start);
         BUILD_THREAD_ID.set(&thread_id, || {
             let driver = match this.entries {
-                Some(entries) => IoUringDriver::new_with_entries(entries)?,
-                None => IoUringDriver::new()?,
+                Some(entries) => IoUringDriver::new_with_entries(entries, &this.uring)?,
+                None => IoUringDriver::new(&this.uring)?,
             };
-            let context = crate::runtime::Context::default();
+            let mut context = crate::runtime::Context::default();
+            context.hooks = this.hooks.clone();
+            Ok(Runtime { driver, context })
+        })
+    }
+}
+
+#[cfg(feature = "sim")]
+impl Buildable for SimDriver {
+    fn build(this: &RuntimeBuilder<Self>) -> io::Result<Runtime<SimDriver>> {
+        #[cfg(not(feature = "sync"))]
+        let thread_id = 0;
+        #[cfg(feature = "sync")]
+        let thread_id = crate::utils::thread_id::gen_id();
+
+        Hooks::call(&this.hooks.on_thread_start);
+        BUILD_THREAD_ID.set(&thread_id, || {
+            let driver = SimDriver::new(&this.sim);
+            let mut context = crate::runtime::Context::default();
+            context.hooks = this.hooks.clone();
+            context.tasks.shuffle(this.sim.seed_for(1));
             Ok(Runtime { driver, context })
         })
     }
@@ -129,6 +303,67 @@ impl<D> RuntimeBuilder<D> {
         self.entries = Some(entries);
         self
     }
+
+    /// Set io_uring setup options. They are ignored by the legacy driver.
+    #[must_use]
+    pub fn with_uring_config(mut self, config: UringConfig) -> Self {
+        self.uring = config;
+        self
+    }
+
+    /// Set the seed and the faults of the simulation. They are ignored by the
+    /// other drivers.
+    #[cfg(feature = "sim")]
+    #[must_use]
+    pub fn with_sim_config(mut self, config: crate::sim::SimConfig) -> Self {
+        self.sim = config;
+        self
+    }
+
+    /// Set a callback called on the thread building the runtime, before the
+    /// runtime is built. It is the thread running the runtime, so it is the
+    /// place to set up thread locals such as allocators.
+    #[must_use]
+    pub fn on_thread_start<F>(mut self, f: F) -> Self
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        self.hooks.on_thread_start = Some(std::sync::Arc::new(f));
+        self
+    }
+
+    /// Set a callback called each time the runtime is going to park, waiting
+    /// for events.
+    #[must_use]
+    pub fn before_park<F>(mut self, f: F) -> Self
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        self.hooks.before_park = Some(std::sync::Arc::new(f));
+        self
+    }
+
+    /// Set a callback called each time the runtime is woken up after parking.
+    #[must_use]
+    pub fn after_unpark<F>(mut self, f: F) -> Self
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        self.hooks.after_unpark = Some(std::sync::Arc::new(f));
+        self
+    }
+
+    /// Set a callback called each time a task is spawned, in the context of
+    /// the spawning task. It gets the metadata of the new task, to tell the
+    /// tasks apart.
+    #[must_use]
+    pub fn on_task_spawn<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&TaskMeta<'_>) + Send + Sync + 'static,
+    {
+        self.hooks.on_task_spawn = Some(std::sync::Arc::new(f));
+        self
+    }
 }
 
 // ===== FusionDriver =====
@@ -145,12 +380,20 @@ impl RuntimeBuilder<FusionDriver> {
         if crate::utils::detect_uring() {
             let builder = RuntimeBuilder::<IoUringDriver> {
                 entries: self.entries,
+                hooks: self.hooks.clone(),
+                uring: self.uring,
+                #[cfg(feature = "sim")]
+                sim: self.sim,
                 _mark: PhantomData,
             };
             Ok(builder.build()?.into())
         } else {
             let builder = RuntimeBuilder::<LegacyDriver> {
                 entries: self.entries,
+                hooks: self.hooks.clone(),
+                uring: self.uring,
+                #[cfg(feature = "sim")]
+                sim: self.sim,
                 _mark: PhantomData,
             };
             Ok(builder.build()?.into())
@@ -162,6 +405,10 @@ impl RuntimeBuilder<FusionDriver> {
     pub fn build(&self) -> io::Result<crate::FusionRuntime<LegacyDriver>> {
         let builder = RuntimeBuilder::<LegacyDriver> {
             entries: self.entries,
+            hooks: self.hooks.clone(),
+            uring: self.uring,
+            #[cfg(feature = "sim")]
+            sim: self.sim,
             _mark: PhantomData,
         };
         Ok(builder.build()?.into())
@@ -172,6 +419,10 @@ impl RuntimeBuilder<FusionDriver> {
     pub fn build(&self) -> io::Result<crate::FusionRuntime<IoUringDriver>> {
         let builder = RuntimeBuilder::<IoUringDriver> {
             entries: self.entries,
+            hooks: self.hooks.clone(),
+            uring: self.uring,
+            #[cfg(feature = "sim")]
+            sim: self.sim,
             _mark: PhantomData,
         };
         Ok(builder.build()?.into())
@@ -188,12 +439,20 @@ impl RuntimeBuilder<TimeDriver<FusionDriver>> {
         if crate::utils::detect_uring() {
             let builder = RuntimeBuilder::<TimeDriver<IoUringDriver>> {
                 entries: self.entries,
+                hooks: self.hooks.clone(),
+                uring: self.uring,
+                #[cfg(feature = "sim")]
+                sim: self.sim,
                 _mark: PhantomData,
             };
             Ok(builder.build()?.into())
         } else {
             let builder = RuntimeBuilder::<TimeDriver<LegacyDriver>> {
                 entries: self.entries,
+                hooks: self.hooks.clone(),
+                uring: self.uring,
+                #[cfg(feature = "sim")]
+                sim: self.sim,
                 _mark: PhantomData,
             };
             Ok(builder.build()?.into())
@@ -205,6 +464,10 @@ impl RuntimeBuilder<TimeDriver<FusionDriver>> {
     pub fn build(&self) -> io::Result<crate::FusionRuntime<TimeDriver<LegacyDriver>>> {
         let builder = RuntimeBuilder::<TimeDriver<LegacyDriver>> {
             entries: self.entries,
+            hooks: self.hooks.clone(),
+            uring: self.uring,
+            #[cfg(feature = "sim")]
+            sim: self.sim,
             _mark: PhantomData,
         };
         Ok(builder.build()?.into())
@@ -215,6 +478,10 @@ impl RuntimeBuilder<TimeDriver<FusionDriver>> {
     pub fn build(&self) -> io::Result<crate::FusionRuntime<TimeDriver<IoUringDriver>>> {
         let builder = RuntimeBuilder::<TimeDriver<IoUringDriver>> {
             entries: self.entries,
+            hooks: self.hooks.clone(),
+            uring: self.uring,
+            #[cfg(feature = "sim")]
+            sim: self.sim,
             _mark: PhantomData,
         };
         Ok(builder.build()?.into())
@@ -230,6 +497,8 @@ mod time_wrap {
 impl time_wrap::TimeWrapable for IoUringDriver {}
 #[cfg(feature = "legacy")]
 impl time_wrap::TimeWrapable for LegacyDriver {}
+#[cfg(feature = "sim")]
+impl time_wrap::TimeWrapable for SimDriver {}
 #[cfg(any(all(target_os = "linux", feature = "iouring"), feature = "legacy"))]
 impl time_wrap::TimeWrapable for FusionDriver {}
 
@@ -244,6 +513,10 @@ where
             mut context,
         } = Buildable::build(&RuntimeBuilder::<D> {
             entries: this.entries,
+            hooks: this.hooks.clone(),
+            uring: this.uring,
+            #[cfg(feature = "sim")]
+            sim: this.sim,
             _mark: PhantomData,
         })?;
 
@@ -266,9 +539,20 @@ impl<D: time_wrap::TimeWrapable> RuntimeBuilder<D> {
     /// Enable timer
     #[must_use]
     pub fn enable_timer(self) -> RuntimeBuilder<TimeDriver<D>> {
-        let Self { entries, .. } = self;
+        let Self {
+            entries,
+            hooks,
+            uring,
+            #[cfg(feature = "sim")]
+            sim,
+            ..
+        } = self;
         RuntimeBuilder {
             entries,
+            hooks,
+            uring,
+            #[cfg(feature = "sim")]
+            sim,
             _mark: PhantomData,
         }
     }
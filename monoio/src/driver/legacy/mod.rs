@@ -243,6 +243,16 @@ impl LegacyInner {
         }
     }
 
+    // Legacy ops are executed lazily when polled, so linking is up to the caller
+    // polling them in order.
+    pub(crate) fn submit_linked<R>(
+        _this: &Rc<UnsafeCell<LegacyInner>>,
+        _hard: bool,
+        f: impl FnOnce() -> R,
+    ) -> R {
+        f()
+    }
+
     pub(crate) fn submit_with<T>(this: &Rc<UnsafeCell<LegacyInner>>, data: T) -> io::Result<Op<T>>
     where
         T: OpAble,
@@ -252,6 +262,10 @@ impl LegacyInner {
             // useless for legacy
             index: 0,
             data: Some(Box::pin(data)),
+            #[cfg(feature = "tracing")]
+            span: tracing::Span::none(),
+            #[cfg(feature = "metrics")]
+            counted: false,
         })
     }
 }
@@ -6,6 +6,8 @@ pub(crate) mod thread;
 
 #[cfg(feature = "legacy")]
 mod legacy;
+#[cfg(feature = "sim")]
+pub(crate) mod sim;
 #[cfg(all(target_os = "linux", feature = "iouring"))]
 mod uring;
 
@@ -21,11 +23,15 @@ use self::op::{CompletionMeta, Op, OpAble};
 
 #[cfg(feature = "legacy")]
 use self::legacy::LegacyInner;
+#[cfg(feature = "sim")]
+use self::sim::SimInner;
 #[cfg(all(target_os = "linux", feature = "iouring"))]
 use self::uring::UringInner;
 
 #[cfg(feature = "legacy")]
 pub use self::legacy::LegacyDriver;
+#[cfg(feature = "sim")]
+pub use self::sim::SimDriver;
 #[cfg(all(target_os = "linux", feature = "iouring"))]
 pub use self::uring::IoUringDriver;
 
@@ -86,6 +92,8 @@ pub(crate) enum Inner {
     Uring(std::rc::Rc<std::cell::UnsafeCell<UringInner>>),
     #[cfg(feature = "legacy")]
     Legacy(std::rc::Rc<std::cell::UnsafeCell<LegacyInner>>),
+    #[cfg(feature = "sim")]
+    Sim(std::rc::Rc<std::cell::RefCell<SimInner>>),
 }
 
 impl Inner {
@@ -95,6 +103,46 @@ impl Inner {
             Inner::Uring(this) => UringInner::submit_with(this, data),
             #[cfg(feature = "legacy")]
             Inner::Legacy(this) => LegacyInner::submit_with(this, data),
+            #[cfg(feature = "sim")]
+            Inner::Sim(this) => SimInner::submit_with(this, data),
+            #[cfg(all(
+                not(feature = "legacy"),
+                not(all(target_os = "linux", feature = "iouring"))
+            ))]
+            _ => {
+                util::feature_panic();
+            }
+        }
+    }
+
+    #[allow(unused)]
+    fn reserve(&self, need: usize) -> io::Result<()> {
+        match self {
+            #[cfg(all(target_os = "linux", feature = "iouring"))]
+            Inner::Uring(this) => UringInner::reserve(this, need),
+            #[cfg(feature = "legacy")]
+            Inner::Legacy(_) => Ok(()),
+            #[cfg(feature = "sim")]
+            Inner::Sim(_) => Ok(()),
+            #[cfg(all(
+                not(feature = "legacy"),
+                not(all(target_os = "linux", feature = "iouring"))
+            ))]
+            _ => {
+                util::feature_panic();
+            }
+        }
+    }
+
+    #[allow(unused)]
+    fn submit_linked<R>(&self, hard: bool, f: impl FnOnce() -> R) -> R {
+        match self {
+            #[cfg(all(target_os = "linux", feature = "iouring"))]
+            Inner::Uring(this) => UringInner::submit_linked(this, hard, f),
+            #[cfg(feature = "legacy")]
+            Inner::Legacy(this) => LegacyInner::submit_linked(this, hard, f),
+            #[cfg(feature = "sim")]
+            Inner::Sim(_) => f(),
             #[cfg(all(
                 not(feature = "legacy"),
                 not(all(target_os = "linux", feature = "iouring"))
@@ -114,9 +162,11 @@ impl Inner {
     ) -> Poll<CompletionMeta> {
         match self {
             #[cfg(all(target_os = "linux", feature = "iouring"))]
-            Inner::Uring(this) => UringInner::poll_op(this, index, cx),
+            Inner::Uring(this) => UringInner::poll_op::<T>(this, data, index, cx),
             #[cfg(feature = "legacy")]
             Inner::Legacy(this) => LegacyInner::poll_op::<T>(this, data, cx),
+            #[cfg(feature = "sim")]
+            Inner::Sim(this) => SimInner::poll_op::<T>(this, data, cx),
             #[cfg(all(
                 not(feature = "legacy"),
                 not(all(target_os = "linux", feature = "iouring"))
@@ -134,6 +184,8 @@ impl Inner {
             Inner::Uring(this) => UringInner::drop_op(this, index, data),
             #[cfg(feature = "legacy")]
             Inner::Legacy(_) => {}
+            #[cfg(feature = "sim")]
+            Inner::Sim(_) => {}
             #[cfg(all(
                 not(feature = "legacy"),
                 not(all(target_os = "linux", feature = "iouring"))
@@ -144,14 +196,115 @@ impl Inner {
         }
     }
 
-    #[cfg(all(target_os = "linux", feature = "iouring", feature = "legacy"))]
-    fn is_legacy(&self) -> bool {
-        matches!(self, Inner::Legacy(..))
+    /// Cancel all in-flight operations, returns how many are still in flight.
+    pub(crate) fn cancel_all(&self) -> usize {
+        match self {
+            #[cfg(all(target_os = "linux", feature = "iouring"))]
+            Inner::Uring(this) => UringInner::cancel_all(this),
+            // Legacy ops run inside poll, nothing is in flight.
+            #[cfg(feature = "legacy")]
+            Inner::Legacy(_) => 0,
+            #[cfg(feature = "sim")]
+            Inner::Sim(_) => 0,
+            #[cfg(all(
+                not(feature = "legacy"),
+                not(all(target_os = "linux", feature = "iouring"))
+            ))]
+            _ => {
+                util::feature_panic();
+            }
+        }
+    }
+
+    /// Leak the buffers of the operations still in flight.
+    pub(crate) fn leak_in_flight(&self) {
+        match self {
+            #[cfg(all(target_os = "linux", feature = "iouring"))]
+            Inner::Uring(this) => UringInner::leak_in_flight(this),
+            #[cfg(feature = "legacy")]
+            Inner::Legacy(_) => {}
+            #[cfg(feature = "sim")]
+            Inner::Sim(_) => {}
+            #[cfg(all(
+                not(feature = "legacy"),
+                not(all(target_os = "linux", feature = "iouring"))
+            ))]
+            _ => {
+                util::feature_panic();
+            }
+        }
+    }
+
+    pub(crate) fn in_flight(&self) -> usize {
+        match self {
+            #[cfg(all(target_os = "linux", feature = "iouring"))]
+            Inner::Uring(this) => UringInner::in_flight(this),
+            #[cfg(feature = "legacy")]
+            Inner::Legacy(_) => 0,
+            #[cfg(feature = "sim")]
+            Inner::Sim(_) => 0,
+            #[cfg(all(
+                not(feature = "legacy"),
+                not(all(target_os = "linux", feature = "iouring"))
+            ))]
+            _ => {
+                util::feature_panic();
+            }
+        }
     }
 
-    #[cfg(all(target_os = "linux", feature = "iouring", not(feature = "legacy")))]
+    #[allow(unused)]
+    pub(crate) fn is_uring_op_supported(&self, code: u8) -> bool {
+        match self {
+            #[cfg(all(target_os = "linux", feature = "iouring"))]
+            Inner::Uring(this) => UringInner::is_op_supported(this, code),
+            #[cfg(feature = "legacy")]
+            Inner::Legacy(_) => false,
+            #[cfg(feature = "sim")]
+            Inner::Sim(_) => false,
+            #[cfg(all(
+                not(feature = "legacy"),
+                not(all(target_os = "linux", feature = "iouring"))
+            ))]
+            _ => {
+                util::feature_panic();
+            }
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    pub(crate) fn fill_metrics(&self, metrics: &mut crate::metrics::RuntimeMetrics) {
+        match self {
+            #[cfg(all(target_os = "linux", feature = "iouring"))]
+            Inner::Uring(this) => UringInner::fill_metrics(this, metrics),
+            // Legacy ops run inside poll, the pending ones are the in-flight ones.
+            #[cfg(feature = "legacy")]
+            Inner::Legacy(_) => {
+                metrics.in_flight_ops = metrics.ops.iter().map(|(_, count)| count).sum();
+            }
+            // Simulated ops complete when polled.
+            #[cfg(feature = "sim")]
+            Inner::Sim(_) => {}
+            #[cfg(all(
+                not(feature = "legacy"),
+                not(all(target_os = "linux", feature = "iouring"))
+            ))]
+            _ => {
+                util::feature_panic();
+            }
+        }
+    }
+
+    // The simulator runs ops when polled too, so it takes the legacy paths.
+    #[cfg(all(target_os = "linux", feature = "iouring"))]
     fn is_legacy(&self) -> bool {
-        false
+        match self {
+            Inner::Uring(_) => false,
+            #[cfg(feature = "legacy")]
+            Inner::Legacy(_) => true,
+            #[cfg(feature = "sim")]
+            Inner::Sim(_) => true,
+        }
     }
 
     #[allow(unused)]
@@ -168,6 +321,8 @@ pub(crate) enum UnparkHandle {
     #[cfg(all(target_os = "linux", feature = "iouring"))]
     Uring(self::uring::UnparkHandle),
     Legacy(self::legacy::UnparkHandle),
+    #[cfg(feature = "sim")]
+    Sim(self::sim::UnparkHandle),
 }
 
 #[cfg(feature = "sync")]
@@ -178,6 +333,8 @@ impl unpark::Unpark for UnparkHandle {
             UnparkHandle::Uring(inner) => inner.unpark(),
             #[cfg(feature = "legacy")]
             UnparkHandle::Legacy(inner) => inner.unpark(),
+            #[cfg(feature = "sim")]
+            UnparkHandle::Sim(inner) => inner.unpark(),
             #[cfg(all(
                 not(feature = "legacy"),
                 not(all(target_os = "linux", feature = "iouring"))
@@ -202,3 +359,10 @@ impl From<self::legacy::UnparkHandle> for UnparkHandle {
         Self::Legacy(inner)
     }
 }
+
+#[cfg(all(feature = "sync", feature = "sim"))]
+impl From<self::sim::UnparkHandle> for UnparkHandle {
+    fn from(inner: self::sim::UnparkHandle) -> Self {
+        Self::Sim(inner)
+    }
+}
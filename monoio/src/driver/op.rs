@@ -4,16 +4,27 @@ use std::future::Future;
 use std::io;
 use std::pin::Pin;
 use std::task::{Context, Poll};
+use std::time::Duration;
 
 pub(crate) mod close;
 
 mod accept;
 mod connect;
+mod fadvise;
+mod fallocate;
 mod fsync;
+#[cfg(all(target_os = "linux", feature = "iouring"))]
+mod link_timeout;
 mod open;
+mod poll;
 mod read;
 mod recv;
 mod send;
+#[cfg(target_os = "linux")]
+mod sendfile;
+#[cfg(target_os = "linux")]
+mod splice;
+mod sync_file_range;
 mod write;
 
 /// In-flight operation
@@ -26,6 +37,14 @@ pub(crate) struct Op<T: 'static> {
 
     // Per-operation data
     pub(super) data: Option<Pin<Box<T>>>,
+
+    // Span of the operation, which records its result
+    #[cfg(feature = "tracing")]
+    pub(super) span: tracing::Span,
+
+    // Whether the operation is counted in the metrics of the runtime
+    #[cfg(feature = "metrics")]
+    pub(super) counted: bool,
 }
 
 /// Operation completion. Returns stored state with the result of the operation.
@@ -43,10 +62,50 @@ pub(crate) struct CompletionMeta {
     pub(crate) flags: u32,
 }
 
+/// How an operation runs with uring when the kernel does not support its
+/// opcode.
+#[cfg(all(target_os = "linux", feature = "iouring"))]
+#[allow(unused)]
+pub(crate) enum Fallback {
+    /// Fail with `ErrorKind::Unsupported`.
+    Unsupported,
+    /// Call [`OpAble::fallback_call`] right away on the runtime thread, for
+    /// operations which do not wait for long.
+    Blocking,
+    /// Call [`OpAble::fallback_start`], then wait for the fd to be ready for
+    /// the `poll(2)` events with a `POLL_ADD` and call
+    /// [`OpAble::fallback_call`].
+    Readiness(std::os::unix::prelude::RawFd, u32),
+}
+
 pub(crate) trait OpAble {
+    /// The fd the operation works on, recorded in its span.
+    #[allow(unused)]
+    fn fd(&self) -> Option<std::os::unix::prelude::RawFd> {
+        None
+    }
+
     #[cfg(all(target_os = "linux", feature = "iouring"))]
     fn uring_op(self: &mut std::pin::Pin<Box<Self>>) -> io_uring::squeue::Entry;
 
+    #[cfg(all(target_os = "linux", feature = "iouring"))]
+    fn uring_fallback(&self) -> Fallback {
+        Fallback::Unsupported
+    }
+
+    /// Run the operation with a syscall on a uring fd, which is blocking.
+    #[cfg(all(target_os = "linux", feature = "iouring"))]
+    fn fallback_call(self: &mut std::pin::Pin<Box<Self>>) -> io::Result<u32> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    /// Start a readiness fallback before waiting, returns the result if it
+    /// completes right away.
+    #[cfg(all(target_os = "linux", feature = "iouring"))]
+    fn fallback_start(self: &mut std::pin::Pin<Box<Self>>) -> Option<io::Result<u32>> {
+        None
+    }
+
     #[cfg(feature = "legacy")]
     fn legacy_interest(&self) -> Option<(super::legacy::ready::Direction, usize)>;
     #[cfg(feature = "legacy")]
@@ -62,7 +121,34 @@ impl<T> Op<T> {
     where
         T: OpAble,
     {
-        driver::CURRENT.with(|this| this.submit_with(data))
+        // The uring driver records the opcode in the current span.
+        #[cfg(feature = "tracing")]
+        let span = tracing::trace_span!(
+            target: "monoio::op",
+            "op",
+            op = crate::utils::short_name(std::any::type_name::<T>()),
+            opcode = tracing::field::Empty,
+            fd = data.fd(),
+            result = tracing::field::Empty,
+        );
+        #[cfg(feature = "tracing")]
+        let op = span
+            .in_scope(|| driver::CURRENT.with(|this| this.submit_with(data)))
+            .map(|mut op| {
+                op.span = span;
+                op
+            })?;
+        #[cfg(not(feature = "tracing"))]
+        let op = driver::CURRENT.with(|this| this.submit_with(data))?;
+        #[cfg(feature = "metrics")]
+        let mut op = op;
+        #[cfg(feature = "metrics")]
+        if crate::runtime::CURRENT.is_set() {
+            crate::runtime::CURRENT
+                .with(|ctx| ctx.counters.op_submitted(std::any::type_name::<T>()));
+            op.counted = true;
+        }
+        Ok(op)
     }
 
     /// Try submitting an operation to uring
@@ -79,6 +165,35 @@ impl<T> Op<T> {
     }
 }
 
+/// Submit the operations created in `f` as a linked chain.
+///
+/// With uring, each operation starts only after the previous one completed
+/// successfully, otherwise the rest of the chain is canceled with `ECANCELED`.
+/// With `hard`, the chain is not broken by failures. The legacy driver executes
+/// operations when polled, so callers must poll them in order and stop at the
+/// first failure to get the same semantic.
+///
+/// If the chain can not be submitted at once, all of its operations fail. So
+/// they do if one of them is not supported by the kernel, as a fallback can not
+/// be linked.
+#[allow(unused)]
+pub(crate) fn submit_linked<R>(hard: bool, f: impl FnOnce() -> R) -> R {
+    driver::CURRENT.with(|this| this.submit_linked(hard, f))
+}
+
+/// Make room to submit `need` operations, so that submitting them can not
+/// fail. Used before submitting data which could not be given back otherwise.
+#[allow(unused)]
+pub(crate) fn reserve(need: usize) -> io::Result<()> {
+    driver::CURRENT.with(|this| this.reserve(need))
+}
+
+/// Returns true if the current driver is the legacy one.
+#[allow(unused)]
+pub(crate) fn is_legacy() -> bool {
+    driver::CURRENT.with(|inner| inner.is_legacy())
+}
+
 impl<T> Future for Op<T>
 where
     T: Unpin + OpAble + 'static,
@@ -86,9 +201,16 @@ where
     type Output = Completion<T>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let coop = ready!(crate::task::coop::poll_proceed(cx));
         let me = &mut *self;
+        #[cfg(feature = "tracing")]
+        let _enter = me.span.enter();
         let data_mut = me.data.as_mut().expect("unexpected operation state");
         let meta = ready!(me.driver.poll_op::<T>(data_mut, me.index, cx));
+        coop.made_progress();
+        #[cfg(feature = "tracing")]
+        me.span
+            .record("result", tracing::field::debug(&meta.result));
 
         me.index = usize::MAX;
         let pinned_data = me.data.take().expect("unexpected operation state");
@@ -97,12 +219,145 @@ where
     }
 }
 
+/// In-flight operation with a timeout, created by [`submit_with_timeout`].
+pub(crate) struct TimeoutOp<T: 'static> {
+    op: Op<T>,
+
+    // Linked timeout, which makes the kernel cancel the operation.
+    #[cfg(all(target_os = "linux", feature = "iouring"))]
+    link: Option<Op<link_timeout::LinkTimeout>>,
+
+    // Timer raced against the operation with the legacy driver.
+    #[cfg(feature = "legacy")]
+    sleep: Option<Pin<Box<crate::time::Sleep>>>,
+
+    // The legacy driver has no timer to race, so the operation fails.
+    #[cfg(feature = "legacy")]
+    no_timer: bool,
+}
+
+/// Submit the operation created in `f` with a timeout.
+///
+/// With uring, a `LINK_TIMEOUT` is linked to the operation, so the kernel
+/// cancels it once the timeout expires. The legacy driver executes operations
+/// when polled, so a timer is raced against the operation instead, which
+/// requires the timer to be enabled. Either way, the operation completes
+/// with `ErrorKind::TimedOut` and gives back its data. Without the timer, the
+/// legacy operation is not run and completes with `ErrorKind::Unsupported`.
+#[allow(unused)]
+pub(crate) fn submit_with_timeout<T: OpAble>(
+    timeout: Duration,
+    f: impl FnOnce() -> io::Result<Op<T>>,
+) -> io::Result<TimeoutOp<T>> {
+    #[cfg(feature = "legacy")]
+    if is_legacy() {
+        let has_timer = crate::runtime::CURRENT.is_set()
+            && crate::runtime::CURRENT.with(|ctx| ctx.time_handle.is_some());
+        return Ok(TimeoutOp {
+            op: f()?,
+            #[cfg(all(target_os = "linux", feature = "iouring"))]
+            link: None,
+            sleep: has_timer.then(|| Box::pin(crate::time::sleep(timeout))),
+            no_timer: !has_timer,
+        });
+    }
+
+    #[cfg(all(target_os = "linux", feature = "iouring"))]
+    {
+        let (op, link) = submit_linked(false, || -> io::Result<_> {
+            let op = f()?;
+            let link = Op::link_timeout(timeout)?;
+            Ok((op, link))
+        })?;
+        Ok(TimeoutOp {
+            op,
+            link: Some(link),
+            #[cfg(feature = "legacy")]
+            sleep: None,
+            #[cfg(feature = "legacy")]
+            no_timer: false,
+        })
+    }
+
+    #[cfg(not(all(target_os = "linux", feature = "iouring")))]
+    unreachable!()
+}
+
+impl<T> Future for TimeoutOp<T>
+where
+    T: Unpin + OpAble + 'static,
+{
+    type Output = Completion<T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let me = &mut *self;
+        #[cfg(feature = "legacy")]
+        if me.no_timer {
+            let err = io::Error::new(
+                io::ErrorKind::Unsupported,
+                "timeouts of the legacy driver require the timer, enable it on the runtime",
+            );
+            return Poll::Ready(me.take_back(err));
+        }
+
+        #[allow(unused_mut)]
+        if let Poll::Ready(mut complete) = Pin::new(&mut me.op).poll(cx) {
+            // The linked timeout is the only one canceling the operation.
+            #[cfg(all(target_os = "linux", feature = "iouring"))]
+            if me.link.is_some() {
+                if let Err(e) = &complete.meta.result {
+                    if e.raw_os_error() == Some(libc::ECANCELED) {
+                        complete.meta.result = Err(io::ErrorKind::TimedOut.into());
+                    }
+                }
+            }
+            return Poll::Ready(complete);
+        }
+
+        #[cfg(feature = "legacy")]
+        if let Some(sleep) = me.sleep.as_mut() {
+            ready!(sleep.as_mut().poll(cx));
+            return Poll::Ready(me.take_back(io::ErrorKind::TimedOut.into()));
+        }
+
+        Poll::Pending
+    }
+}
+
+impl<T> TimeoutOp<T> {
+    // Legacy operations are not in-flight between polls, so the data can be
+    // taken back right now.
+    #[cfg(feature = "legacy")]
+    fn take_back(&mut self, err: io::Error) -> Completion<T> {
+        self.op.index = usize::MAX;
+        let pinned_data = self.op.data.take().expect("unexpected operation state");
+        let data = Box::into_inner(unsafe { Pin::into_inner_unchecked(pinned_data) });
+        Completion {
+            data,
+            meta: CompletionMeta {
+                result: Err(err),
+                flags: 0,
+            },
+        }
+    }
+}
+
 impl<T> Drop for Op<T> {
     fn drop(&mut self) {
         self.driver.drop_op(self.index, &mut self.data);
+        #[cfg(feature = "metrics")]
+        if self.counted && crate::runtime::CURRENT.is_set() {
+            crate::runtime::CURRENT.with(|ctx| ctx.counters.op_dropped(std::any::type_name::<T>()));
+        }
     }
 }
 
+// Length of a range for sync ops, which only take 32 bits. Syncing more than
+// asked is always fine, so a longer range is extended to the end of the file.
+fn range_len(len: u64) -> u32 {
+    len.try_into().unwrap_or(0)
+}
+
 #[allow(unused)]
 #[cfg(not(target_os = "linux"))]
 fn non_blocking() -> bool {
@@ -33,6 +33,10 @@ impl Op<Accept> {
 }
 
 impl OpAble for Accept {
+    fn fd(&self) -> Option<std::os::unix::io::RawFd> {
+        Some(self.fd.raw_fd())
+    }
+
     #[cfg(all(target_os = "linux", feature = "iouring"))]
     fn uring_op(self: &mut std::pin::Pin<Box<Self>>) -> io_uring::squeue::Entry {
         opcode::Accept::new(
@@ -43,6 +47,19 @@ impl OpAble for Accept {
         .build()
     }
 
+    #[cfg(all(target_os = "linux", feature = "iouring"))]
+    fn uring_fallback(&self) -> super::Fallback {
+        super::Fallback::Readiness(self.fd.raw_fd(), libc::POLLIN as _)
+    }
+
+    #[cfg(all(target_os = "linux", feature = "iouring"))]
+    fn fallback_call(self: &mut std::pin::Pin<Box<Self>>) -> io::Result<u32> {
+        let fd = self.fd.raw_fd();
+        let addr = self.addr.as_mut_ptr() as *mut _;
+        let len = &mut self.addrlen;
+        crate::syscall_u32!(accept4(fd, addr, len, libc::SOCK_CLOEXEC))
+    }
+
     #[cfg(feature = "legacy")]
     fn legacy_interest(&self) -> Option<(Direction, usize)> {
         self.fd.registered_index().map(|idx| (Direction::Read, idx))
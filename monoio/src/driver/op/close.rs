@@ -19,11 +19,25 @@ impl Op<Close> {
 }
 
 impl OpAble for Close {
+    fn fd(&self) -> Option<RawFd> {
+        Some(self.fd)
+    }
+
     #[cfg(all(target_os = "linux", feature = "iouring"))]
     fn uring_op(self: &mut std::pin::Pin<Box<Self>>) -> io_uring::squeue::Entry {
         opcode::Close::new(types::Fd(self.fd)).build()
     }
 
+    #[cfg(all(target_os = "linux", feature = "iouring"))]
+    fn uring_fallback(&self) -> super::Fallback {
+        super::Fallback::Blocking
+    }
+
+    #[cfg(all(target_os = "linux", feature = "iouring"))]
+    fn fallback_call(self: &mut std::pin::Pin<Box<Self>>) -> io::Result<u32> {
+        crate::syscall_u32!(close(self.fd))
+    }
+
     #[cfg(feature = "legacy")]
     fn legacy_interest(&self) -> Option<(Direction, usize)> {
         None
@@ -49,6 +49,10 @@ impl Op<Connect> {
 }
 
 impl OpAble for Connect {
+    fn fd(&self) -> Option<std::os::unix::io::RawFd> {
+        Some(self.fd.raw_fd())
+    }
+
     #[cfg(all(target_os = "linux", feature = "iouring"))]
     fn uring_op(self: &mut std::pin::Pin<Box<Self>>) -> io_uring::squeue::Entry {
         opcode::Connect::new(
@@ -59,6 +63,26 @@ impl OpAble for Connect {
         .build()
     }
 
+    // Connect without blocking, then wait for the socket to be writable.
+    #[cfg(all(target_os = "linux", feature = "iouring"))]
+    fn uring_fallback(&self) -> super::Fallback {
+        super::Fallback::Readiness(self.fd.raw_fd(), libc::POLLOUT as _)
+    }
+
+    #[cfg(all(target_os = "linux", feature = "iouring"))]
+    fn fallback_start(self: &mut std::pin::Pin<Box<Self>>) -> Option<io::Result<u32>> {
+        start_connect(
+            &self.fd,
+            self.os_socket_addr.as_ptr(),
+            self.os_socket_addr.len(),
+        )
+    }
+
+    #[cfg(all(target_os = "linux", feature = "iouring"))]
+    fn fallback_call(self: &mut std::pin::Pin<Box<Self>>) -> io::Result<u32> {
+        connect_result(&self.fd)
+    }
+
     #[cfg(feature = "legacy")]
     fn legacy_interest(&self) -> Option<(Direction, usize)> {
         None
@@ -102,6 +126,10 @@ impl Op<ConnectUnix> {
 }
 
 impl OpAble for ConnectUnix {
+    fn fd(&self) -> Option<std::os::unix::io::RawFd> {
+        Some(self.fd.raw_fd())
+    }
+
     #[cfg(all(target_os = "linux", feature = "iouring"))]
     fn uring_op(self: &mut std::pin::Pin<Box<Self>>) -> io_uring::squeue::Entry {
         opcode::Connect::new(
@@ -112,6 +140,25 @@ impl OpAble for ConnectUnix {
         .build()
     }
 
+    #[cfg(all(target_os = "linux", feature = "iouring"))]
+    fn uring_fallback(&self) -> super::Fallback {
+        super::Fallback::Readiness(self.fd.raw_fd(), libc::POLLOUT as _)
+    }
+
+    #[cfg(all(target_os = "linux", feature = "iouring"))]
+    fn fallback_start(self: &mut std::pin::Pin<Box<Self>>) -> Option<io::Result<u32>> {
+        start_connect(
+            &self.fd,
+            &self.socket_addr as *const _ as *const _,
+            self.socket_len,
+        )
+    }
+
+    #[cfg(all(target_os = "linux", feature = "iouring"))]
+    fn fallback_call(self: &mut std::pin::Pin<Box<Self>>) -> io::Result<u32> {
+        connect_result(&self.fd)
+    }
+
     #[cfg(feature = "legacy")]
     fn legacy_interest(&self) -> Option<(Direction, usize)> {
         None
@@ -129,3 +176,36 @@ impl OpAble for ConnectUnix {
         }
     }
 }
+
+// Connects a blocking socket without blocking, returns None if the connection
+// is in progress.
+#[cfg(all(target_os = "linux", feature = "iouring"))]
+fn start_connect(
+    fd: &SharedFd,
+    addr: *const libc::sockaddr,
+    len: libc::socklen_t,
+) -> Option<io::Result<u32>> {
+    let fd = fd.raw_fd();
+    let flags = match crate::syscall_u32!(fcntl(fd, libc::F_GETFL)) {
+        Ok(flags) => flags as libc::c_int,
+        Err(e) => return Some(Err(e)),
+    };
+    if let Err(e) = crate::syscall_u32!(fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK)) {
+        return Some(Err(e));
+    }
+    let res = crate::syscall_u32!(connect(fd, addr, len));
+    let _ = crate::syscall_u32!(fcntl(fd, libc::F_SETFL, flags));
+    match res {
+        Err(e) if e.raw_os_error() == Some(libc::EINPROGRESS) => None,
+        res => Some(res),
+    }
+}
+
+// Returns the result of a connection once the socket is writable.
+#[cfg(all(target_os = "linux", feature = "iouring"))]
+fn connect_result(fd: &SharedFd) -> io::Result<u32> {
+    match socket::getsockopt(fd.raw_fd(), sockopt::SocketError)? {
+        0 => Ok(0),
+        errno => Err(io::Error::from_raw_os_error(errno)),
+    }
+}
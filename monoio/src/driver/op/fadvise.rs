@@ -0,0 +1,98 @@
+use super::{super::shared_fd::SharedFd, Op, OpAble};
+
+#[cfg(feature = "legacy")]
+use crate::driver::legacy::ready::Direction;
+#[cfg(all(target_os = "linux", feature = "iouring"))]
+use io_uring::{opcode, types};
+
+use std::io;
+
+pub(crate) struct Fadvise {
+    #[allow(unused)]
+    fd: SharedFd,
+    #[allow(unused)]
+    offset: u64,
+    #[allow(unused)]
+    len: u64,
+    #[allow(unused)]
+    advice: i32,
+}
+
+impl Op<Fadvise> {
+    pub(crate) fn fadvise(
+        fd: &SharedFd,
+        offset: u64,
+        len: u64,
+        advice: i32,
+    ) -> io::Result<Op<Fadvise>> {
+        Op::submit_with(Fadvise {
+            fd: fd.clone(),
+            offset,
+            len,
+            advice,
+        })
+    }
+}
+
+impl OpAble for Fadvise {
+    fn fd(&self) -> Option<std::os::unix::io::RawFd> {
+        Some(self.fd.raw_fd())
+    }
+
+    #[cfg(all(target_os = "linux", feature = "iouring"))]
+    fn uring_op(self: &mut std::pin::Pin<Box<Self>>) -> io_uring::squeue::Entry {
+        opcode::Fadvise::new(types::Fd(self.fd.raw_fd()), self.len as _, self.advice)
+            .offset64(self.offset as _)
+            .build()
+    }
+
+    #[cfg(all(target_os = "linux", feature = "iouring"))]
+    fn uring_fallback(&self) -> super::Fallback {
+        super::Fallback::Blocking
+    }
+
+    #[cfg(all(target_os = "linux", feature = "iouring"))]
+    fn fallback_call(self: &mut std::pin::Pin<Box<Self>>) -> io::Result<u32> {
+        // posix_fadvise returns the error number instead of setting errno.
+        let ret = unsafe {
+            libc::posix_fadvise(
+                self.fd.raw_fd(),
+                self.offset as _,
+                self.len as _,
+                self.advice,
+            )
+        };
+        if ret != 0 {
+            return Err(io::Error::from_raw_os_error(ret));
+        }
+        Ok(0)
+    }
+
+    #[cfg(feature = "legacy")]
+    fn legacy_interest(&self) -> Option<(Direction, usize)> {
+        None
+    }
+
+    #[cfg(all(target_os = "linux", feature = "legacy"))]
+    fn legacy_call(self: &mut std::pin::Pin<Box<Self>>) -> io::Result<u32> {
+        // posix_fadvise returns the error number instead of setting errno.
+        let ret = unsafe {
+            libc::posix_fadvise(
+                self.fd.raw_fd(),
+                self.offset as _,
+                self.len as _,
+                self.advice,
+            )
+        };
+        if ret != 0 {
+            return Err(io::Error::from_raw_os_error(ret));
+        }
+        Ok(0)
+    }
+
+    // Advice is only a hint, so it is fine to ignore it where unsupported.
+    #[cfg(all(not(target_os = "linux"), feature = "legacy"))]
+    fn legacy_call(self: &mut std::pin::Pin<Box<Self>>) -> io::Result<u32> {
+        Ok(0)
+    }
+}
@@ -0,0 +1,86 @@
+use super::{super::shared_fd::SharedFd, Op, OpAble};
+
+#[cfg(feature = "legacy")]
+use crate::driver::legacy::ready::Direction;
+#[cfg(all(target_os = "linux", feature = "legacy"))]
+use crate::syscall_u32;
+#[cfg(all(target_os = "linux", feature = "iouring"))]
+use io_uring::{opcode, types};
+
+use std::io;
+
+pub(crate) struct Fallocate {
+    #[allow(unused)]
+    fd: SharedFd,
+    #[allow(unused)]
+    offset: u64,
+    #[allow(unused)]
+    len: u64,
+    #[allow(unused)]
+    mode: i32,
+}
+
+impl Op<Fallocate> {
+    pub(crate) fn fallocate(
+        fd: &SharedFd,
+        offset: u64,
+        len: u64,
+        mode: i32,
+    ) -> io::Result<Op<Fallocate>> {
+        Op::submit_with(Fallocate {
+            fd: fd.clone(),
+            offset,
+            len,
+            mode,
+        })
+    }
+}
+
+impl OpAble for Fallocate {
+    fn fd(&self) -> Option<std::os::unix::io::RawFd> {
+        Some(self.fd.raw_fd())
+    }
+
+    #[cfg(all(target_os = "linux", feature = "iouring"))]
+    fn uring_op(self: &mut std::pin::Pin<Box<Self>>) -> io_uring::squeue::Entry {
+        opcode::Fallocate64::new(types::Fd(self.fd.raw_fd()), self.len as _)
+            .offset64(self.offset as _)
+            .mode(self.mode)
+            .build()
+    }
+
+    #[cfg(all(target_os = "linux", feature = "iouring"))]
+    fn uring_fallback(&self) -> super::Fallback {
+        super::Fallback::Blocking
+    }
+
+    #[cfg(all(target_os = "linux", feature = "iouring"))]
+    fn fallback_call(self: &mut std::pin::Pin<Box<Self>>) -> io::Result<u32> {
+        crate::syscall_u32!(fallocate(
+            self.fd.raw_fd(),
+            self.mode,
+            self.offset as _,
+            self.len as _
+        ))
+    }
+
+    #[cfg(feature = "legacy")]
+    fn legacy_interest(&self) -> Option<(Direction, usize)> {
+        None
+    }
+
+    #[cfg(all(target_os = "linux", feature = "legacy"))]
+    fn legacy_call(self: &mut std::pin::Pin<Box<Self>>) -> io::Result<u32> {
+        syscall_u32!(fallocate(
+            self.fd.raw_fd(),
+            self.mode,
+            self.offset as _,
+            self.len as _
+        ))
+    }
+
+    #[cfg(all(not(target_os = "linux"), feature = "legacy"))]
+    fn legacy_call(self: &mut std::pin::Pin<Box<Self>>) -> io::Result<u32> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+}
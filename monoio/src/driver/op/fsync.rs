@@ -1,4 +1,4 @@
-use super::{super::shared_fd::SharedFd, Op, OpAble};
+use super::{super::shared_fd::SharedFd, range_len, Op, OpAble};
 
 #[cfg(feature = "legacy")]
 use crate::{driver::legacy::ready::Direction, syscall_u32};
@@ -12,33 +12,57 @@ pub(crate) struct Fsync {
     fd: SharedFd,
     #[cfg(target_os = "linux")]
     data_sync: bool,
+    // Range to sync, len 0 means until the end of the file. It is only a
+    // hint, all the drivers sync the whole file for now.
+    #[allow(unused)]
+    offset: u64,
+    #[allow(unused)]
+    len: u32,
 }
 
 impl Op<Fsync> {
     pub(crate) fn fsync(fd: &SharedFd) -> io::Result<Op<Fsync>> {
+        Self::fsync_range(fd, 0, 0)
+    }
+
+    pub(crate) fn datasync(fd: &SharedFd) -> io::Result<Op<Fsync>> {
+        Self::datasync_range(fd, 0, 0)
+    }
+
+    pub(crate) fn fsync_range(fd: &SharedFd, offset: u64, len: u64) -> io::Result<Op<Fsync>> {
         Op::submit_with(Fsync {
             fd: fd.clone(),
             #[cfg(target_os = "linux")]
             data_sync: false,
+            offset,
+            len: range_len(len),
         })
     }
 
-    pub(crate) fn datasync(fd: &SharedFd) -> io::Result<Op<Fsync>> {
+    pub(crate) fn datasync_range(fd: &SharedFd, offset: u64, len: u64) -> io::Result<Op<Fsync>> {
         Op::submit_with(Fsync {
             fd: fd.clone(),
             #[cfg(target_os = "linux")]
             data_sync: true,
+            offset,
+            len: range_len(len),
         })
     }
 }
 
 impl OpAble for Fsync {
+    fn fd(&self) -> Option<std::os::unix::io::RawFd> {
+        Some(self.fd.raw_fd())
+    }
+
     #[cfg(all(target_os = "linux", feature = "iouring"))]
     fn uring_op(self: &mut std::pin::Pin<Box<Self>>) -> io_uring::squeue::Entry {
         let mut opc = opcode::Fsync::new(types::Fd(self.fd.raw_fd()));
         if self.data_sync {
             opc = opc.flags(types::FsyncFlags::DATASYNC)
         }
+        // io-uring has no builder for the range of IORING_OP_FSYNC, so the
+        // whole file is synced.
         opc.build()
     }
 
@@ -47,6 +71,7 @@ impl OpAble for Fsync {
         None
     }
 
+    // There is no ranged fsync syscall, sync the whole file instead.
     #[cfg(all(not(target_os = "linux"), feature = "legacy"))]
     fn legacy_call(self: &mut std::pin::Pin<Box<Self>>) -> io::Result<u32> {
         syscall_u32!(fsync(self.fd.raw_fd()))
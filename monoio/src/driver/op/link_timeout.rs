@@ -0,0 +1,38 @@
+use super::{Op, OpAble};
+use crate::driver::util::timespec;
+
+#[cfg(feature = "legacy")]
+use crate::driver::legacy::ready::Direction;
+use io_uring::{opcode, types::Timespec};
+
+use std::{io, time::Duration};
+
+/// Timeout of the operation linked before it. Only used with uring.
+pub(crate) struct LinkTimeout {
+    // Read by the kernel when the sqe is submitted.
+    timespec: Timespec,
+}
+
+impl Op<LinkTimeout> {
+    pub(crate) fn link_timeout(duration: Duration) -> io::Result<Op<LinkTimeout>> {
+        Op::submit_with(LinkTimeout {
+            timespec: timespec(duration),
+        })
+    }
+}
+
+impl OpAble for LinkTimeout {
+    fn uring_op(self: &mut std::pin::Pin<Box<Self>>) -> io_uring::squeue::Entry {
+        opcode::LinkTimeout::new(&self.timespec).build()
+    }
+
+    #[cfg(feature = "legacy")]
+    fn legacy_interest(&self) -> Option<(Direction, usize)> {
+        None
+    }
+
+    #[cfg(feature = "legacy")]
+    fn legacy_call(self: &mut std::pin::Pin<Box<Self>>) -> io::Result<u32> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+}
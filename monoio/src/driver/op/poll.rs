@@ -0,0 +1,51 @@
+use super::{super::shared_fd::SharedFd, Op, OpAble};
+
+#[cfg(feature = "legacy")]
+use crate::{driver::legacy::ready::Direction, syscall_u32};
+#[cfg(all(target_os = "linux", feature = "iouring"))]
+use io_uring::{opcode, types};
+
+use std::io;
+
+/// Wait for a fd to become readable.
+pub(crate) struct PollReadable {
+    /// Holds a strong ref to the FD, preventing the file from being closed
+    /// while the operation is in-flight.
+    #[allow(unused)]
+    fd: SharedFd,
+}
+
+impl Op<PollReadable> {
+    pub(crate) fn poll_readable(fd: &SharedFd) -> io::Result<Op<PollReadable>> {
+        Op::submit_with(PollReadable { fd: fd.clone() })
+    }
+}
+
+impl OpAble for PollReadable {
+    fn fd(&self) -> Option<std::os::unix::io::RawFd> {
+        Some(self.fd.raw_fd())
+    }
+
+    #[cfg(all(target_os = "linux", feature = "iouring"))]
+    fn uring_op(self: &mut std::pin::Pin<Box<Self>>) -> io_uring::squeue::Entry {
+        opcode::PollAdd::new(types::Fd(self.fd.raw_fd()), libc::POLLIN as _).build()
+    }
+
+    #[cfg(feature = "legacy")]
+    fn legacy_interest(&self) -> Option<(Direction, usize)> {
+        self.fd.registered_index().map(|idx| (Direction::Read, idx))
+    }
+
+    #[cfg(feature = "legacy")]
+    fn legacy_call(self: &mut std::pin::Pin<Box<Self>>) -> io::Result<u32> {
+        let mut pollfd = libc::pollfd {
+            fd: self.fd.raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        match syscall_u32!(poll(&mut pollfd, 1, 0))? {
+            0 => Err(io::ErrorKind::WouldBlock.into()),
+            _ => Ok(pollfd.revents as u32),
+        }
+    }
+}
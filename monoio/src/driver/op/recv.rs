@@ -1,4 +1,4 @@
-use super::{super::shared_fd::SharedFd, Op, OpAble};
+use super::{super::shared_fd::SharedFd, Completion, Op, OpAble, TimeoutOp};
 use crate::{buf::IoBufMut, BufResult};
 
 #[cfg(all(target_os = "linux", feature = "iouring"))]
@@ -30,21 +30,34 @@ impl<T: IoBufMut> Op<Recv<T>> {
     }
 
     pub(crate) async fn read(self) -> BufResult<usize, T> {
-        let complete = self.await;
-        let res = complete.meta.result.map(|v| v as _);
-        let mut buf = complete.data.buf;
+        complete_read(self.await)
+    }
+}
+
+impl<T: IoBufMut> TimeoutOp<Recv<T>> {
+    pub(crate) async fn read(self) -> BufResult<usize, T> {
+        complete_read(self.await)
+    }
+}
+
+fn complete_read<T: IoBufMut>(complete: Completion<Recv<T>>) -> BufResult<usize, T> {
+    let res = complete.meta.result.map(|v| v as _);
+    let mut buf = complete.data.buf;
 
-        if let Ok(n) = res {
-            // Safety: the kernel wrote `n` bytes to the buffer.
-            unsafe {
-                buf.set_init(n);
-            }
+    if let Ok(n) = res {
+        // Safety: the kernel wrote `n` bytes to the buffer.
+        unsafe {
+            buf.set_init(n);
         }
-        (res, buf)
     }
+    (res, buf)
 }
 
 impl<T: IoBufMut> OpAble for Recv<T> {
+    fn fd(&self) -> Option<std::os::unix::io::RawFd> {
+        Some(self.fd.raw_fd())
+    }
+
     #[cfg(all(target_os = "linux", feature = "iouring"))]
     fn uring_op(self: &mut std::pin::Pin<Box<Self>>) -> io_uring::squeue::Entry {
         opcode::Recv::new(
@@ -55,6 +68,21 @@ impl<T: IoBufMut> OpAble for Recv<T> {
         .build()
     }
 
+    #[cfg(all(target_os = "linux", feature = "iouring"))]
+    fn uring_fallback(&self) -> super::Fallback {
+        super::Fallback::Readiness(self.fd.raw_fd(), libc::POLLIN as _)
+    }
+
+    #[cfg(all(target_os = "linux", feature = "iouring"))]
+    fn fallback_call(self: &mut std::pin::Pin<Box<Self>>) -> io::Result<u32> {
+        crate::syscall_u32!(recv(
+            self.fd.raw_fd(),
+            self.buf.write_ptr() as _,
+            self.buf.bytes_total().min(u32::MAX as usize),
+            0
+        ))
+    }
+
     #[cfg(feature = "legacy")]
     fn legacy_interest(&self) -> Option<(Direction, usize)> {
         self.fd.registered_index().map(|idx| (Direction::Read, idx))
@@ -1,4 +1,4 @@
-use super::{super::shared_fd::SharedFd, Op, OpAble};
+use super::{super::shared_fd::SharedFd, Op, OpAble, TimeoutOp};
 use crate::{buf::IoBuf, BufResult};
 
 #[cfg(all(target_os = "linux", feature = "iouring"))]
@@ -34,7 +34,18 @@ impl<T: IoBuf> Op<Send<T>> {
     }
 }
 
+impl<T: IoBuf> TimeoutOp<Send<T>> {
+    pub(crate) async fn write(self) -> BufResult<usize, T> {
+        let complete = self.await;
+        (complete.meta.result.map(|v| v as _), complete.data.buf)
+    }
+}
+
 impl<T: IoBuf> OpAble for Send<T> {
+    fn fd(&self) -> Option<std::os::unix::io::RawFd> {
+        Some(self.fd.raw_fd())
+    }
+
     #[cfg(all(target_os = "linux", feature = "iouring"))]
     fn uring_op(self: &mut std::pin::Pin<Box<Self>>) -> io_uring::squeue::Entry {
         #[cfg(feature = "zero-copy")]
@@ -66,6 +77,21 @@ impl<T: IoBuf> OpAble for Send<T> {
         .build()
     }
 
+    #[cfg(all(target_os = "linux", feature = "iouring"))]
+    fn uring_fallback(&self) -> super::Fallback {
+        super::Fallback::Readiness(self.fd.raw_fd(), libc::POLLOUT as _)
+    }
+
+    #[cfg(all(target_os = "linux", feature = "iouring"))]
+    fn fallback_call(self: &mut std::pin::Pin<Box<Self>>) -> io::Result<u32> {
+        crate::syscall_u32!(send(
+            self.fd.raw_fd(),
+            self.buf.read_ptr() as _,
+            self.buf.bytes_init(),
+            libc::MSG_NOSIGNAL
+        ))
+    }
+
     #[cfg(feature = "legacy")]
     fn legacy_interest(&self) -> Option<(Direction, usize)> {
         self.fd
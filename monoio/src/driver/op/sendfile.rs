@@ -0,0 +1,66 @@
+use super::{super::shared_fd::SharedFd, Op, OpAble};
+
+#[cfg(feature = "legacy")]
+use crate::{driver::legacy::ready::Direction, syscall_u32};
+
+use std::io;
+
+/// Send a file to a socket. Only used with the legacy driver, the uring one
+/// splices through a pipe instead.
+pub(crate) struct SendFile {
+    /// Holds a strong ref to the FDs, preventing them from being closed
+    /// while the operation is in-flight.
+    #[allow(unused)]
+    socket: SharedFd,
+    #[allow(unused)]
+    file: SharedFd,
+    #[allow(unused)]
+    offset: u64,
+    #[allow(unused)]
+    len: usize,
+}
+
+impl Op<SendFile> {
+    pub(crate) fn sendfile(
+        socket: &SharedFd,
+        file: &SharedFd,
+        offset: u64,
+        len: usize,
+    ) -> io::Result<Op<SendFile>> {
+        Op::submit_with(SendFile {
+            socket: socket.clone(),
+            file: file.clone(),
+            offset,
+            len,
+        })
+    }
+}
+
+impl OpAble for SendFile {
+    fn fd(&self) -> Option<std::os::unix::io::RawFd> {
+        Some(self.socket.raw_fd())
+    }
+
+    #[cfg(all(target_os = "linux", feature = "iouring"))]
+    fn uring_op(self: &mut std::pin::Pin<Box<Self>>) -> io_uring::squeue::Entry {
+        unreachable!("sendfile has no uring opcode")
+    }
+
+    #[cfg(feature = "legacy")]
+    fn legacy_interest(&self) -> Option<(Direction, usize)> {
+        self.socket
+            .registered_index()
+            .map(|idx| (Direction::Write, idx))
+    }
+
+    #[cfg(feature = "legacy")]
+    fn legacy_call(self: &mut std::pin::Pin<Box<Self>>) -> io::Result<u32> {
+        let mut offset = self.offset as libc::off_t;
+        syscall_u32!(sendfile(
+            self.socket.raw_fd(),
+            self.file.raw_fd(),
+            &mut offset,
+            self.len.min(u32::MAX as usize)
+        ))
+    }
+}
@@ -0,0 +1,119 @@
+use super::{super::shared_fd::SharedFd, Op, OpAble};
+
+#[cfg(feature = "legacy")]
+use crate::{driver::legacy::ready::Direction, syscall_u32};
+#[cfg(all(target_os = "linux", feature = "iouring"))]
+use io_uring::{opcode, types};
+
+use std::io;
+
+/// Move data between two fds, one of them being a pipe.
+pub(crate) struct Splice {
+    /// Holds a strong ref to the FDs, preventing them from being closed
+    /// while the operation is in-flight.
+    fd_in: SharedFd,
+    fd_out: SharedFd,
+    // Offset to read from, -1 to use the file position(required for pipes).
+    off_in: i64,
+    len: u32,
+}
+
+impl Op<Splice> {
+    pub(crate) fn splice(fd_in: &SharedFd, fd_out: &SharedFd, len: u32) -> io::Result<Op<Splice>> {
+        Op::submit_with(Splice {
+            fd_in: fd_in.clone(),
+            fd_out: fd_out.clone(),
+            off_in: -1,
+            len,
+        })
+    }
+
+    /// Splice from `fd_in` at `off_in`, which must not be a pipe.
+    pub(crate) fn splice_at(
+        fd_in: &SharedFd,
+        off_in: u64,
+        fd_out: &SharedFd,
+        len: u32,
+    ) -> io::Result<Op<Splice>> {
+        Op::submit_with(Splice {
+            fd_in: fd_in.clone(),
+            fd_out: fd_out.clone(),
+            off_in: off_in as _,
+            len,
+        })
+    }
+}
+
+impl OpAble for Splice {
+    fn fd(&self) -> Option<std::os::unix::io::RawFd> {
+        Some(self.fd_in.raw_fd())
+    }
+
+    #[cfg(all(target_os = "linux", feature = "iouring"))]
+    fn uring_op(self: &mut std::pin::Pin<Box<Self>>) -> io_uring::squeue::Entry {
+        opcode::Splice::new(
+            types::Fd(self.fd_in.raw_fd()),
+            self.off_in,
+            types::Fd(self.fd_out.raw_fd()),
+            -1,
+            self.len,
+        )
+        .flags(libc::SPLICE_F_MOVE)
+        .build()
+    }
+
+    // A socket of uring is blocking, so wait for data before.
+    #[cfg(all(target_os = "linux", feature = "iouring"))]
+    fn uring_fallback(&self) -> super::Fallback {
+        super::Fallback::Readiness(self.fd_in.raw_fd(), libc::POLLIN as _)
+    }
+
+    #[cfg(all(target_os = "linux", feature = "iouring"))]
+    fn fallback_call(self: &mut std::pin::Pin<Box<Self>>) -> io::Result<u32> {
+        let mut off_in = self.off_in;
+        let off_in_ptr = if off_in < 0 {
+            std::ptr::null_mut()
+        } else {
+            &mut off_in as *mut i64
+        };
+        crate::syscall_u32!(splice(
+            self.fd_in.raw_fd(),
+            off_in_ptr,
+            self.fd_out.raw_fd(),
+            std::ptr::null_mut(),
+            self.len as usize,
+            libc::SPLICE_F_MOVE
+        ))
+    }
+
+    // Pipes are not registered, so wait for the readiness of the other fd.
+    #[cfg(feature = "legacy")]
+    fn legacy_interest(&self) -> Option<(Direction, usize)> {
+        self.fd_in
+            .registered_index()
+            .map(|idx| (Direction::Read, idx))
+            .or_else(|| {
+                self.fd_out
+                    .registered_index()
+                    .map(|idx| (Direction::Write, idx))
+            })
+    }
+
+    #[cfg(feature = "legacy")]
+    fn legacy_call(self: &mut std::pin::Pin<Box<Self>>) -> io::Result<u32> {
+        let mut off_in = self.off_in;
+        let off_in_ptr = if off_in < 0 {
+            std::ptr::null_mut()
+        } else {
+            &mut off_in as *mut i64
+        };
+        syscall_u32!(splice(
+            self.fd_in.raw_fd(),
+            off_in_ptr,
+            self.fd_out.raw_fd(),
+            std::ptr::null_mut(),
+            self.len as usize,
+            libc::SPLICE_F_MOVE | libc::SPLICE_F_NONBLOCK
+        ))
+    }
+}
@@ -0,0 +1,70 @@
+use super::{super::shared_fd::SharedFd, range_len, Op, OpAble};
+
+#[cfg(feature = "legacy")]
+use crate::{driver::legacy::ready::Direction, syscall_u32};
+#[cfg(all(target_os = "linux", feature = "iouring"))]
+use io_uring::{opcode, types};
+
+use std::io;
+
+pub(crate) struct SyncFileRange {
+    #[allow(unused)]
+    fd: SharedFd,
+    #[allow(unused)]
+    offset: u64,
+    #[allow(unused)]
+    len: u32,
+    #[allow(unused)]
+    flags: u32,
+}
+
+impl Op<SyncFileRange> {
+    pub(crate) fn sync_file_range(
+        fd: &SharedFd,
+        offset: u64,
+        len: u64,
+        flags: u32,
+    ) -> io::Result<Op<SyncFileRange>> {
+        Op::submit_with(SyncFileRange {
+            fd: fd.clone(),
+            offset,
+            len: range_len(len),
+            flags,
+        })
+    }
+}
+
+impl OpAble for SyncFileRange {
+    fn fd(&self) -> Option<std::os::unix::io::RawFd> {
+        Some(self.fd.raw_fd())
+    }
+
+    #[cfg(all(target_os = "linux", feature = "iouring"))]
+    fn uring_op(self: &mut std::pin::Pin<Box<Self>>) -> io_uring::squeue::Entry {
+        opcode::SyncFileRange::new(types::Fd(self.fd.raw_fd()), self.len)
+            .offset(self.offset as _)
+            .flags(self.flags)
+            .build()
+    }
+
+    #[cfg(feature = "legacy")]
+    fn legacy_interest(&self) -> Option<(Direction, usize)> {
+        None
+    }
+
+    #[cfg(all(target_os = "linux", feature = "legacy"))]
+    fn legacy_call(self: &mut std::pin::Pin<Box<Self>>) -> io::Result<u32> {
+        syscall_u32!(sync_file_range(
+            self.fd.raw_fd(),
+            self.offset as _,
+            self.len as _,
+            self.flags
+        ))
+    }
+
+    // sync_file_range is linux only, fsync is the closest thing elsewhere.
+    #[cfg(all(not(target_os = "linux"), feature = "legacy"))]
+    fn legacy_call(self: &mut std::pin::Pin<Box<Self>>) -> io::Result<u32> {
+        syscall_u32!(fsync(self.fd.raw_fd()))
+    }
+}
@@ -48,6 +48,12 @@ enum UringState {
     Closed,
 }
 
+/// IO types backed by a SharedFd.
+pub(crate) trait AsSharedFd {
+    /// Returns the SharedFd of the IO type.
+    fn as_shared_fd(&self) -> &SharedFd;
+}
+
 impl AsRawFd for SharedFd {
     fn as_raw_fd(&self) -> RawFd {
         self.raw_fd()
@@ -65,14 +71,16 @@ impl SharedFd {
             super::Inner::Uring(_) => None,
             super::Inner::Legacy(inner) => {
                 let mut source = mio::unix::SourceFd(&fd);
-                Some(super::legacy::LegacyDriver::register(
-                    inner,
-                    &mut source,
-                    RW_INTERESTS,
-                ))
+                Some(
+                    super::legacy::LegacyDriver::register(inner, &mut source, RW_INTERESTS)
+                        .map(Some),
+                )
             }
+            // The simulation does not poll real fds.
+            #[cfg(feature = "sim")]
+            super::Inner::Sim(_) => Some(Ok(None)),
         }) {
-            Some(reg) => State::Legacy(Some(reg?)),
+            Some(reg) => State::Legacy(reg?),
             None => State::Uring(UringState::Init),
         };
 
@@ -85,10 +93,14 @@ impl SharedFd {
                 super::Inner::Legacy(inner) => {
                     let mut source = mio::unix::SourceFd(&fd);
                     super::legacy::LegacyDriver::register(inner, &mut source, RW_INTERESTS)
+                        .map(Some)
                 }
+                // The simulation does not poll real fds.
+                #[cfg(feature = "sim")]
+                super::Inner::Sim(_) => Ok(None),
             });
 
-            State::Legacy(Some(reg?))
+            State::Legacy(reg?)
         };
 
         #[cfg(all(
@@ -114,6 +126,8 @@ impl SharedFd {
             super::Inner::Uring(_) => State::Uring(UringState::Init),
             #[cfg(feature = "legacy")]
             super::Inner::Legacy(_) => State::Legacy(None),
+            #[cfg(feature = "sim")]
+            super::Inner::Sim(_) => State::Legacy(None),
             #[cfg(all(
                 not(feature = "legacy"),
                 not(all(target_os = "linux", feature = "iouring"))
@@ -166,6 +180,8 @@ impl SharedFd {
                                         );
                                     }
                                 }
+                                #[cfg(feature = "sim")]
+                                super::Inner::Sim(_) => {}
                             }
                         })
                     }
@@ -300,6 +316,8 @@ impl Drop for Inner {
                                     );
                                 }
                             }
+                            #[cfg(feature = "sim")]
+                            super::Inner::Sim(_) => {}
                         }
                     })
                 }
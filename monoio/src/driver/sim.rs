@@ -0,0 +1,311 @@
+//! Deterministic simulation driver.
+
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    io,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use super::{
+    op::{CompletionMeta, Op, OpAble},
+    Driver, Inner, CURRENT,
+};
+use crate::{
+    sim::{net::Network, SimConfig},
+    utils::rand::FastRand,
+};
+
+type Event = Box<dyn FnOnce()>;
+
+/// Driver running a deterministic simulation, see [`sim`](crate::sim).
+///
+/// Time is virtual: parking advances the clock to the next timer or network
+/// event right away. So a run only depends on the seed of its
+/// [`SimConfig`].
+pub struct SimDriver {
+    inner: Rc<RefCell<SimInner>>,
+
+    // Used for drop
+    #[cfg(feature = "sync")]
+    thread_id: usize,
+}
+
+pub(crate) struct SimInner {
+    config: SimConfig,
+    rng: FastRand,
+    // The virtual clock starts at the real time the driver was created.
+    base: std::time::Instant,
+    elapsed: Duration,
+    // Pending events by time, then by order of scheduling.
+    events: BTreeMap<(Duration, u64), Event>,
+    next_event: u64,
+    pub(crate) net: Network,
+
+    // Waker receiver
+    #[cfg(feature = "sync")]
+    waker_receiver: flume::Receiver<std::task::Waker>,
+}
+
+impl SimDriver {
+    pub(crate) fn new(config: &SimConfig) -> Self {
+        let base = std::time::Instant::now();
+        crate::time::clock::set_virtual(Some(base));
+
+        #[cfg(feature = "sync")]
+        let thread_id = crate::builder::BUILD_THREAD_ID.with(|id| *id);
+        #[cfg(feature = "sync")]
+        let (waker_sender, waker_receiver) = flume::unbounded::<std::task::Waker>();
+
+        let inner = SimInner {
+            config: *config,
+            rng: FastRand::new(config.seed_for(0)),
+            base,
+            elapsed: Duration::ZERO,
+            events: BTreeMap::new(),
+            next_event: 0,
+            net: Network::new(),
+            #[cfg(feature = "sync")]
+            waker_receiver,
+        };
+
+        // Register unpark handle
+        #[cfg(feature = "sync")]
+        {
+            super::thread::register_unpark_handle(thread_id, UnparkHandle(()).into());
+            super::thread::register_waker_sender(thread_id, waker_sender);
+        }
+
+        Self {
+            inner: Rc::new(RefCell::new(inner)),
+            #[cfg(feature = "sync")]
+            thread_id,
+        }
+    }
+
+    fn inner_park(&self, timeout: Option<Duration>) -> io::Result<()> {
+        #[cfg(feature = "sync")]
+        let timeout = if self.wake_foreign() {
+            Some(Duration::ZERO)
+        } else {
+            timeout
+        };
+
+        let target = {
+            let inner = self.inner.borrow();
+            let next = inner.events.keys().next().map(|&(at, _)| at);
+            match (next, timeout) {
+                (Some(at), Some(timeout)) => at.min(inner.elapsed + timeout),
+                (Some(at), None) => at,
+                (None, Some(timeout)) => inner.elapsed + timeout,
+                // Nothing can wake the runtime but another thread.
+                #[cfg(feature = "sync")]
+                (None, None) => {
+                    let receiver = inner.waker_receiver.clone();
+                    drop(inner);
+                    if let Ok(w) = receiver.recv() {
+                        w.wake();
+                    }
+                    return Ok(());
+                }
+                #[cfg(not(feature = "sync"))]
+                (None, None) => {
+                    panic!("simulation deadlocked: all the tasks are waiting for nothing")
+                }
+            }
+        };
+        self.advance(target);
+        Ok(())
+    }
+
+    // Process foreign wakers, returning whether there was any.
+    #[cfg(feature = "sync")]
+    fn wake_foreign(&self) -> bool {
+        let receiver = self.inner.borrow().waker_receiver.clone();
+        let mut woken = false;
+        while let Ok(w) = receiver.try_recv() {
+            w.wake();
+            woken = true;
+        }
+        woken
+    }
+
+    // Moves the clock forward to `target`, and runs the events due until then.
+    fn advance(&self, target: Duration) {
+        {
+            let mut inner = self.inner.borrow_mut();
+            if target > inner.elapsed {
+                inner.elapsed = target;
+                crate::time::clock::set_virtual(Some(inner.base + target));
+            }
+        }
+        loop {
+            let event = {
+                let mut inner = self.inner.borrow_mut();
+                let inner = &mut *inner;
+                match inner.events.first_entry() {
+                    Some(entry) if entry.key().0 <= inner.elapsed => entry.remove(),
+                    _ => break,
+                }
+            };
+            // Events wake tasks, and may drop sockets which schedule others.
+            event();
+        }
+    }
+}
+
+impl SimInner {
+    pub(crate) fn config(&self) -> &SimConfig {
+        &self.config
+    }
+
+    /// Returns the virtual time elapsed since the start of the simulation.
+    pub(crate) fn now(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Returns true with the probability `rate`.
+    pub(crate) fn chance(&self, rate: f64) -> bool {
+        self.rng.chance(rate)
+    }
+
+    /// Returns a random number in `0..n`.
+    pub(crate) fn rand_n(&self, n: u32) -> u32 {
+        self.rng.fastrand_n(n)
+    }
+
+    /// Returns a random latency of the network, including the retransmit
+    /// timeout of a dropped packet.
+    pub(crate) fn latency(&self) -> Duration {
+        let SimConfig {
+            min_latency,
+            max_latency,
+            drop_rate,
+            retransmit_timeout,
+            ..
+        } = self.config;
+        let span = (max_latency - min_latency).as_micros() as u32;
+        let mut latency = min_latency;
+        if span != 0 {
+            latency += Duration::from_micros(self.rng.fastrand_n(span + 1) as u64);
+        }
+        if self.chance(drop_rate) {
+            latency += retransmit_timeout;
+        }
+        latency
+    }
+
+    /// Runs `event` once the virtual clock reaches `at`.
+    pub(crate) fn schedule_at(&mut self, at: Duration, event: impl FnOnce() + 'static) {
+        let id = self.next_event;
+        self.next_event += 1;
+        self.events
+            .insert((at.max(self.elapsed), id), Box::new(event));
+    }
+
+    // Ops on real fds are not simulated, they complete with an error.
+    pub(crate) fn submit_with<T: OpAble>(
+        this: &Rc<RefCell<SimInner>>,
+        data: T,
+    ) -> io::Result<Op<T>> {
+        Ok(Op {
+            driver: Inner::Sim(this.clone()),
+            // useless for sim
+            index: 0,
+            data: Some(Box::pin(data)),
+            #[cfg(feature = "tracing")]
+            span: tracing::Span::none(),
+            #[cfg(feature = "metrics")]
+            counted: false,
+        })
+    }
+
+    pub(crate) fn poll_op<T: OpAble>(
+        _this: &Rc<RefCell<SimInner>>,
+        _data: &mut Pin<Box<T>>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<CompletionMeta> {
+        Poll::Ready(CompletionMeta {
+            result: Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "operation not supported by the simulation driver",
+            )),
+            flags: 0,
+        })
+    }
+}
+
+/// Runs `f` with the simulation driver of the current thread.
+pub(crate) fn with_current<R>(f: impl FnOnce(&mut SimInner) -> R) -> io::Result<R> {
+    let not_sim = || {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            "simulated sockets must be used inside a simulation runtime",
+        )
+    };
+    if !CURRENT.is_set() {
+        return Err(not_sim());
+    }
+    CURRENT.with(|inner| match inner {
+        Inner::Sim(this) => Ok(f(&mut this.borrow_mut())),
+        _ => Err(not_sim()),
+    })
+}
+
+impl Driver for SimDriver {
+    fn with<R>(&self, f: impl FnOnce() -> R) -> R {
+        let inner = Inner::Sim(self.inner.clone());
+        CURRENT.set(&inner, f)
+    }
+
+    fn submit(&self) -> io::Result<()> {
+        // Run the events due without moving the clock.
+        self.park_timeout(Duration::ZERO)
+    }
+
+    fn park(&self) -> io::Result<()> {
+        self.inner_park(None)
+    }
+
+    fn park_timeout(&self, duration: Duration) -> io::Result<()> {
+        self.inner_park(Some(duration))
+    }
+
+    #[cfg(feature = "sync")]
+    type Unpark = UnparkHandle;
+
+    #[cfg(feature = "sync")]
+    fn unpark(&self) -> Self::Unpark {
+        UnparkHandle(())
+    }
+}
+
+impl Drop for SimDriver {
+    fn drop(&mut self) {
+        crate::time::clock::set_virtual(None);
+
+        // Deregister thread id
+        #[cfg(feature = "sync")]
+        {
+            use crate::driver::thread::{unregister_unpark_handle, unregister_waker_sender};
+            unregister_unpark_handle(self.thread_id);
+            unregister_waker_sender(self.thread_id);
+        }
+    }
+}
+
+/// Unparks a simulation runtime. The wakers sent from other threads already
+/// wake it up, so there is nothing to do.
+#[cfg(feature = "sync")]
+#[derive(Clone)]
+pub struct UnparkHandle(());
+
+#[cfg(feature = "sync")]
+impl super::unpark::Unpark for UnparkHandle {
+    fn unpark(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
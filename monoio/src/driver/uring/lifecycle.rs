@@ -24,6 +24,13 @@ pub(crate) enum Lifecycle {
     Completed(io::Result<u32>, u32),
 }
 
+impl Lifecycle {
+    /// Returns true if the kernel has not completed the operation yet.
+    pub(crate) fn is_in_flight(&self) -> bool {
+        !matches!(self, Lifecycle::Completed(..))
+    }
+}
+
 impl<'a> Ref<'a, Lifecycle> {
     pub(crate) fn complete(mut self, result: io::Result<u32>, flags: u32) {
         let ref_mut = &mut *self;
@@ -12,12 +12,13 @@ use std::{
 };
 
 use super::{
-    op::{CompletionMeta, Op, OpAble},
+    op::{CompletionMeta, Fallback, Op, OpAble},
     util::timespec,
     Driver, Inner, CURRENT,
 };
-use crate::utils::slab::Slab;
-use io_uring::{cqueue, opcode, types::Timespec, IoUring};
+use crate::{builder::UringConfig, utils::slab::Slab};
+use fxhash::FxHashSet;
+use io_uring::{cqueue, opcode, squeue, types::Timespec, IoUring, Probe};
 use lifecycle::Lifecycle;
 
 mod lifecycle;
@@ -26,7 +27,6 @@ mod waker;
 #[cfg(feature = "sync")]
 pub(crate) use waker::UnparkHandle;
 
-#[allow(unused)]
 pub(crate) const CANCEL_USERDATA: u64 = u64::MAX;
 pub(crate) const TIMEOUT_USERDATA: u64 = u64::MAX - 1;
 #[allow(unused)]
@@ -34,6 +34,10 @@ pub(crate) const EVENTFD_USERDATA: u64 = u64::MAX - 2;
 
 pub(crate) const MIN_REVERSED_USERDATA: u64 = u64::MAX - 2;
 
+// Not exported by io_uring.
+const IORING_ENTER_GETEVENTS: u32 = 1;
+const IORING_ENTER_SQ_WAKEUP: u32 = 2;
+
 /// Driver with uring.
 pub struct IoUringDriver {
     inner: Rc<UnsafeCell<UringInner>>,
@@ -57,6 +61,28 @@ pub(crate) struct UringInner {
     /// IoUring bindings
     uring: ManuallyDrop<IoUring>,
 
+    /// SQEs of the linked chain being built, with their op index
+    linked: Option<Vec<(usize, squeue::Entry)>>,
+
+    /// Setup with COOP_TASKRUN
+    coop_taskrun: bool,
+
+    /// Setup with DEFER_TASKRUN
+    defer_taskrun: bool,
+
+    /// Opcodes supported by the kernel, None if it can not be probed
+    probe: Option<Probe>,
+
+    /// Bitmap of the opcodes treated as not supported
+    unsupported_opcodes: [u64; 4],
+
+    /// Ops waiting for readiness to run their fallback
+    fallbacks: FxHashSet<usize>,
+
+    /// Number of submissions
+    #[cfg(feature = "metrics")]
+    submit_batches: u64,
+
     /// Shared waker
     #[cfg(feature = "sync")]
     shared_waker: std::sync::Arc<waker::EventWaker>,
@@ -74,22 +100,72 @@ pub(crate) struct UringInner {
 // type wraps the slab and ensures that, on drop, the slab is empty.
 struct Ops {
     slab: Slab<Lifecycle>,
+    // Leak the slab on drop, as the kernel may still write to the buffers of
+    // the ops in flight
+    leak: bool,
 }
 
 impl IoUringDriver {
     const DEFAULT_ENTRIES: u32 = 1024;
 
-    pub(crate) fn new() -> io::Result<IoUringDriver> {
-        Self::new_with_entries(Self::DEFAULT_ENTRIES)
+    pub(crate) fn new(config: &UringConfig) -> io::Result<IoUringDriver> {
+        Self::new_with_entries(Self::DEFAULT_ENTRIES, config)
+    }
+
+    fn new_uring(entries: u32, config: &UringConfig) -> io::Result<IoUring> {
+        let mut builder = IoUring::builder();
+        if let Some(idle) = config.sqpoll_idle {
+            builder.setup_sqpoll(idle);
+            if let Some(cpu) = config.sqpoll_cpu {
+                builder.setup_sqpoll_cpu(cpu);
+            }
+        }
+        if let Some(cq_entries) = config.cq_entries {
+            builder.setup_cqsize(cq_entries);
+        }
+        if config.coop_taskrun {
+            // The flag tells when completions are waiting for a syscall.
+            builder.setup_coop_taskrun().setup_taskrun_flag();
+        }
+        if config.single_issuer {
+            builder.setup_single_issuer();
+        }
+        if config.defer_taskrun {
+            builder.setup_defer_taskrun();
+        }
+        if let Some(fd) = config.attach_wq {
+            builder.setup_attach_wq(fd);
+        }
+        builder.build(entries)
+    }
+
+    // Kernels before 5.6 can not be probed, they are assumed to support all
+    // the opcodes as before.
+    fn probe(uring: &IoUring) -> Option<Probe> {
+        let mut probe = Probe::new();
+        uring.submitter().register_probe(&mut probe).ok()?;
+        Some(probe)
     }
 
     #[cfg(not(feature = "sync"))]
-    pub(crate) fn new_with_entries(entries: u32) -> io::Result<IoUringDriver> {
-        let uring = ManuallyDrop::new(IoUring::new(entries)?);
+    pub(crate) fn new_with_entries(
+        entries: u32,
+        config: &UringConfig,
+    ) -> io::Result<IoUringDriver> {
+        let uring = ManuallyDrop::new(Self::new_uring(entries, config)?);
+        let probe = Self::probe(&uring);
 
         let inner = Rc::new(UnsafeCell::new(UringInner {
             ops: Ops::new(),
             uring,
+            linked: None,
+            coop_taskrun: config.coop_taskrun,
+            defer_taskrun: config.defer_taskrun,
+            probe,
+            unsupported_opcodes: config.unsupported_opcodes,
+            fallbacks: FxHashSet::default(),
+            #[cfg(feature = "metrics")]
+            submit_batches: 0,
         }));
 
         Ok(IoUringDriver {
@@ -99,8 +175,12 @@ impl IoUringDriver {
     }
 
     #[cfg(feature = "sync")]
-    pub(crate) fn new_with_entries(entries: u32) -> io::Result<IoUringDriver> {
-        let uring = ManuallyDrop::new(IoUring::new(entries)?);
+    pub(crate) fn new_with_entries(
+        entries: u32,
+        config: &UringConfig,
+    ) -> io::Result<IoUringDriver> {
+        let uring = ManuallyDrop::new(Self::new_uring(entries, config)?);
+        let probe = Self::probe(&uring);
 
         // Create eventfd and register it to the ring.
         let waker = {
@@ -116,6 +196,14 @@ impl IoUringDriver {
         let inner = Rc::new(UnsafeCell::new(UringInner {
             ops: Ops::new(),
             uring,
+            linked: None,
+            coop_taskrun: config.coop_taskrun,
+            defer_taskrun: config.defer_taskrun,
+            probe,
+            unsupported_opcodes: config.unsupported_opcodes,
+            fallbacks: FxHashSet::default(),
+            #[cfg(feature = "metrics")]
+            submit_batches: 0,
             shared_waker: std::sync::Arc::new(waker::EventWaker::new(waker)),
             eventfd_installed: false,
             waker_receiver,
@@ -148,6 +236,11 @@ impl IoUringDriver {
         if sq.len() + need > sq.capacity() {
             drop(sq);
             inner.submit()?;
+            // With SQPOLL, the kernel thread consumes the queue asynchronously.
+            let capacity = inner.uring.submission().capacity();
+            while inner.uring.submission().len() + need > capacity {
+                inner.uring.submitter().squeue_wait()?;
+            }
         }
         Ok(())
     }
@@ -231,10 +324,18 @@ impl IoUringDriver {
             }
 
             // Submit and Wait
-            inner.uring.submit_and_wait(1)?;
+            let _submitted = inner.uring.submit_and_wait(1)?;
+            #[cfg(feature = "tracing")]
+            tracing::trace!(target: "monoio::driver", submitted = _submitted, "submit and wait");
         } else {
             // Submit only
-            inner.uring.submit()?;
+            let _submitted = inner.submit_nowait()?;
+            #[cfg(feature = "tracing")]
+            tracing::trace!(target: "monoio::driver", submitted = _submitted, "submit");
+        }
+        #[cfg(feature = "metrics")]
+        {
+            inner.submit_batches += 1;
         }
 
         // Set status as awake
@@ -302,10 +403,39 @@ impl UringInner {
         }
     }
 
+    // Submit without waiting. Completions are posted by kernel task work,
+    // which only runs when getting events with DEFER_TASKRUN, and may be
+    // waiting for a syscall with COOP_TASKRUN, so events are got if needed.
+    fn submit_nowait(&mut self) -> io::Result<usize> {
+        let mut sq = self.uring.submission();
+        if !self.defer_taskrun && !(self.coop_taskrun && sq.taskrun()) {
+            drop(sq);
+            return self.uring.submit();
+        }
+        let mut flags = IORING_ENTER_GETEVENTS;
+        if sq.need_wakeup() {
+            flags |= IORING_ENTER_SQ_WAKEUP;
+        }
+        sq.sync();
+        let len = sq.len();
+        drop(sq);
+        unsafe {
+            self.uring
+                .submitter()
+                .enter::<libc::sigset_t>(len as _, 0, flags, None)
+        }
+    }
+
     fn submit(&mut self) -> io::Result<()> {
         loop {
-            match self.uring.submit() {
-                Ok(_) => {
+            match self.submit_nowait() {
+                Ok(_submitted) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::trace!(target: "monoio::driver", submitted = _submitted, "submit");
+                    #[cfg(feature = "metrics")]
+                    {
+                        self.submit_batches += 1;
+                    }
                     self.uring.submission().sync();
                     return Ok(());
                 }
@@ -327,6 +457,10 @@ impl UringInner {
             driver,
             index: inner.ops.insert(),
             data: Some(Box::pin(data)),
+            #[cfg(feature = "tracing")]
+            span: tracing::Span::none(),
+            #[cfg(feature = "metrics")]
+            counted: false,
         }
     }
 
@@ -336,16 +470,49 @@ impl UringInner {
     {
         let inner = unsafe { &mut *this.get() };
         // If the submission queue is full, flush it to the kernel
-        if inner.uring.submission().is_full() {
-            inner.submit()?;
-        }
+        IoUringDriver::flush_space(inner, 1)?;
 
         // Create the operation
         let mut op = Self::new_op(data, inner, Inner::Uring(this.clone()));
 
         // Configure the SQE
         let pinned_data = unsafe { op.data.as_mut().unwrap_unchecked() };
-        let sqe = OpAble::uring_op(pinned_data).user_data(op.index as _);
+        let mut sqe = OpAble::uring_op(pinned_data).user_data(op.index as _);
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("opcode", opcode_of(&sqe));
+
+        // Run the fallback if the kernel does not support the opcode. It can
+        // not be linked, so a chain with it fails when submitted.
+        if inner.linked.is_none() && !inner.supports(opcode_of(&sqe)) {
+            match pinned_data.uring_fallback() {
+                Fallback::Unsupported => {
+                    let err = io::ErrorKind::Unsupported.into();
+                    inner.ops.complete(op.index, Err(err), 0);
+                    return Ok(op);
+                }
+                Fallback::Blocking => {
+                    let result = OpAble::fallback_call(pinned_data);
+                    inner.ops.complete(op.index, result, 0);
+                    return Ok(op);
+                }
+                Fallback::Readiness(fd, events) => {
+                    if let Some(result) = OpAble::fallback_start(pinned_data) {
+                        inner.ops.complete(op.index, result, 0);
+                        return Ok(op);
+                    }
+                    inner.fallbacks.insert(op.index);
+                    sqe = opcode::PollAdd::new(io_uring::types::Fd(fd), events)
+                        .build()
+                        .user_data(op.index as _);
+                }
+            }
+        }
+
+        // Defer the push if we are building a linked chain
+        if let Some(linked) = inner.linked.as_mut() {
+            linked.push((op.index, sqe));
+            return Ok(op);
+        }
 
         {
             let mut sq = inner.uring.submission();
@@ -368,14 +535,86 @@ impl UringInner {
         Ok(op)
     }
 
-    pub(crate) fn poll_op(
+    pub(crate) fn submit_linked<R>(
+        this: &Rc<UnsafeCell<UringInner>>,
+        hard: bool,
+        f: impl FnOnce() -> R,
+    ) -> R {
+        {
+            let inner = unsafe { &mut *this.get() };
+            debug_assert!(inner.linked.is_none(), "linked chains can not be nested");
+            inner.linked = Some(Vec::new());
+        }
+
+        // Ops submitted inside f will be collected instead of pushed.
+        let guard = LinkedGuard(this);
+        let r = f();
+        std::mem::forget(guard);
+
+        let inner = unsafe { &mut *this.get() };
+        let linked = inner.linked.take().unwrap_or_default();
+        if linked.is_empty() {
+            return r;
+        }
+
+        // The whole chain must land in the same submission, or the kernel will
+        // break the link. If it can not, or an op of the chain is not
+        // supported, fail all the ops of the chain.
+        let err = if linked
+            .iter()
+            .any(|(_, sqe)| !inner.supports(opcode_of(sqe)))
+        {
+            Some(io::ErrorKind::Unsupported.into())
+        } else if linked.len() > inner.uring.submission().capacity() {
+            Some(io::Error::from_raw_os_error(libc::EINVAL))
+        } else {
+            IoUringDriver::flush_space(inner, linked.len()).err()
+        };
+        if let Some(err) = err {
+            let (kind, errno) = (err.kind(), err.raw_os_error());
+            for (index, _) in linked {
+                let err = match errno {
+                    Some(errno) => io::Error::from_raw_os_error(errno),
+                    None => kind.into(),
+                };
+                inner.ops.complete(index, Err(err), 0);
+            }
+            return r;
+        }
+
+        let link_flag = if hard {
+            squeue::Flags::IO_HARDLINK
+        } else {
+            squeue::Flags::IO_LINK
+        };
+        let last = linked.len() - 1;
+        let mut sq = inner.uring.submission();
+        for (i, (_, sqe)) in linked.into_iter().enumerate() {
+            let sqe = if i == last { sqe } else { sqe.flags(link_flag) };
+            if unsafe { sq.push(&sqe).is_err() } {
+                unreachable!("space has been flushed");
+            }
+        }
+        r
+    }
+
+    pub(crate) fn poll_op<T: OpAble>(
         this: &Rc<UnsafeCell<UringInner>>,
+        data: &mut Pin<Box<T>>,
         index: usize,
         cx: &mut Context<'_>,
     ) -> Poll<CompletionMeta> {
         let inner = unsafe { &mut *this.get() };
         let lifecycle = unsafe { inner.ops.slab.get(index).unwrap_unchecked() };
-        lifecycle.poll_op(cx)
+        let meta = ready!(lifecycle.poll_op(cx));
+        if !inner.fallbacks.remove(&index) || meta.result.is_err() {
+            return Poll::Ready(meta);
+        }
+        // The fd is ready, so the syscall does not block.
+        Poll::Ready(CompletionMeta {
+            result: OpAble::fallback_call(data),
+            flags: 0,
+        })
     }
 
     pub(crate) fn drop_op<T: 'static>(
@@ -388,19 +627,89 @@ impl UringInner {
             // already finished
             return;
         }
+        inner.fallbacks.remove(&index);
         if let Some(lifecycle) = inner.ops.slab.get(index) {
             let _must_finished = lifecycle.drop_op(data);
-            #[cfg(features = "async-cancel")]
+            #[cfg(feature = "async-cancel")]
             if !_must_finished {
-                unsafe {
-                    let cancel = io_uring::opcode::AsyncCancel::new(index as u64).build();
+                inner.cancel(index);
+            }
+        }
+    }
 
-                    // Try push cancel, if failed, will submit and re-push.
-                    if inner.uring.submission().push(&cancel).is_err() {
-                        let _ = inner.submit();
-                        let _ = inner.uring.submission().push(&cancel);
-                    }
-                }
+    /// Cancel all in-flight operations, returns how many are still in flight.
+    /// Their buffers are held until they complete.
+    pub(crate) fn cancel_all(this: &Rc<UnsafeCell<UringInner>>) -> usize {
+        let inner = unsafe { &mut *this.get() };
+        let in_flight = inner
+            .ops
+            .slab
+            .iter()
+            .filter(|(_, lifecycle)| lifecycle.is_in_flight())
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+        for index in in_flight.iter() {
+            inner.cancel(*index);
+        }
+        let _ = inner.submit();
+        in_flight.len()
+    }
+
+    #[cfg(feature = "metrics")]
+    pub(crate) fn fill_metrics(
+        this: &Rc<UnsafeCell<UringInner>>,
+        metrics: &mut crate::metrics::RuntimeMetrics,
+    ) {
+        let inner = unsafe { &mut *this.get() };
+        metrics.in_flight_ops = inner.ops.slab.len();
+        metrics.submit_batches = inner.submit_batches;
+        metrics.cq_overflow = inner.uring.completion().overflow() as u64;
+    }
+
+    pub(crate) fn in_flight(this: &Rc<UnsafeCell<UringInner>>) -> usize {
+        let inner = unsafe { &*this.get() };
+        inner
+            .ops
+            .slab
+            .iter()
+            .filter(|(_, lifecycle)| lifecycle.is_in_flight())
+            .count()
+    }
+
+    pub(crate) fn reserve(this: &Rc<UnsafeCell<UringInner>>, need: usize) -> io::Result<()> {
+        let inner = unsafe { &mut *this.get() };
+        IoUringDriver::flush_space(inner, need)
+    }
+
+    pub(crate) fn is_op_supported(this: &Rc<UnsafeCell<UringInner>>, code: u8) -> bool {
+        let inner = unsafe { &*this.get() };
+        inner.supports(code)
+    }
+
+    fn supports(&self, code: u8) -> bool {
+        self.unsupported_opcodes[code as usize / 64] & (1 << (code % 64)) == 0
+            && self
+                .probe
+                .as_ref()
+                .map_or(true, |probe| probe.is_supported(code))
+    }
+
+    /// Leak the buffers of the in-flight operations once the driver is
+    /// dropped, instead of waiting for them to complete.
+    pub(crate) fn leak_in_flight(this: &Rc<UnsafeCell<UringInner>>) {
+        let inner = unsafe { &mut *this.get() };
+        inner.ops.leak = true;
+    }
+
+    fn cancel(&mut self, index: usize) {
+        let cancel = opcode::AsyncCancel::new(index as u64)
+            .build()
+            .user_data(CANCEL_USERDATA);
+        unsafe {
+            // Try push cancel, if failed, will submit and re-push.
+            if self.uring.submission().push(&cancel).is_err() {
+                let _ = self.submit();
+                let _ = self.uring.submission().push(&cancel);
             }
         }
     }
@@ -442,9 +751,20 @@ impl Drop for UringInner {
     }
 }
 
+impl Drop for Ops {
+    fn drop(&mut self) {
+        if self.leak {
+            std::mem::forget(std::mem::replace(&mut self.slab, Slab::new()));
+        }
+    }
+}
+
 impl Ops {
     const fn new() -> Self {
-        Ops { slab: Slab::new() }
+        Ops {
+            slab: Slab::new(),
+            leak: false,
+        }
     }
 
     // Insert a new operation
@@ -458,6 +778,25 @@ impl Ops {
     }
 }
 
+// Stops building the linked chain if it panics, failing the ops collected as
+// they will never be pushed.
+struct LinkedGuard<'a>(&'a Rc<UnsafeCell<UringInner>>);
+
+impl Drop for LinkedGuard<'_> {
+    fn drop(&mut self) {
+        let inner = unsafe { &mut *self.0.get() };
+        for (index, _) in inner.linked.take().unwrap_or_default() {
+            let err = io::Error::from_raw_os_error(libc::ECANCELED);
+            inner.ops.complete(index, Err(err), 0);
+        }
+    }
+}
+
+// The opcode is the first byte of the SQE.
+fn opcode_of(sqe: &squeue::Entry) -> u8 {
+    unsafe { *(sqe as *const squeue::Entry as *const u8) }
+}
+
 #[inline]
 fn resultify(cqe: &cqueue::Entry) -> io::Result<u32> {
     let res = cqe.result();
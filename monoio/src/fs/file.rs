@@ -1,5 +1,8 @@
 use crate::buf::{IoBuf, IoBufMut};
-use crate::driver::{op::Op, shared_fd::SharedFd};
+use crate::driver::{
+    op::{reserve, submit_linked, Op},
+    shared_fd::{AsSharedFd, SharedFd},
+};
 use crate::fs::OpenOptions;
 
 use std::io;
@@ -52,6 +55,10 @@ use std::path::Path;
 pub struct File {
     /// Open file descriptor
     fd: SharedFd,
+
+    /// Required alignment if the file is opened with `O_DIRECT`
+    #[cfg(all(debug_assertions, target_os = "linux"))]
+    direct_io_align: Option<usize>,
 }
 
 impl File {
@@ -113,7 +120,73 @@ impl File {
     }
 
     pub(crate) fn from_shared_fd(fd: SharedFd) -> File {
-        File { fd }
+        #[cfg(all(debug_assertions, target_os = "linux"))]
+        let direct_io_align = match crate::syscall!(fcntl(fd.raw_fd(), libc::F_GETFL)) {
+            Ok(flags) if flags & libc::O_DIRECT != 0 => logical_block_size(fd.raw_fd()).ok(),
+            _ => None,
+        };
+
+        File {
+            fd,
+            #[cfg(all(debug_assertions, target_os = "linux"))]
+            direct_io_align,
+        }
+    }
+
+    /// Returns the logical block size of the device backing the file.
+    ///
+    /// Files opened with `O_DIRECT` must be read and written with buffers,
+    /// lengths and offsets aligned to this size. See
+    /// [`AlignedBuf`](crate::buf::AlignedBuf).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use monoio::buf::AlignedBuf;
+    /// use monoio::fs::OpenOptions;
+    ///
+    /// #[monoio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let f = OpenOptions::new()
+    ///         .read(true)
+    ///         .custom_flags(libc::O_DIRECT)
+    ///         .open("foo.txt")
+    ///         .await?;
+    ///
+    ///     let align = f.logical_block_size()?;
+    ///     let buf = AlignedBuf::new(align * 8, align);
+    ///     let (res, buf) = f.read_at(buf, 0).await;
+    ///     let n = res?;
+    ///
+    ///     println!("The bytes: {:?}", &buf[..n]);
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(target_os = "linux")]
+    pub fn logical_block_size(&self) -> io::Result<usize> {
+        logical_block_size(self.fd.raw_fd())
+    }
+
+    // O_DIRECT requires the memory address, the length and the file offset to
+    // be aligned. The kernel would return an opaque EINVAL, so check it early
+    // in debug builds.
+    #[cfg(all(debug_assertions, target_os = "linux"))]
+    fn check_direct_io(&self, ptr: *const u8, len: usize, pos: u64) -> io::Result<()> {
+        match self.direct_io_align {
+            Some(align)
+                if ptr as usize % align != 0 || len % align != 0 || pos % align as u64 != 0 =>
+            {
+                Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "misaligned O_DIRECT io: buffer address, length and offset must be \
+                         aligned to {} bytes",
+                        align
+                    ),
+                ))
+            }
+            _ => Ok(()),
+        }
     }
 
     /// Read some bytes at the specified offset from the file into the specified
@@ -160,7 +233,12 @@ impl File {
     ///     Ok(())
     /// }
     /// ```
-    pub async fn read_at<T: IoBufMut>(&self, buf: T, pos: u64) -> crate::BufResult<usize, T> {
+    pub async fn read_at<T: IoBufMut>(&self, mut buf: T, pos: u64) -> crate::BufResult<usize, T> {
+        #[cfg(all(debug_assertions, target_os = "linux"))]
+        if let Err(e) = self.check_direct_io(buf.write_ptr(), buf.bytes_total(), pos) {
+            return (Err(e), buf);
+        }
+
         // Submit the read operation
         let op = Op::read_at(&self.fd, buf, pos).unwrap();
         op.read().await
@@ -293,6 +371,11 @@ impl File {
     ///
     /// [`Ok(n)`]: Ok
     pub async fn write_at<T: IoBuf>(&self, buf: T, pos: u64) -> crate::BufResult<usize, T> {
+        #[cfg(all(debug_assertions, target_os = "linux"))]
+        if let Err(e) = self.check_direct_io(buf.read_ptr(), buf.bytes_init(), pos) {
+            return (Err(e), buf);
+        }
+
         let op = Op::write_at(&self.fd, buf, pos).unwrap();
         op.write().await
     }
@@ -440,6 +523,275 @@ impl File {
         Ok(())
     }
 
+    /// Attempts to sync file data in range `[offset, offset + len)` to disk.
+    ///
+    /// This method is similar to [`sync_data`], but only the given range is
+    /// required to reach the disk. A `len` of 0 means until the end of the
+    /// file.
+    ///
+    /// Note that some platforms and drivers may sync the whole file.
+    ///
+    /// [`sync_data`]: File::sync_data
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use monoio::fs::File;
+    ///
+    /// #[monoio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let f = File::create("foo.txt").await?;
+    ///     let (res, buf) = f.write_at(&b"Hello, world!"[..], 4096).await;
+    ///     let n = res?;
+    ///
+    ///     f.sync_data_range(4096, n as u64).await?;
+    ///
+    ///     // Close the file
+    ///     f.close().await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn sync_data_range(&self, offset: u64, len: u64) -> io::Result<()> {
+        let op = Op::datasync_range(&self.fd, offset, len)?;
+        let completion = op.await;
+
+        completion.meta.result?;
+        Ok(())
+    }
+
+    /// Initiates and/or waits for write-out of dirty pages in range
+    /// `[offset, offset + len)`, equivalent to `sync_file_range(2)`. A `len`
+    /// of 0 means until the end of the file.
+    ///
+    /// Unlike [`sync_data_range`], this does not flush metadata nor the disk
+    /// write cache, so it gives **no** durability guarantee. It is useful to
+    /// start write-out early and avoid a large burst of writes at the next
+    /// sync.
+    ///
+    /// On platforms without `sync_file_range(2)` the whole file is synced.
+    ///
+    /// [`sync_data_range`]: File::sync_data_range
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use monoio::fs::{File, SyncRangeFlags};
+    ///
+    /// #[monoio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let f = File::create("foo.txt").await?;
+    ///     let (res, buf) = f.write_at(&b"Hello, world!"[..], 0).await;
+    ///     let n = res?;
+    ///
+    ///     // Start write-out without waiting for it
+    ///     f.sync_range(0, n as u64, SyncRangeFlags::WRITE).await?;
+    ///
+    ///     // Close the file
+    ///     f.close().await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn sync_range(&self, offset: u64, len: u64, flags: SyncRangeFlags) -> io::Result<()> {
+        let op = Op::sync_file_range(&self.fd, offset, len, flags.bits()).unwrap();
+        let completion = op.await;
+
+        completion.meta.result?;
+        Ok(())
+    }
+
+    /// Write a buffer into this file at the specified offset, then sync the
+    /// written range to disk.
+    ///
+    /// The write and the sync are submitted together as a linked chain, the
+    /// sync only starts once the write has completed. This saves a round trip
+    /// compared to calling [`write_at`] and [`sync_data_range`] in turn.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the write if it failed, otherwise the error of the
+    /// sync. A short write cancels the linked sync, in this case the written
+    /// part is synced on its own and its length is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use monoio::fs::File;
+    ///
+    /// #[monoio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let f = File::create("foo.txt").await?;
+    ///     let (res, buf) = f.write_and_sync_data_at(&b"Hello, world!"[..], 0).await;
+    ///     let n = res?;
+    ///
+    ///     println!("wrote {} bytes", n);
+    ///
+    ///     // Close the file
+    ///     f.close().await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// [`write_at`]: File::write_at
+    /// [`sync_data_range`]: File::sync_data_range
+    pub async fn write_and_sync_data_at<T: IoBuf>(
+        &self,
+        buf: T,
+        pos: u64,
+    ) -> crate::BufResult<usize, T> {
+        #[cfg(all(debug_assertions, target_os = "linux"))]
+        if let Err(e) = self.check_direct_io(buf.read_ptr(), buf.bytes_init(), pos) {
+            return (Err(e), buf);
+        }
+
+        // The buffer can not be given back if submitting the write fails, so
+        // make room for the chain first.
+        if let Err(e) = reserve(2) {
+            return (Err(e), buf);
+        }
+        let len = buf.bytes_init();
+        let (write, sync) = submit_linked(false, || {
+            (
+                Op::write_at(&self.fd, buf, pos).expect("room is reserved"),
+                Op::datasync_range(&self.fd, pos, len as u64),
+            )
+        });
+
+        // Poll in order, the legacy driver executes ops when polled.
+        let (res, buf) = write.write().await;
+        let n = match res {
+            Ok(n) => n,
+            Err(e) => return (Err(e), buf),
+        };
+        let res = match sync {
+            Ok(sync) => sync.await.meta.result,
+            Err(e) => Err(e),
+        };
+        match res {
+            Ok(_) => (Ok(n), buf),
+            // The short write broke the link, sync what was written.
+            Err(e) if n < len && e.raw_os_error() == Some(libc::ECANCELED) => {
+                match self.sync_data_range(pos, n as u64).await {
+                    Ok(()) => (Ok(n), buf),
+                    Err(e) => (Err(e), buf),
+                }
+            }
+            Err(e) => (Err(e), buf),
+        }
+    }
+
+    /// Truncates or extends the underlying file, updating the size of this file
+    /// to become `size`.
+    ///
+    /// If the `size` is less than the current file's size, then the file will
+    /// be shrunk. If it is greater than the current file's size, then the file
+    /// will be extended to `size` and have all of the intermediate data filled
+    /// in with 0s.
+    ///
+    /// The file's cursor isn't changed. In particular, if the cursor was at
+    /// the end and the file is shrunk using this operation, the cursor will now
+    /// be past the end.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file is not opened for
+    /// writing.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use monoio::fs::File;
+    ///
+    /// #[monoio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let f = File::create("foo.txt").await?;
+    ///     f.set_len(10).await?;
+    ///
+    ///     // Close the file
+    ///     f.close().await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn set_len(&self, size: u64) -> io::Result<()> {
+        // There is no uring opcode for ftruncate, it is a cheap metadata
+        // operation so we do the syscall directly.
+        let size: libc::off_t = size
+            .try_into()
+            .map_err(|_| io::Error::from_raw_os_error(libc::EINVAL))?;
+        crate::syscall!(ftruncate(self.fd.raw_fd(), size))?;
+        Ok(())
+    }
+
+    /// Manipulates the allocated disk space of the file in range
+    /// `[offset, offset + len)`, equivalent to `fallocate(2)`.
+    ///
+    /// With [`AllocateMode::Allocate`], the range is preallocated and the file
+    /// is extended if the range goes beyond its end. The other modes allow
+    /// preallocating without changing the file size, deallocating ranges
+    /// (punching holes) and zeroing ranges.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file system does not support
+    /// the requested mode. On platforms without `fallocate(2)` an error of the
+    /// kind [`ErrorKind::Unsupported`] is always returned.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use monoio::fs::{AllocateMode, File};
+    ///
+    /// #[monoio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let f = File::create("foo.txt").await?;
+    ///
+    ///     // Preallocate 1MiB without changing the file size
+    ///     f.allocate(0, 1024 * 1024, AllocateMode::KeepSize).await?;
+    ///
+    ///     // Close the file
+    ///     f.close().await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// [`ErrorKind::Unsupported`]: std::io::ErrorKind::Unsupported
+    pub async fn allocate(&self, offset: u64, len: u64, mode: AllocateMode) -> io::Result<()> {
+        let op = Op::fallocate(&self.fd, offset, len, mode.as_raw()).unwrap();
+        let completion = op.await;
+
+        completion.meta.result?;
+        Ok(())
+    }
+
+    /// Announces an intention to access file data in range
+    /// `[offset, offset + len)` in a specific pattern, equivalent to
+    /// `posix_fadvise(2)`. A `len` of 0 means until the end of the file.
+    ///
+    /// The advice is only a hint for the page cache. On platforms without
+    /// `posix_fadvise(2)` it is ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use monoio::fs::{Advice, File};
+    ///
+    /// #[monoio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let f = File::open("foo.txt").await?;
+    ///     f.advise(0, 0, Advice::Sequential).await?;
+    ///
+    ///     // Close the file
+    ///     f.close().await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        let op = Op::fadvise(&self.fd, offset, len, advice.as_raw()).unwrap();
+        let completion = op.await;
+
+        completion.meta.result?;
+        Ok(())
+    }
+
     /// Closes the file.
     ///
     /// The method completes once the close operation has completed,
@@ -470,8 +822,179 @@ impl File {
     }
 }
 
+#[cfg(target_os = "linux")]
+fn logical_block_size(fd: RawFd) -> io::Result<usize> {
+    let mut stat = std::mem::MaybeUninit::<libc::stat>::uninit();
+    crate::syscall!(fstat(fd, stat.as_mut_ptr()))?;
+    let stat = unsafe { stat.assume_init() };
+
+    // A block device can tell us directly.
+    if stat.st_mode & libc::S_IFMT == libc::S_IFBLK {
+        let mut size: libc::c_int = 0;
+        crate::syscall!(ioctl(fd, libc::BLKSSZGET, &mut size))?;
+        return Ok(size as usize);
+    }
+
+    // For regular files, look up the backing device in sysfs. Partitions do not
+    // have a queue directory, it lives in the parent device.
+    let dev = stat.st_dev;
+    let major = ((dev >> 32) & 0xffff_f000) | ((dev >> 8) & 0x0000_0fff);
+    let minor = ((dev >> 12) & 0xffff_ff00) | (dev & 0x0000_00ff);
+    for path in [
+        format!(
+            "/sys/dev/block/{}:{}/queue/logical_block_size",
+            major, minor
+        ),
+        format!(
+            "/sys/dev/block/{}:{}/../queue/logical_block_size",
+            major, minor
+        ),
+    ] {
+        if let Some(size) = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|s| s.trim().parse::<usize>().ok())
+        {
+            return Ok(size);
+        }
+    }
+
+    // Not backed by a block device(tmpfs, overlayfs...), fallback to the
+    // preferred io size.
+    Ok(stat.st_blksize as usize)
+}
+
 impl AsRawFd for File {
     fn as_raw_fd(&self) -> RawFd {
         self.fd.raw_fd()
     }
 }
+
+impl AsSharedFd for File {
+    fn as_shared_fd(&self) -> &SharedFd {
+        &self.fd
+    }
+}
+
+/// Mode of [`File::allocate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AllocateMode {
+    /// Allocate disk space for the range, extending the file size if the range
+    /// goes beyond the end of the file.
+    Allocate,
+    /// Allocate disk space for the range, but do not change the file size
+    /// (`FALLOC_FL_KEEP_SIZE`).
+    KeepSize,
+    /// Deallocate the range, leaving a hole that reads back as zeros. The
+    /// file size is not changed (`FALLOC_FL_PUNCH_HOLE | FALLOC_FL_KEEP_SIZE`).
+    PunchHole,
+    /// Zero the range, extending the file size if the range goes beyond the
+    /// end of the file (`FALLOC_FL_ZERO_RANGE`).
+    ZeroRange,
+    /// Zero the range without changing the file size
+    /// (`FALLOC_FL_ZERO_RANGE | FALLOC_FL_KEEP_SIZE`).
+    ZeroRangeKeepSize,
+}
+
+impl AllocateMode {
+    #[cfg(target_os = "linux")]
+    fn as_raw(self) -> i32 {
+        match self {
+            AllocateMode::Allocate => 0,
+            AllocateMode::KeepSize => libc::FALLOC_FL_KEEP_SIZE,
+            AllocateMode::PunchHole => libc::FALLOC_FL_PUNCH_HOLE | libc::FALLOC_FL_KEEP_SIZE,
+            AllocateMode::ZeroRange => libc::FALLOC_FL_ZERO_RANGE,
+            AllocateMode::ZeroRangeKeepSize => {
+                libc::FALLOC_FL_ZERO_RANGE | libc::FALLOC_FL_KEEP_SIZE
+            }
+        }
+    }
+
+    // fallocate is not available, the op will report unsupported anyway.
+    #[cfg(not(target_os = "linux"))]
+    fn as_raw(self) -> i32 {
+        0
+    }
+}
+
+/// Access pattern advice for [`File::advise`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Advice {
+    /// No special treatment (`POSIX_FADV_NORMAL`).
+    Normal,
+    /// Data will be accessed sequentially (`POSIX_FADV_SEQUENTIAL`).
+    Sequential,
+    /// Data will be accessed in random order (`POSIX_FADV_RANDOM`).
+    Random,
+    /// Data will be accessed only once (`POSIX_FADV_NOREUSE`).
+    NoReuse,
+    /// Data will be accessed in the near future (`POSIX_FADV_WILLNEED`).
+    WillNeed,
+    /// Data will not be accessed in the near future (`POSIX_FADV_DONTNEED`).
+    DontNeed,
+}
+
+impl Advice {
+    #[cfg(target_os = "linux")]
+    fn as_raw(self) -> i32 {
+        match self {
+            Advice::Normal => libc::POSIX_FADV_NORMAL,
+            Advice::Sequential => libc::POSIX_FADV_SEQUENTIAL,
+            Advice::Random => libc::POSIX_FADV_RANDOM,
+            Advice::NoReuse => libc::POSIX_FADV_NOREUSE,
+            Advice::WillNeed => libc::POSIX_FADV_WILLNEED,
+            Advice::DontNeed => libc::POSIX_FADV_DONTNEED,
+        }
+    }
+
+    // posix_fadvise is not available, the op ignores the advice anyway.
+    #[cfg(not(target_os = "linux"))]
+    fn as_raw(self) -> i32 {
+        0
+    }
+}
+
+/// Flags for [`File::sync_range`], see `sync_file_range(2)`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SyncRangeFlags(u32);
+
+impl SyncRangeFlags {
+    /// Wait upon write-out of all pages in the range that have already been
+    /// submitted to the device driver for write-out before performing any
+    /// write (`SYNC_FILE_RANGE_WAIT_BEFORE`).
+    pub const WAIT_BEFORE: Self = Self(1);
+    /// Initiate write-out of all dirty pages in the range which are not
+    /// presently submitted write-out (`SYNC_FILE_RANGE_WRITE`).
+    pub const WRITE: Self = Self(2);
+    /// Wait upon write-out of all pages in the range after performing any
+    /// write (`SYNC_FILE_RANGE_WAIT_AFTER`).
+    pub const WAIT_AFTER: Self = Self(4);
+
+    /// No flag set. The operation is a no-op.
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Returns the raw value of the flags.
+    pub const fn bits(&self) -> u32 {
+        self.0
+    }
+
+    /// Returns true if all flags in `other` are set.
+    pub const fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl std::ops::BitOr for SyncRangeFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl std::ops::BitOrAssign for SyncRangeFlags {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
@@ -1,7 +1,7 @@
 //! Filesystem manipulation operations.
 
 mod file;
-pub use file::File;
+pub use file::{Advice, AllocateMode, File, SyncRangeFlags};
 
 mod open_options;
 pub use open_options::OpenOptions;
@@ -60,6 +60,7 @@ pub struct OpenOptions {
     create: bool,
     create_new: bool,
     pub(crate) mode: libc::mode_t,
+    pub(crate) custom_flags: libc::c_int,
 }
 
 impl OpenOptions {
@@ -92,6 +93,7 @@ impl OpenOptions {
             create: false,
             create_new: false,
             mode: 0o666,
+            custom_flags: 0,
         }
     }
 
@@ -271,6 +273,67 @@ impl OpenOptions {
         self
     }
 
+    /// Sets the mode bits that a new file will be created with.
+    ///
+    /// If a new file is created as part of an `OpenOptions::open` call then this
+    /// specified `mode` will be used as the permission bits for the new file.
+    /// If no `mode` is set, the default of `0o666` will be used.
+    /// The operating system masks out bits with the system's `umask`, to produce
+    /// the final permissions.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use monoio::fs::OpenOptions;
+    ///
+    /// #[monoio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let file = OpenOptions::new()
+    ///         .write(true)
+    ///         .create(true)
+    ///         .mode(0o600)
+    ///         .open("foo.txt")
+    ///         .await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn mode(&mut self, mode: u32) -> &mut OpenOptions {
+        self.mode = mode as libc::mode_t;
+        self
+    }
+
+    /// Pass custom flags to the `flags` argument of `open`.
+    ///
+    /// The bits that define the access mode are masked out with `O_ACCMODE`, to
+    /// ensure they do not interfere with the access mode set by Rust's options.
+    ///
+    /// Custom flags can only set flags, not remove flags set by Rust's options.
+    /// This function overwrites any previously set custom flags.
+    ///
+    /// A common use is `O_DIRECT`, in which case reads and writes must use
+    /// buffers aligned to the device's logical block size, see
+    /// [`AlignedBuf`](crate::buf::AlignedBuf).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use monoio::fs::OpenOptions;
+    ///
+    /// #[monoio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let file = OpenOptions::new()
+    ///         .read(true)
+    ///         .custom_flags(libc::O_NOFOLLOW)
+    ///         .open("foo.txt")
+    ///         .await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn custom_flags(&mut self, flags: i32) -> &mut OpenOptions {
+        self.custom_flags = flags;
+        self
+    }
+
     /// Opens a file at `path` with the options specified by `self`.
     ///
     /// # Errors
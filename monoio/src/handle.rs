@@ -0,0 +1,184 @@
+//! Handle to spawn tasks onto a runtime from other threads.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+use crate::{
+    driver::{
+        thread::{get_unpark_handle, get_waker_sender},
+        unpark::Unpark,
+        UnparkHandle,
+    },
+    task::waker_fn::dummy_waker,
+};
+
+/// A job sent to a runtime, which spawns a task once called on its thread.
+pub(crate) type RemoteTask = Box<dyn FnOnce() + Send>;
+
+/// A `Send` and `Clone` handle to a runtime, used to spawn tasks onto it from
+/// any thread.
+///
+/// # Examples
+///
+/// ```no_run
+/// use monoio::{RuntimeBuilder, RuntimeHandle};
+///
+/// let (tx, rx) = std::sync::mpsc::channel();
+/// std::thread::spawn(move || {
+///     let mut rt = RuntimeBuilder::<monoio::FusionDriver>::new()
+///         .build()
+///         .unwrap();
+///     tx.send(rt.handle()).unwrap();
+///     rt.block_on(std::future::pending::<()>());
+/// });
+///
+/// let handle: RuntimeHandle = rx.recv().unwrap();
+/// // The Rc is created on the runtime thread.
+/// let len = handle.block_on(|| async {
+///     let rc = std::rc::Rc::new(vec![1, 2, 3]);
+///     rc.len()
+/// });
+/// assert_eq!(len, Some(3));
+/// ```
+#[derive(Clone)]
+pub struct RuntimeHandle {
+    thread_id: usize,
+    tasks: flume::Sender<RemoteTask>,
+    waker_sender: flume::Sender<Waker>,
+    unpark: UnparkHandle,
+}
+
+impl RuntimeHandle {
+    pub(crate) fn new(thread_id: usize, tasks: flume::Sender<RemoteTask>) -> Self {
+        Self {
+            thread_id,
+            tasks,
+            waker_sender: get_waker_sender(thread_id)
+                .expect("waker sender has not been registered"),
+            unpark: get_unpark_handle(thread_id).expect("unpark handle has not been registered"),
+        }
+    }
+
+    /// Returns a handle to the current runtime.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside a runtime.
+    pub fn current() -> Self {
+        crate::runtime::CURRENT.with(|ctx| ctx.handle())
+    }
+
+    /// Spawn a task onto the runtime.
+    ///
+    /// `f` is sent to the runtime thread and called there, so the future it
+    /// returns does not need to be `Send`. If the runtime is gone, `f` is
+    /// dropped and the returned handle resolves to `None`.
+    pub fn spawn<F, Fut>(&self, f: F) -> RemoteJoinHandle<Fut::Output>
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future + 'static,
+        Fut::Output: Send + 'static,
+    {
+        RemoteJoinHandle {
+            rx: self.send(f).into_recv_async(),
+        }
+    }
+
+    /// Spawn a task onto the runtime, and block the current thread until it
+    /// completes.
+    ///
+    /// Returns `None` if the runtime is gone before the task completes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called on the thread of the target runtime, which would
+    /// deadlock.
+    pub fn block_on<F, Fut>(&self, f: F) -> Option<Fut::Output>
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future + 'static,
+        Fut::Output: Send + 'static,
+    {
+        let on_target = crate::runtime::CURRENT.is_set()
+            && crate::runtime::CURRENT.with(|ctx| ctx.thread_id == self.thread_id);
+        assert!(!on_target, "Can not block on the current runtime");
+        self.send(f).recv().ok()
+    }
+
+    fn send<F, Fut>(&self, f: F) -> flume::Receiver<Fut::Output>
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future + 'static,
+        Fut::Output: Send + 'static,
+    {
+        let (tx, rx) = flume::bounded(1);
+        self.send_remote(Box::new(move || {
+            crate::spawn(async move {
+                let _ = tx.send(f().await);
+            });
+        }));
+        rx
+    }
+
+    /// Returns a [`dump`](crate::task::dump) of the live tasks of the
+    /// runtime.
+    ///
+    /// The dump is taken on the runtime thread without spawning a task, once
+    /// the running task yields. It resolves to `None` if the runtime is gone.
+    #[cfg(feature = "task-dump")]
+    pub fn dump(&self) -> RemoteJoinHandle<Vec<crate::task::TaskDump>> {
+        let (tx, rx) = flume::bounded(1);
+        self.send_remote(Box::new(move || {
+            let _ = tx.send(crate::task::dump());
+        }));
+        RemoteJoinHandle {
+            rx: rx.into_recv_async(),
+        }
+    }
+
+    fn send_remote(&self, task: RemoteTask) {
+        if self.tasks.send(task).is_ok() {
+            // The waker makes sure the runtime does not park again before
+            // spawning the task, even if it is awake now.
+            let _ = self.waker_sender.send(dummy_waker());
+            let _ = self.unpark.unpark();
+        }
+    }
+}
+
+impl std::fmt::Debug for RuntimeHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RuntimeHandle")
+            .field("thread_id", &self.thread_id)
+            .finish()
+    }
+}
+
+/// Handle to a task spawned by [`RuntimeHandle::spawn`], which can be awaited
+/// on any thread and runtime.
+///
+/// Resolves to `None` if the task is dropped before completing, e.g. when its
+/// runtime is gone.
+pub struct RemoteJoinHandle<T: 'static> {
+    rx: flume::r#async::RecvFut<'static, T>,
+}
+
+impl<T: 'static> Future for RemoteJoinHandle<T> {
+    type Output = Option<T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let coop = ready!(crate::task::coop::poll_proceed(cx));
+        let output = ready!(Pin::new(&mut self.rx).poll(cx));
+        coop.made_progress();
+        Poll::Ready(output.ok())
+    }
+}
+
+impl<T: 'static> std::fmt::Debug for RemoteJoinHandle<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RemoteJoinHandle").finish()
+    }
+}
@@ -7,14 +7,23 @@ mod async_write_rent_ext;
 
 mod async_buf_read;
 
+mod pipe;
 pub mod sink;
 pub mod stream;
+#[cfg(feature = "test-util")]
+pub mod test_util;
 
 pub use async_buf_read::AsyncBufRead;
 pub use async_read_rent::{AsyncReadRent, AsyncReadRentAt};
 pub use async_read_rent_ext::AsyncReadRentExt;
 pub use async_write_rent::{AsyncWriteRent, AsyncWriteRentAt};
 pub use async_write_rent_ext::AsyncWriteRentExt;
+pub use pipe::{pipe, Receiver, Sender};
 
 mod util;
-pub use util::{copy, BufReader, BufWriter, PrefixedReadIo};
+pub(crate) use util::send_file;
+pub use util::{
+    chain, copy, copy_bidirectional, copy_bidirectional_with_idle_timeout, zero_copy, BufReader,
+    BufWriter, Chain, ChainFd, IntoSplit, PrefixedReadIo, SpliceDestination, SpliceFd,
+    SpliceSource,
+};
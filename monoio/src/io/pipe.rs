@@ -0,0 +1,342 @@
+//! Anonymous pipes and named FIFOs.
+
+use crate::{
+    buf::{IoBuf, IoBufMut, IoVecBuf, IoVecBufMut},
+    driver::{
+        op::{is_legacy, Op},
+        shared_fd::{AsSharedFd, SharedFd},
+    },
+    io::{AsyncReadRent, AsyncWriteRent, SpliceDestination, SpliceFd, SpliceSource},
+    syscall,
+};
+use std::{
+    ffi::CString,
+    future::Future,
+    io,
+    os::unix::{
+        ffi::OsStrExt,
+        prelude::{AsRawFd, IntoRawFd, RawFd},
+    },
+    path::Path,
+};
+
+/// Create an anonymous pipe.
+///
+/// Data written to the [`Sender`] can be read from the [`Receiver`]. The
+/// receiver sees EOF once the sender is dropped.
+///
+/// # Examples
+///
+/// ```no_run
+/// use monoio::io::{AsyncReadRent, AsyncWriteRentExt};
+///
+/// #[monoio::main]
+/// async fn main() -> std::io::Result<()> {
+///     let (mut tx, mut rx) = monoio::io::pipe()?;
+///     tx.write_all(b"hello").await.0?;
+///     drop(tx);
+///     let (res, buf) = rx.read(Vec::with_capacity(16)).await;
+///     assert_eq!(res?, 5);
+///     assert_eq!(&buf, b"hello");
+///     Ok(())
+/// }
+/// ```
+pub fn pipe() -> io::Result<(Sender, Receiver)> {
+    let mut fds = [0; 2];
+
+    #[cfg(target_os = "linux")]
+    {
+        let flags = if is_legacy() {
+            libc::O_CLOEXEC | libc::O_NONBLOCK
+        } else {
+            libc::O_CLOEXEC
+        };
+        syscall!(pipe2(fds.as_mut_ptr(), flags))?;
+    }
+
+    // Darwin doesn't have pipe2.
+    #[cfg(not(target_os = "linux"))]
+    {
+        syscall!(pipe(fds.as_mut_ptr()))?;
+        for fd in fds {
+            if let Err(e) = set_flags(fd) {
+                let _ = syscall!(close(fds[0]));
+                let _ = syscall!(close(fds[1]));
+                return Err(e);
+            }
+        }
+    }
+
+    // Safety: the fds have just been created.
+    unsafe {
+        let receiver = Receiver::from_raw_fd_checked(fds[0]);
+        let sender = Sender::from_raw_fd_checked(fds[1]);
+        Ok((sender?, receiver?))
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn set_flags(fd: RawFd) -> io::Result<()> {
+    syscall!(fcntl(fd, libc::F_SETFL, libc::O_NONBLOCK))?;
+    syscall!(fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC))?;
+    Ok(())
+}
+
+// The legacy driver requires non-blocking fds, while the uring one works
+// with blocking fds.
+fn set_blocking_mode(fd: RawFd) -> io::Result<()> {
+    let flags = syscall!(fcntl(fd, libc::F_GETFL))?;
+    let flags = if is_legacy() {
+        flags | libc::O_NONBLOCK
+    } else {
+        flags & !libc::O_NONBLOCK
+    };
+    syscall!(fcntl(fd, libc::F_SETFL, flags))?;
+    Ok(())
+}
+
+// Open a FIFO without blocking until the other side is opened too.
+fn open_fifo(path: &Path, access: libc::c_int) -> io::Result<RawFd> {
+    let path = CString::new(path.as_os_str().as_bytes())?;
+    let fd = syscall!(open(
+        path.as_ptr(),
+        access | libc::O_CLOEXEC | libc::O_NONBLOCK
+    ))?;
+
+    let result = (|| {
+        let mut stat: libc::stat = unsafe { std::mem::zeroed() };
+        syscall!(fstat(fd, &mut stat))?;
+        if stat.st_mode & libc::S_IFMT != libc::S_IFIFO {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "not a FIFO"));
+        }
+        set_blocking_mode(fd)?;
+        Ok(fd)
+    })();
+    if result.is_err() {
+        let _ = syscall!(close(fd));
+    }
+    result
+}
+
+/// The writing end of a pipe or a FIFO.
+pub struct Sender {
+    fd: SharedFd,
+}
+
+/// The reading end of a pipe or a FIFO.
+pub struct Receiver {
+    fd: SharedFd,
+}
+
+impl Sender {
+    /// Open the FIFO at `path` for writing.
+    ///
+    /// Fails with `ENXIO` if no process has the FIFO opened for reading.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let fd = open_fifo(path.as_ref(), libc::O_WRONLY)?;
+        // Safety: the fd has just been opened.
+        unsafe { Self::from_raw_fd_checked(fd) }
+    }
+
+    unsafe fn from_raw_fd_checked(fd: RawFd) -> io::Result<Self> {
+        match SharedFd::new(fd) {
+            Ok(fd) => Ok(Self { fd }),
+            Err(e) => {
+                let _ = syscall!(close(fd));
+                Err(e)
+            }
+        }
+    }
+
+    // Take the ownership of a pipe created elsewhere, e.g. by std::process.
+    pub(crate) unsafe fn from_pipe_fd(fd: RawFd) -> io::Result<Self> {
+        if let Err(e) = set_blocking_mode(fd) {
+            let _ = syscall!(close(fd));
+            return Err(e);
+        }
+        Self::from_raw_fd_checked(fd)
+    }
+
+    /// Returns the capacity of the pipe.
+    #[cfg(target_os = "linux")]
+    pub fn pipe_size(&self) -> io::Result<usize> {
+        pipe_size(self.fd.raw_fd())
+    }
+
+    /// Set the capacity of the pipe(`F_SETPIPE_SZ`), and return the actual
+    /// capacity, which may be larger.
+    #[cfg(target_os = "linux")]
+    pub fn set_pipe_size(&self, size: usize) -> io::Result<usize> {
+        set_pipe_size(self.fd.raw_fd(), size)
+    }
+}
+
+impl Receiver {
+    /// Open the FIFO at `path` for reading.
+    ///
+    /// It does not wait for a writer: reads return EOF until one has opened
+    /// the FIFO.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let fd = open_fifo(path.as_ref(), libc::O_RDONLY)?;
+        // Safety: the fd has just been opened.
+        unsafe { Self::from_raw_fd_checked(fd) }
+    }
+
+    unsafe fn from_raw_fd_checked(fd: RawFd) -> io::Result<Self> {
+        match SharedFd::new(fd) {
+            Ok(fd) => Ok(Self { fd }),
+            Err(e) => {
+                let _ = syscall!(close(fd));
+                Err(e)
+            }
+        }
+    }
+
+    // Take the ownership of a pipe created elsewhere, e.g. by std::process.
+    pub(crate) unsafe fn from_pipe_fd(fd: RawFd) -> io::Result<Self> {
+        if let Err(e) = set_blocking_mode(fd) {
+            let _ = syscall!(close(fd));
+            return Err(e);
+        }
+        Self::from_raw_fd_checked(fd)
+    }
+
+    /// Returns the capacity of the pipe.
+    #[cfg(target_os = "linux")]
+    pub fn pipe_size(&self) -> io::Result<usize> {
+        pipe_size(self.fd.raw_fd())
+    }
+
+    /// Set the capacity of the pipe(`F_SETPIPE_SZ`), and return the actual
+    /// capacity, which may be larger.
+    #[cfg(target_os = "linux")]
+    pub fn set_pipe_size(&self, size: usize) -> io::Result<usize> {
+        set_pipe_size(self.fd.raw_fd(), size)
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn pipe_size(fd: RawFd) -> io::Result<usize> {
+    syscall!(fcntl(fd, libc::F_GETPIPE_SZ)).map(|size| size as usize)
+}
+
+#[cfg(target_os = "linux")]
+fn set_pipe_size(fd: RawFd, size: usize) -> io::Result<usize> {
+    let size: libc::c_int = size
+        .try_into()
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "pipe size too large"))?;
+    syscall!(fcntl(fd, libc::F_SETPIPE_SZ, size)).map(|size| size as usize)
+}
+
+impl AsyncWriteRent for Sender {
+    type WriteFuture<'a, B> = impl Future<Output = crate::BufResult<usize, B>> where
+        B: 'a;
+    type WritevFuture<'a, B> = impl Future<Output = crate::BufResult<usize, B>> where
+        B: 'a;
+    type FlushFuture<'a> = impl Future<Output = io::Result<()>>;
+    type ShutdownFuture<'a> = impl Future<Output = io::Result<()>>;
+
+    fn write<T: IoBuf>(&mut self, buf: T) -> Self::WriteFuture<'_, T> {
+        // Pipes ignore the offset.
+        let op = Op::write_at(&self.fd, buf, 0).unwrap();
+        op.write()
+    }
+
+    fn writev<T: IoVecBuf>(&mut self, buf_vec: T) -> Self::WritevFuture<'_, T> {
+        let op = Op::writev(&self.fd, buf_vec).unwrap();
+        op.write()
+    }
+
+    fn flush(&mut self) -> Self::FlushFuture<'_> {
+        // Pipe does not need flush.
+        async move { Ok(()) }
+    }
+
+    fn shutdown(&mut self) -> Self::ShutdownFuture<'_> {
+        // A pipe can not be shut down, the receiver sees EOF once the sender
+        // is dropped.
+        async move { Ok(()) }
+    }
+}
+
+impl AsyncReadRent for Receiver {
+    type ReadFuture<'a, B> = impl std::future::Future<Output = crate::BufResult<usize, B>> where
+        B: 'a;
+    type ReadvFuture<'a, B> = impl std::future::Future<Output = crate::BufResult<usize, B>> where
+        B: 'a;
+
+    fn read<T: IoBufMut>(&mut self, buf: T) -> Self::ReadFuture<'_, T> {
+        // Pipes ignore the offset.
+        let op = Op::read_at(&self.fd, buf, 0).unwrap();
+        op.read()
+    }
+
+    fn readv<T: IoVecBufMut>(&mut self, buf: T) -> Self::ReadvFuture<'_, T> {
+        let op = Op::readv(&self.fd, buf).unwrap();
+        op.read()
+    }
+}
+
+impl SpliceSource for Receiver {
+    fn splice_fd(&self) -> SpliceFd {
+        SpliceFd::new(self)
+    }
+}
+
+impl SpliceDestination for Sender {
+    fn splice_fd(&self) -> SpliceFd {
+        SpliceFd::new(self)
+    }
+}
+
+impl AsSharedFd for Sender {
+    fn as_shared_fd(&self) -> &SharedFd {
+        &self.fd
+    }
+}
+
+impl AsSharedFd for Receiver {
+    fn as_shared_fd(&self) -> &SharedFd {
+        &self.fd
+    }
+}
+
+impl AsRawFd for Sender {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.raw_fd()
+    }
+}
+
+impl AsRawFd for Receiver {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.raw_fd()
+    }
+}
+
+impl IntoRawFd for Sender {
+    fn into_raw_fd(self) -> RawFd {
+        self.fd
+            .try_unwrap()
+            .expect("unexpected multiple reference to rawfd")
+    }
+}
+
+impl IntoRawFd for Receiver {
+    fn into_raw_fd(self) -> RawFd {
+        self.fd
+            .try_unwrap()
+            .expect("unexpected multiple reference to rawfd")
+    }
+}
+
+impl std::fmt::Debug for Sender {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Sender").field("fd", &self.fd).finish()
+    }
+}
+
+impl std::fmt::Debug for Receiver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Receiver").field("fd", &self.fd).finish()
+    }
+}
@@ -0,0 +1,447 @@
+//! Utilities to test the IO of a protocol against misbehaving streams.
+//!
+//! [`duplex`] creates a pair of connected in-memory streams, and
+//! [`FaultyIo`] wraps a stream to make its reads and writes short, fail with
+//! [`ErrorKind::Interrupted`] or [`ErrorKind::WouldBlock`], and complete
+//! after a delay. The faults are chosen by a rng seeded with
+//! [`FaultConfig::seed`], so a failing test can be replayed.
+//!
+//! [`ErrorKind::Interrupted`]: std::io::ErrorKind::Interrupted
+//! [`ErrorKind::WouldBlock`]: std::io::ErrorKind::WouldBlock
+//!
+//! # Examples
+//!
+//! ```
+//! use monoio::io::{
+//!     test_util::{duplex, FaultConfig, FaultyIo},
+//!     AsyncReadRentExt, AsyncWriteRentExt,
+//! };
+//!
+//! #[monoio::main(timer_enabled = true)]
+//! async fn main() {
+//!     let (client, mut server) = duplex(64);
+//!     let config = FaultConfig::new()
+//!         .seed(42)
+//!         .short_io_rate(0.5)
+//!         .interrupted_rate(0.1);
+//!     let mut client = FaultyIo::new(client, config);
+//!     // `write_all` and `read_exact` retry on short and interrupted IO.
+//!     client.write_all(b"hello").await.0.unwrap();
+//!     let (res, buf) = server.read_exact(vec![0; 5]).await;
+//!     res.unwrap();
+//!     assert_eq!(buf, b"hello");
+//! }
+//! ```
+
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    future::Future,
+    io,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+
+use crate::{
+    buf::{IoBuf, IoBufMut, IoVecBuf, IoVecBufMut, RawBuf},
+    io::{AsyncReadRent, AsyncWriteRent},
+    utils::rand::FastRand,
+    BufResult,
+};
+
+/// Create a pair of connected in-memory streams.
+///
+/// The data written to one stream is read from the other. Each direction
+/// buffers up to `max_buf_size` bytes, then writes wait for the peer to read.
+/// Once a stream is shut down or dropped, its peer reads EOF.
+///
+/// # Panics
+///
+/// Panics if `max_buf_size` is 0.
+pub fn duplex(max_buf_size: usize) -> (DuplexStream, DuplexStream) {
+    assert!(max_buf_size > 0, "max_buf_size must be greater than 0");
+    let one = Rc::new(RefCell::new(Pipe::new(max_buf_size)));
+    let two = Rc::new(RefCell::new(Pipe::new(max_buf_size)));
+    (
+        DuplexStream {
+            read: one.clone(),
+            write: two.clone(),
+        },
+        DuplexStream {
+            read: two,
+            write: one,
+        },
+    )
+}
+
+/// One end of an in-memory stream, created by [`duplex`].
+pub struct DuplexStream {
+    read: Rc<RefCell<Pipe>>,
+    write: Rc<RefCell<Pipe>>,
+}
+
+struct Pipe {
+    data: VecDeque<u8>,
+    max_buf_size: usize,
+    // The writer has shut down or is dropped.
+    closed: bool,
+    // The reader is dropped.
+    reader_gone: bool,
+    reader: Option<Waker>,
+    writer: Option<Waker>,
+}
+
+impl Pipe {
+    fn new(max_buf_size: usize) -> Self {
+        Self {
+            data: VecDeque::new(),
+            max_buf_size,
+            closed: false,
+            reader_gone: false,
+            reader: None,
+            writer: None,
+        }
+    }
+
+    fn poll_read(&mut self, cx: &mut Context<'_>, ptr: *mut u8, len: usize) -> Poll<usize> {
+        if len == 0 {
+            return Poll::Ready(0);
+        }
+        if self.data.is_empty() {
+            if self.closed {
+                return Poll::Ready(0);
+            }
+            self.reader = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+        let n = len.min(self.data.len());
+        let dst = unsafe { std::slice::from_raw_parts_mut(ptr, n) };
+        for (dst, src) in dst.iter_mut().zip(self.data.drain(..n)) {
+            *dst = src;
+        }
+        if let Some(waker) = self.writer.take() {
+            waker.wake();
+        }
+        Poll::Ready(n)
+    }
+
+    fn poll_write(&mut self, cx: &mut Context<'_>, src: &[u8]) -> Poll<io::Result<usize>> {
+        if self.closed || self.reader_gone {
+            return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()));
+        }
+        if src.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+        let n = src.len().min(self.max_buf_size - self.data.len());
+        if n == 0 {
+            self.writer = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+        self.data.extend(&src[..n]);
+        if let Some(waker) = self.reader.take() {
+            waker.wake();
+        }
+        Poll::Ready(Ok(n))
+    }
+
+    fn close(&mut self) {
+        self.closed = true;
+        if let Some(waker) = self.reader.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl Drop for DuplexStream {
+    fn drop(&mut self) {
+        self.write.borrow_mut().close();
+        let mut read = self.read.borrow_mut();
+        read.reader_gone = true;
+        if let Some(waker) = read.writer.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl AsyncReadRent for DuplexStream {
+    type ReadFuture<'a, B> = impl Future<Output = BufResult<usize, B>> where
+        B: 'a;
+    type ReadvFuture<'a, B> = impl Future<Output = BufResult<usize, B>> where
+        B: 'a;
+
+    fn read<T: IoBufMut>(&mut self, mut buf: T) -> Self::ReadFuture<'_, T> {
+        async move {
+            let n = std::future::poll_fn(|cx| {
+                self.read
+                    .borrow_mut()
+                    .poll_read(cx, buf.write_ptr(), buf.bytes_total())
+            })
+            .await;
+            unsafe { buf.set_init(n) };
+            (Ok(n), buf)
+        }
+    }
+
+    fn readv<T: IoVecBufMut>(&mut self, mut buf: T) -> Self::ReadvFuture<'_, T> {
+        async move {
+            let n = match unsafe { RawBuf::new_from_iovec_mut(&mut buf) } {
+                Some(raw_buf) => self.read(raw_buf).await.0,
+                None => Ok(0),
+            };
+            if let Ok(n) = n {
+                unsafe { buf.set_init(n) };
+            }
+            (n, buf)
+        }
+    }
+}
+
+impl AsyncWriteRent for DuplexStream {
+    type WriteFuture<'a, B> = impl Future<Output = BufResult<usize, B>> where
+        B: 'a;
+    type WritevFuture<'a, B> = impl Future<Output = BufResult<usize, B>> where
+        B: 'a;
+    type FlushFuture<'a> = impl Future<Output = io::Result<()>>;
+    type ShutdownFuture<'a> = impl Future<Output = io::Result<()>>;
+
+    fn write<T: IoBuf>(&mut self, buf: T) -> Self::WriteFuture<'_, T> {
+        async move {
+            let src = unsafe { std::slice::from_raw_parts(buf.read_ptr(), buf.bytes_init()) };
+            let res = std::future::poll_fn(|cx| self.write.borrow_mut().poll_write(cx, src)).await;
+            (res, buf)
+        }
+    }
+
+    fn writev<T: IoVecBuf>(&mut self, buf_vec: T) -> Self::WritevFuture<'_, T> {
+        async move {
+            let res = match unsafe { RawBuf::new_from_iovec(&buf_vec) } {
+                Some(raw_buf) => self.write(raw_buf).await.0,
+                None => Ok(0),
+            };
+            (res, buf_vec)
+        }
+    }
+
+    fn flush(&mut self) -> Self::FlushFuture<'_> {
+        // Data is readable once written.
+        async move { Ok(()) }
+    }
+
+    fn shutdown(&mut self) -> Self::ShutdownFuture<'_> {
+        self.write.borrow_mut().close();
+        async move { Ok(()) }
+    }
+}
+
+/// Faults injected by a [`FaultyIo`].
+///
+/// By default the seed is 0 and no fault is injected.
+#[derive(Debug, Clone, Copy)]
+pub struct FaultConfig {
+    seed: u64,
+    short_io_rate: f64,
+    interrupted_rate: f64,
+    would_block_rate: f64,
+    max_delay: Duration,
+}
+
+impl Default for FaultConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FaultConfig {
+    /// Create a config injecting no fault.
+    pub fn new() -> Self {
+        Self {
+            seed: 0,
+            short_io_rate: 0.0,
+            interrupted_rate: 0.0,
+            would_block_rate: 0.0,
+            max_delay: Duration::ZERO,
+        }
+    }
+
+    /// Set the seed of the rng choosing the faults.
+    #[must_use]
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Set the probability a read or write only transfers a random part of
+    /// its buffer.
+    #[must_use]
+    pub fn short_io_rate(mut self, rate: f64) -> Self {
+        self.short_io_rate = rate;
+        self
+    }
+
+    /// Set the probability an op fails with `ErrorKind::Interrupted`,
+    /// without transferring anything.
+    #[must_use]
+    pub fn interrupted_rate(mut self, rate: f64) -> Self {
+        self.interrupted_rate = rate;
+        self
+    }
+
+    /// Set the probability an op fails with `ErrorKind::WouldBlock`, without
+    /// transferring anything.
+    #[must_use]
+    pub fn would_block_rate(mut self, rate: f64) -> Self {
+        self.would_block_rate = rate;
+        self
+    }
+
+    /// Delay every op by a random duration up to `max_delay`, with
+    /// [`sleep`](crate::time::sleep). The runtime must have its timer
+    /// enabled.
+    #[must_use]
+    pub fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+}
+
+/// Wraps an IO to inject the faults of a [`FaultConfig`] into its ops.
+pub struct FaultyIo<I> {
+    io: I,
+    config: FaultConfig,
+    rng: FastRand,
+}
+
+impl<I> FaultyIo<I> {
+    /// Create a FaultyIo with given io and config.
+    pub fn new(io: I, config: FaultConfig) -> Self {
+        Self {
+            io,
+            config,
+            rng: FastRand::new(config.seed),
+        }
+    }
+
+    /// Get a reference to the inner io.
+    pub fn get_ref(&self) -> &I {
+        &self.io
+    }
+
+    /// Get a mutable reference to the inner io.
+    pub fn get_mut(&mut self) -> &mut I {
+        &mut self.io
+    }
+
+    /// Into inner
+    pub fn into_inner(self) -> I {
+        self.io
+    }
+
+    // Delays the op, then maybe fails it.
+    async fn fault(&self) -> io::Result<()> {
+        let max_delay = self.config.max_delay.as_micros().min(u32::MAX as u128 - 1) as u32;
+        if max_delay != 0 {
+            let delay = self.rng.fastrand_n(max_delay + 1) as u64;
+            crate::time::sleep(Duration::from_micros(delay)).await;
+        }
+        if self.rng.chance(self.config.interrupted_rate) {
+            return Err(io::ErrorKind::Interrupted.into());
+        }
+        if self.rng.chance(self.config.would_block_rate) {
+            return Err(io::ErrorKind::WouldBlock.into());
+        }
+        Ok(())
+    }
+
+    // Returns how many of `len` bytes the op transfers, at least 1.
+    fn chunk(&self, len: usize) -> usize {
+        if len > 1 && self.rng.chance(self.config.short_io_rate) {
+            let max = (len - 1).min(u32::MAX as usize) as u32;
+            1 + self.rng.fastrand_n(max) as usize
+        } else {
+            len
+        }
+    }
+}
+
+impl<I: AsyncReadRent> AsyncReadRent for FaultyIo<I> {
+    type ReadFuture<'a, T> = impl Future<Output = BufResult<usize, T>> where
+        T: 'a, Self: 'a;
+    type ReadvFuture<'a, T> = impl Future<Output = BufResult<usize, T>> where
+        T: 'a, Self: 'a;
+
+    fn read<T: IoBufMut>(&mut self, mut buf: T) -> Self::ReadFuture<'_, T> {
+        async move {
+            if let Err(e) = self.fault().await {
+                return (Err(e), buf);
+            }
+            let len = self.chunk(buf.bytes_total());
+            let raw_buf = unsafe { RawBuf::new(buf.write_ptr(), len) };
+            let res = self.io.read(raw_buf).await.0;
+            if let Ok(n) = res {
+                unsafe { buf.set_init(n) };
+            }
+            (res, buf)
+        }
+    }
+
+    fn readv<T: IoVecBufMut>(&mut self, mut buf: T) -> Self::ReadvFuture<'_, T> {
+        async move {
+            let n = match unsafe { RawBuf::new_from_iovec_mut(&mut buf) } {
+                Some(raw_buf) => self.read(raw_buf).await.0,
+                None => Ok(0),
+            };
+            if let Ok(n) = n {
+                unsafe { buf.set_init(n) };
+            }
+            (n, buf)
+        }
+    }
+}
+
+impl<I: AsyncWriteRent> AsyncWriteRent for FaultyIo<I> {
+    type WriteFuture<'a, T> = impl Future<Output = BufResult<usize, T>> where
+        T: 'a, Self: 'a;
+    type WritevFuture<'a, T> = impl Future<Output = BufResult<usize, T>> where
+        T: 'a, Self: 'a;
+    type FlushFuture<'a> = impl Future<Output = io::Result<()>> where
+        Self: 'a;
+    type ShutdownFuture<'a> = impl Future<Output = io::Result<()>> where
+        Self: 'a;
+
+    fn write<T: IoBuf>(&mut self, buf: T) -> Self::WriteFuture<'_, T> {
+        async move {
+            if let Err(e) = self.fault().await {
+                return (Err(e), buf);
+            }
+            let len = self.chunk(buf.bytes_init());
+            let raw_buf = unsafe { RawBuf::new(buf.read_ptr(), len) };
+            let res = self.io.write(raw_buf).await.0;
+            (res, buf)
+        }
+    }
+
+    fn writev<T: IoVecBuf>(&mut self, buf_vec: T) -> Self::WritevFuture<'_, T> {
+        async move {
+            let res = match unsafe { RawBuf::new_from_iovec(&buf_vec) } {
+                Some(raw_buf) => self.write(raw_buf).await.0,
+                None => Ok(0),
+            };
+            (res, buf_vec)
+        }
+    }
+
+    fn flush(&mut self) -> Self::FlushFuture<'_> {
+        async move {
+            self.fault().await?;
+            self.io.flush().await
+        }
+    }
+
+    fn shutdown(&mut self) -> Self::ShutdownFuture<'_> {
+        async move {
+            self.fault().await?;
+            self.io.shutdown().await
+        }
+    }
+}
@@ -0,0 +1,417 @@
+use std::{future::Future, io, pin::Pin};
+
+use crate::{
+    buf::{IoBuf, IoBufMut},
+    driver::{
+        op::{is_legacy, submit_linked, Op},
+        shared_fd::{AsSharedFd, SharedFd},
+    },
+    fs::{File, SyncRangeFlags},
+    net::{TcpStream, UnixStream},
+};
+
+/// Create an empty [`Chain`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use monoio::fs::File;
+///
+/// #[monoio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let file = File::create("foo.txt").await?;
+///
+///     let (results, bufs) = monoio::io::chain()
+///         .write_at(&file, b"hello ".to_vec(), 0)
+///         .write_at(&file, b"world".to_vec(), 6)
+///         .sync_data(&file)
+///         .submit()
+///         .await;
+///     for res in results {
+///         res?;
+///     }
+///     // The sync step has no buffer.
+///     assert_eq!(bufs.len(), 3);
+///     assert!(bufs[2].is_none());
+///     Ok(())
+/// }
+/// ```
+pub fn chain<B: 'static>() -> Chain<B> {
+    Chain::new()
+}
+
+/// A sequence of operations submitted together, each one starting only after
+/// the previous one has completed.
+///
+/// With the uring driver, the chain maps to linked SQEs(`IOSQE_IO_LINK`), so
+/// the whole chain costs a single round trip through the scheduler. With the
+/// legacy driver, the steps are executed one after another.
+///
+/// By default, a step which fails cancels the rest of the chain, whose results
+/// are then errors with `ECANCELED`. Note that a short `read_at` or `write_at`
+/// counts as a failure, unlike a short `recv` or `send`. Use
+/// [`Chain::hard_link`] to keep going after failures.
+///
+/// All the buffers of a chain are of the same type `B`, and are returned by
+/// step once the chain is done.
+pub struct Chain<B> {
+    steps: Vec<Box<dyn Step<B>>>,
+    hard: bool,
+}
+
+/// A file descriptor a [`Chain`] step operates on.
+///
+/// It can be created from a reference to a [`File`], a [`TcpStream`] or a
+/// [`UnixStream`]. It keeps the file descriptor open until the chain is done.
+pub struct ChainFd {
+    fd: SharedFd,
+}
+
+impl From<&File> for ChainFd {
+    fn from(file: &File) -> Self {
+        ChainFd {
+            fd: file.as_shared_fd().clone(),
+        }
+    }
+}
+
+impl From<&TcpStream> for ChainFd {
+    fn from(stream: &TcpStream) -> Self {
+        ChainFd {
+            fd: stream.as_shared_fd().clone(),
+        }
+    }
+}
+
+impl From<&UnixStream> for ChainFd {
+    fn from(stream: &UnixStream) -> Self {
+        ChainFd {
+            fd: stream.as_shared_fd().clone(),
+        }
+    }
+}
+
+impl<B: 'static> Chain<B> {
+    /// Create an empty chain.
+    pub fn new() -> Self {
+        Chain {
+            steps: Vec::new(),
+            hard: false,
+        }
+    }
+
+    /// Keep executing the chain when a step fails(`IOSQE_IO_HARDLINK`).
+    pub fn hard_link(mut self, hard: bool) -> Self {
+        self.hard = hard;
+        self
+    }
+
+    /// Number of steps in the chain.
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Returns true if the chain has no step.
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Read into `buf` at `pos` of the file, like [`File::read_at`].
+    pub fn read_at(mut self, fd: impl Into<ChainFd>, buf: B, pos: u64) -> Self
+    where
+        B: IoBufMut,
+    {
+        self.steps.push(Box::new(ReadAtStep {
+            fd: fd.into().fd,
+            buf,
+            pos,
+        }));
+        self
+    }
+
+    /// Write `buf` at `pos` of the file, like [`File::write_at`].
+    pub fn write_at(mut self, fd: impl Into<ChainFd>, buf: B, pos: u64) -> Self
+    where
+        B: IoBuf,
+    {
+        self.steps.push(Box::new(WriteAtStep {
+            fd: fd.into().fd,
+            buf,
+            pos,
+        }));
+        self
+    }
+
+    /// Receive into `buf` from a stream.
+    pub fn recv(mut self, fd: impl Into<ChainFd>, buf: B) -> Self
+    where
+        B: IoBufMut,
+    {
+        self.steps.push(Box::new(RecvStep {
+            fd: fd.into().fd,
+            buf,
+        }));
+        self
+    }
+
+    /// Send `buf` to a stream.
+    pub fn send(mut self, fd: impl Into<ChainFd>, buf: B) -> Self
+    where
+        B: IoBuf,
+    {
+        self.steps.push(Box::new(SendStep {
+            fd: fd.into().fd,
+            buf,
+        }));
+        self
+    }
+
+    /// Sync the file data and metadata, like [`File::sync_all`].
+    pub fn sync_all(mut self, fd: impl Into<ChainFd>) -> Self {
+        self.steps.push(Box::new(FsyncStep {
+            fd: fd.into().fd,
+            data_sync: false,
+            offset: 0,
+            len: 0,
+        }));
+        self
+    }
+
+    /// Sync the file data, like [`File::sync_data`].
+    pub fn sync_data(self, fd: impl Into<ChainFd>) -> Self {
+        self.sync_data_range(fd, 0, 0)
+    }
+
+    /// Sync the file data in a range, like [`File::sync_data_range`].
+    pub fn sync_data_range(mut self, fd: impl Into<ChainFd>, offset: u64, len: u64) -> Self {
+        self.steps.push(Box::new(FsyncStep {
+            fd: fd.into().fd,
+            data_sync: true,
+            offset,
+            len,
+        }));
+        self
+    }
+
+    /// Write-out dirty pages in a range, like [`File::sync_range`].
+    pub fn sync_range(
+        mut self,
+        fd: impl Into<ChainFd>,
+        offset: u64,
+        len: u64,
+        flags: SyncRangeFlags,
+    ) -> Self {
+        self.steps.push(Box::new(SyncRangeStep {
+            fd: fd.into().fd,
+            offset,
+            len,
+            flags,
+        }));
+        self
+    }
+
+    /// Submit the chain and wait for all the steps to complete.
+    ///
+    /// Returns the result and the buffer of each step, in the order of the
+    /// steps. Steps without a buffer, such as syncs, have `None`.
+    pub async fn submit(self) -> (Vec<io::Result<usize>>, Vec<Option<B>>) {
+        let mut results = Vec::with_capacity(self.steps.len());
+        let mut bufs = Vec::with_capacity(self.steps.len());
+
+        if is_legacy() {
+            // Ops are executed when polled, so stop creating them once the
+            // chain is broken.
+            let mut broken = false;
+            for step in self.steps {
+                if broken {
+                    results.push(Err(io::Error::from_raw_os_error(libc::ECANCELED)));
+                    bufs.push(step.cancel());
+                    continue;
+                }
+                let (expected, fut) = step.submit();
+                let (res, buf) = fut.await;
+                broken = !self.hard && is_broken(&res, expected);
+                results.push(res);
+                bufs.push(buf);
+            }
+        } else {
+            let hard = self.hard;
+            let steps = self.steps;
+            let futs: Vec<_> = submit_linked(hard, || {
+                steps.into_iter().map(|step| step.submit()).collect()
+            });
+            for (_, fut) in futs {
+                let (res, buf) = fut.await;
+                results.push(res);
+                bufs.push(buf);
+            }
+        }
+        (results, bufs)
+    }
+}
+
+impl<B: 'static> Default for Chain<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// The kernel breaks a soft link on errors, and on short transfers of the
+// steps expecting a length.
+fn is_broken(res: &io::Result<usize>, expected: Option<usize>) -> bool {
+    match (res, expected) {
+        (Err(_), _) => true,
+        (Ok(n), Some(expected)) => *n < expected,
+        (Ok(_), None) => false,
+    }
+}
+
+type StepFuture<B> = Pin<Box<dyn Future<Output = (io::Result<usize>, Option<B>)>>>;
+
+trait Step<B> {
+    /// Submit the op of the step. Returns the length of a transfer which
+    /// breaks the chain if short, and the future of the op.
+    fn submit(self: Box<Self>) -> (Option<usize>, StepFuture<B>);
+
+    /// Give back the buffer of a step which will not be submitted.
+    fn cancel(self: Box<Self>) -> Option<B>;
+}
+
+struct ReadAtStep<B> {
+    fd: SharedFd,
+    buf: B,
+    pos: u64,
+}
+
+impl<B: IoBufMut> Step<B> for ReadAtStep<B> {
+    fn submit(self: Box<Self>) -> (Option<usize>, StepFuture<B>) {
+        let expected = self.buf.bytes_total();
+        let op = Op::read_at(&self.fd, self.buf, self.pos).unwrap();
+        (
+            Some(expected),
+            Box::pin(async move {
+                let (res, buf) = op.read().await;
+                (res, Some(buf))
+            }),
+        )
+    }
+
+    fn cancel(self: Box<Self>) -> Option<B> {
+        Some(self.buf)
+    }
+}
+
+struct WriteAtStep<B> {
+    fd: SharedFd,
+    buf: B,
+    pos: u64,
+}
+
+impl<B: IoBuf> Step<B> for WriteAtStep<B> {
+    fn submit(self: Box<Self>) -> (Option<usize>, StepFuture<B>) {
+        let expected = self.buf.bytes_init();
+        let op = Op::write_at(&self.fd, self.buf, self.pos).unwrap();
+        (
+            Some(expected),
+            Box::pin(async move {
+                let (res, buf) = op.write().await;
+                (res, Some(buf))
+            }),
+        )
+    }
+
+    fn cancel(self: Box<Self>) -> Option<B> {
+        Some(self.buf)
+    }
+}
+
+struct RecvStep<B> {
+    fd: SharedFd,
+    buf: B,
+}
+
+// Without MSG_WAITALL, a short recv or send does not break the chain.
+impl<B: IoBufMut> Step<B> for RecvStep<B> {
+    fn submit(self: Box<Self>) -> (Option<usize>, StepFuture<B>) {
+        let op = Op::recv(&self.fd, self.buf).unwrap();
+        (
+            None,
+            Box::pin(async move {
+                let (res, buf) = op.read().await;
+                (res, Some(buf))
+            }),
+        )
+    }
+
+    fn cancel(self: Box<Self>) -> Option<B> {
+        Some(self.buf)
+    }
+}
+
+struct SendStep<B> {
+    fd: SharedFd,
+    buf: B,
+}
+
+impl<B: IoBuf> Step<B> for SendStep<B> {
+    fn submit(self: Box<Self>) -> (Option<usize>, StepFuture<B>) {
+        let op = Op::send(&self.fd, self.buf).unwrap();
+        (
+            None,
+            Box::pin(async move {
+                let (res, buf) = op.write().await;
+                (res, Some(buf))
+            }),
+        )
+    }
+
+    fn cancel(self: Box<Self>) -> Option<B> {
+        Some(self.buf)
+    }
+}
+
+struct FsyncStep {
+    fd: SharedFd,
+    data_sync: bool,
+    offset: u64,
+    len: u64,
+}
+
+impl<B: 'static> Step<B> for FsyncStep {
+    fn submit(self: Box<Self>) -> (Option<usize>, StepFuture<B>) {
+        let op = if self.data_sync {
+            Op::datasync_range(&self.fd, self.offset, self.len).unwrap()
+        } else {
+            Op::fsync_range(&self.fd, self.offset, self.len).unwrap()
+        };
+        (
+            None,
+            Box::pin(async move { (op.await.meta.result.map(|_| 0), None) }),
+        )
+    }
+
+    fn cancel(self: Box<Self>) -> Option<B> {
+        None
+    }
+}
+
+struct SyncRangeStep {
+    fd: SharedFd,
+    offset: u64,
+    len: u64,
+    flags: SyncRangeFlags,
+}
+
+impl<B: 'static> Step<B> for SyncRangeStep {
+    fn submit(self: Box<Self>) -> (Option<usize>, StepFuture<B>) {
+        let op = Op::sync_file_range(&self.fd, self.offset, self.len, self.flags.bits()).unwrap();
+        (
+            None,
+            Box::pin(async move { (op.await.meta.result.map(|_| 0), None) }),
+        )
+    }
+
+    fn cancel(self: Box<Self>) -> Option<B> {
+        None
+    }
+}
@@ -0,0 +1,183 @@
+use crate::{
+    io::{AsyncReadRent, AsyncWriteRent, AsyncWriteRentExt},
+    macros::support::poll_fn,
+    net::{TcpStream, UnixStream},
+    time::{sleep, Duration, Instant},
+};
+use std::{cell::Cell, future::Future, io, task::Poll};
+
+const BUF_SIZE: usize = 4 * 1024;
+
+/// IO types which can be split into owned read and write halves.
+pub trait IntoSplit {
+    /// The read half.
+    type ReadHalf: AsyncReadRent;
+    /// The write half.
+    type WriteHalf: AsyncWriteRent;
+
+    /// Split into read and write halves with ownership.
+    fn into_split(self) -> (Self::ReadHalf, Self::WriteHalf);
+}
+
+impl IntoSplit for TcpStream {
+    type ReadHalf = crate::net::tcp::TcpOwnedReadHalf;
+    type WriteHalf = crate::net::tcp::TcpOwnedWriteHalf;
+
+    fn into_split(self) -> (Self::ReadHalf, Self::WriteHalf) {
+        TcpStream::into_split(self)
+    }
+}
+
+impl IntoSplit for UnixStream {
+    type ReadHalf = crate::net::unix::UnixOwnedReadHalf;
+    type WriteHalf = crate::net::unix::UnixOwnedWriteHalf;
+
+    fn into_split(self) -> (Self::ReadHalf, Self::WriteHalf) {
+        UnixStream::into_split(self)
+    }
+}
+
+/// Copy data in both directions between `a` and `b`.
+///
+/// Both directions run concurrently on the split halves. When one side
+/// reaches EOF, the write half of the other side is shut down, while the
+/// other direction keeps going until it reaches EOF too.
+///
+/// Returns the number of bytes copied from `a` to `b` and from `b` to `a`.
+/// The first error of either direction is returned right away.
+///
+/// # Examples
+///
+/// ```no_run
+/// use monoio::net::{TcpListener, TcpStream};
+///
+/// #[monoio::main]
+/// async fn main() -> std::io::Result<()> {
+///     let listener = TcpListener::bind("127.0.0.1:8080")?;
+///     let (inbound, _) = listener.accept().await?;
+///     let outbound = TcpStream::connect("127.0.0.1:8081").await?;
+///     let (sent, received) = monoio::io::copy_bidirectional(inbound, outbound).await?;
+///     println!("sent {} bytes, received {} bytes", sent, received);
+///     Ok(())
+/// }
+/// ```
+pub async fn copy_bidirectional<A, B>(a: A, b: B) -> io::Result<(u64, u64)>
+where
+    A: IntoSplit,
+    B: IntoSplit,
+{
+    copy_bidirectional_inner(a, b, None).await
+}
+
+/// Like [`copy_bidirectional`], but fails with `ErrorKind::TimedOut` once no
+/// data has been transferred in either direction for `idle`.
+///
+/// The timer must be enabled.
+pub async fn copy_bidirectional_with_idle_timeout<A, B>(
+    a: A,
+    b: B,
+    idle: Duration,
+) -> io::Result<(u64, u64)>
+where
+    A: IntoSplit,
+    B: IntoSplit,
+{
+    copy_bidirectional_inner(a, b, Some(idle)).await
+}
+
+async fn copy_bidirectional_inner<A, B>(
+    a: A,
+    b: B,
+    idle: Option<Duration>,
+) -> io::Result<(u64, u64)>
+where
+    A: IntoSplit,
+    B: IntoSplit,
+{
+    let (mut a_read, mut a_write) = a.into_split();
+    let (mut b_read, mut b_write) = b.into_split();
+
+    // Last time data was transferred, only tracked with an idle timeout.
+    let last_active = idle.map(|_| Cell::new(Instant::now()));
+    let a_to_b = copy_one_way(&mut a_read, &mut b_write, last_active.as_ref());
+    let b_to_a = copy_one_way(&mut b_read, &mut a_write, last_active.as_ref());
+    pin!(a_to_b, b_to_a);
+    let mut timer = idle.map(|idle| Box::pin(sleep(idle)));
+
+    let mut a_to_b_done = None;
+    let mut b_to_a_done = None;
+    poll_fn(|cx| {
+        if a_to_b_done.is_none() {
+            if let Poll::Ready(res) = a_to_b.as_mut().poll(cx) {
+                a_to_b_done = Some(res?);
+            }
+        }
+        if b_to_a_done.is_none() {
+            if let Poll::Ready(res) = b_to_a.as_mut().poll(cx) {
+                b_to_a_done = Some(res?);
+            }
+        }
+        if let (Some(a_to_b), Some(b_to_a)) = (a_to_b_done, b_to_a_done) {
+            return Poll::Ready(Ok((a_to_b, b_to_a)));
+        }
+
+        if let (Some(timer), Some(idle), Some(last_active)) =
+            (timer.as_mut(), idle, last_active.as_ref())
+        {
+            while timer.as_mut().poll(cx).is_ready() {
+                let deadline = last_active.get() + idle;
+                if deadline <= Instant::now() {
+                    return Poll::Ready(Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        "connection idle for too long",
+                    )));
+                }
+                timer.as_mut().reset(deadline);
+            }
+        }
+        Poll::Pending
+    })
+    .await
+}
+
+async fn copy_one_way<R, W>(
+    reader: &mut R,
+    writer: &mut W,
+    last_active: Option<&Cell<Instant>>,
+) -> io::Result<u64>
+where
+    R: AsyncReadRent,
+    W: AsyncWriteRent,
+{
+    let mut buf: Vec<u8> = Vec::with_capacity(BUF_SIZE);
+    let mut transfered: u64 = 0;
+
+    loop {
+        let (read_res, buf_read) = reader.read(buf).await;
+        let n = match read_res {
+            Ok(0) => {
+                // read closed, propagate it to the other side
+                writer.shutdown().await?;
+                return Ok(transfered);
+            }
+            Ok(n) => n,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {
+                buf = buf_read;
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        if let Some(last_active) = last_active {
+            last_active.set(Instant::now());
+        }
+
+        let (write_res, mut buf_written) = writer.write_all(buf_read).await;
+        write_res?;
+        if let Some(last_active) = last_active {
+            last_active.set(Instant::now());
+        }
+        transfered += n as u64;
+        buf_written.clear();
+        buf = buf_written;
+    }
+}
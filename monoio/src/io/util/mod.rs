@@ -2,10 +2,17 @@
 
 mod buf_reader;
 mod buf_writer;
+mod chain;
 mod copy;
+mod copy_bidirectional;
 mod prefixed_io;
+mod zero_copy;
 
 pub use buf_reader::BufReader;
 pub use buf_writer::BufWriter;
+pub use chain::{chain, Chain, ChainFd};
 pub use copy::copy;
+pub use copy_bidirectional::{copy_bidirectional, copy_bidirectional_with_idle_timeout, IntoSplit};
 pub use prefixed_io::PrefixedReadIo;
+pub(crate) use zero_copy::send_file;
+pub use zero_copy::{zero_copy, SpliceDestination, SpliceFd, SpliceSource};
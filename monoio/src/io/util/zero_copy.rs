@@ -0,0 +1,255 @@
+use super::copy;
+use crate::{
+    driver::shared_fd::{AsSharedFd, SharedFd},
+    io::{AsyncReadRent, AsyncWriteRent},
+    net::{TcpStream, UnixStream},
+};
+use std::io;
+
+/// Streams which [`zero_copy`] can read from.
+pub trait SpliceSource: AsyncReadRent {
+    /// Returns the file descriptor data is spliced from.
+    fn splice_fd(&self) -> SpliceFd;
+}
+
+/// Streams which [`zero_copy`] can write to.
+pub trait SpliceDestination: AsyncWriteRent {
+    /// Returns the file descriptor data is spliced to.
+    fn splice_fd(&self) -> SpliceFd;
+}
+
+/// An opaque file descriptor used by [`zero_copy`].
+///
+/// It keeps the file descriptor open until the copy is done.
+pub struct SpliceFd {
+    pub(crate) fd: SharedFd,
+}
+
+impl SpliceFd {
+    pub(crate) fn new<T: AsSharedFd>(io: &T) -> Self {
+        Self {
+            fd: io.as_shared_fd().clone(),
+        }
+    }
+}
+
+impl SpliceSource for TcpStream {
+    fn splice_fd(&self) -> SpliceFd {
+        SpliceFd::new(self)
+    }
+}
+
+impl SpliceDestination for TcpStream {
+    fn splice_fd(&self) -> SpliceFd {
+        SpliceFd::new(self)
+    }
+}
+
+impl SpliceSource for UnixStream {
+    fn splice_fd(&self) -> SpliceFd {
+        SpliceFd::new(self)
+    }
+}
+
+impl SpliceDestination for UnixStream {
+    fn splice_fd(&self) -> SpliceFd {
+        SpliceFd::new(self)
+    }
+}
+
+/// Copy data from reader to writer without copying it through userspace.
+///
+/// On Linux, data is moved with `splice(2)` through an internal pipe, which
+/// is `IORING_OP_SPLICE` with the uring driver. Elsewhere, or if the reader
+/// does not support splicing, it falls back to [`copy`]. Use [`copy`] directly
+/// for IO types which do not expose a file descriptor.
+///
+/// Returns the number of bytes copied once the reader is closed.
+///
+/// # Examples
+///
+/// ```no_run
+/// use monoio::net::TcpStream;
+///
+/// #[monoio::main]
+/// async fn main() -> std::io::Result<()> {
+///     let mut inbound = TcpStream::connect("127.0.0.1:8080").await?;
+///     let mut outbound = TcpStream::connect("127.0.0.1:8081").await?;
+///     let (mut in_r, _in_w) = inbound.split();
+///     let (_out_r, mut out_w) = outbound.split();
+///     monoio::io::zero_copy(&mut in_r, &mut out_w).await?;
+///     Ok(())
+/// }
+/// ```
+pub async fn zero_copy<R, W>(reader: &mut R, writer: &mut W) -> io::Result<u64>
+where
+    R: SpliceSource + ?Sized,
+    W: SpliceDestination + ?Sized,
+{
+    #[cfg(target_os = "linux")]
+    {
+        use crate::driver::op::Op;
+
+        let src = reader.splice_fd();
+        let dst = writer.splice_fd();
+        let (pipe_r, pipe_w) = match new_pipe() {
+            Ok(pipe) => pipe,
+            Err(_) => return copy(reader, writer).await,
+        };
+
+        let mut transfered: u64 = 0;
+        loop {
+            let n = match Op::splice(&src.fd, &pipe_w, PIPE_SIZE)?.await.meta.result {
+                Ok(0) => {
+                    // read closed
+                    break;
+                }
+                Ok(n) => n,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) if transfered == 0 && is_unsupported(&e) => {
+                    // nothing is in the pipe yet
+                    return copy(reader, writer).await;
+                }
+                Err(e) => return Err(e),
+            };
+
+            drain_pipe(&pipe_r, &dst.fd, n).await?;
+            transfered += n as u64;
+        }
+        Ok(transfered)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    copy(reader, writer).await
+}
+
+/// Send `len` bytes of `file` from `offset` to `socket`, stopping early at
+/// the end of the file. Returns the number of bytes sent.
+pub(crate) async fn send_file(
+    socket: &SharedFd,
+    file: &SharedFd,
+    offset: u64,
+    len: usize,
+) -> io::Result<usize> {
+    use crate::driver::op::Op;
+
+    let mut sent = 0;
+
+    #[cfg(target_os = "linux")]
+    {
+        if crate::driver::op::is_legacy() {
+            while sent < len {
+                let op = Op::sendfile(socket, file, offset + sent as u64, len - sent)?;
+                match op.await.meta.result {
+                    Ok(0) => break,
+                    Ok(n) => sent += n as usize,
+                    Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(e) => return Err(e),
+                }
+            }
+            return Ok(sent);
+        }
+
+        let (pipe_r, pipe_w) = new_pipe()?;
+        while sent < len {
+            let chunk = (len - sent).min(PIPE_SIZE as usize) as u32;
+            let op = Op::splice_at(file, offset + sent as u64, &pipe_w, chunk)?;
+            let n = match op.await.meta.result {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            drain_pipe(&pipe_r, socket, n).await?;
+            sent += n as usize;
+        }
+        Ok(sent)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        use crate::buf::IoBuf;
+
+        let mut buf = Vec::with_capacity(len.min(64 * 1024));
+        while sent < len {
+            let want = (len - sent).min(buf.capacity());
+            let (res, mut buf_read) = Op::read_at(file, buf, offset + sent as u64)?.read().await;
+            let n = match res {
+                Ok(0) => break,
+                Ok(n) => n.min(want),
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {
+                    buf = buf_read;
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            buf_read.truncate(n);
+
+            let mut written = 0;
+            while written < n {
+                let (res, buf_) = Op::send(socket, buf_read.slice(written..))?.write().await;
+                buf_read = buf_.into_inner();
+                match res {
+                    Ok(0) => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::WriteZero,
+                            "write zero byte into socket",
+                        ));
+                    }
+                    Ok(m) => written += m,
+                    Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(e) => return Err(e),
+                }
+            }
+            sent += n;
+            buf_read.clear();
+            buf = buf_read;
+        }
+        Ok(sent)
+    }
+}
+
+// Default capacity of a pipe.
+#[cfg(target_os = "linux")]
+const PIPE_SIZE: u32 = 64 * 1024;
+
+// Move `len` bytes which are in the pipe to `dst`.
+#[cfg(target_os = "linux")]
+async fn drain_pipe(pipe: &SharedFd, dst: &SharedFd, len: u32) -> io::Result<()> {
+    use crate::driver::op::Op;
+
+    let mut left = len;
+    while left > 0 {
+        match Op::splice(pipe, dst, left)?.await.meta.result {
+            Ok(0) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::WriteZero,
+                    "write zero byte into writer",
+                ));
+            }
+            Ok(written) => left -= written,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn new_pipe() -> io::Result<(SharedFd, SharedFd)> {
+    let mut fds = [0; 2];
+    crate::syscall!(pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC))?;
+    // Pipes are not registered to the legacy driver, splicing waits for the
+    // readiness of the stream instead.
+    let r = SharedFd::new_without_register(fds[0])?;
+    let w = SharedFd::new_without_register(fds[1])?;
+    Ok((r, w))
+}
+
+#[cfg(target_os = "linux")]
+fn is_unsupported(e: &io::Error) -> bool {
+    matches!(
+        e.raw_os_error(),
+        Some(libc::EINVAL) | Some(libc::ENOSYS) | Some(libc::EOPNOTSUPP)
+    )
+}
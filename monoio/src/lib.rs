@@ -22,6 +22,8 @@ pub use monoio_macros::select_priv_declare_output_enum;
 #[macro_use]
 mod driver;
 pub(crate) mod builder;
+#[cfg(feature = "sync")]
+mod handle;
 pub(crate) mod runtime;
 mod scheduler;
 pub mod time;
@@ -31,13 +33,22 @@ extern crate alloc;
 pub mod buf;
 pub mod fs;
 pub mod io;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 pub mod net;
+pub mod process;
+pub mod signal;
+#[cfg(feature = "sim")]
+pub mod sim;
 pub mod task;
 pub mod utils;
 
-pub use builder::{Buildable, RuntimeBuilder};
+pub use builder::{Buildable, RuntimeBuilder, UringConfig};
 pub use driver::Driver;
-pub use runtime::{spawn, Runtime};
+pub use runtime::{shutdown_token, spawn, spawn_with_priority, Runtime, ShutdownToken};
+
+#[cfg(feature = "sync")]
+pub use handle::{RemoteJoinHandle, RuntimeHandle};
 
 #[cfg(any(all(target_os = "linux", feature = "iouring"), feature = "legacy"))]
 pub use {builder::FusionDriver, runtime::FusionRuntime};
@@ -46,6 +57,8 @@ pub use {builder::FusionDriver, runtime::FusionRuntime};
 pub use driver::IoUringDriver;
 #[cfg(feature = "legacy")]
 pub use driver::LegacyDriver;
+#[cfg(feature = "sim")]
+pub use driver::SimDriver;
 
 #[cfg(feature = "macros")]
 pub use monoio_macros::{main, test, test_all};
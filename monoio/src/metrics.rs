@@ -0,0 +1,133 @@
+//! Runtime metrics.
+//!
+//! Counters are updated by the runtime with plain thread local increments, and
+//! a [`RuntimeMetrics`] snapshot is cheap enough to be taken every second.
+
+use std::{
+    cell::{Cell, RefCell},
+    time::Duration,
+};
+
+use fxhash::FxHashMap;
+
+use crate::task::Priority;
+
+/// A snapshot of the metrics of a runtime.
+///
+/// Counters are cumulative since the runtime is built, others are sampled
+/// when the snapshot is taken.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct RuntimeMetrics {
+    /// Number of tasks in the run queues.
+    pub task_queue_depth: usize,
+    /// Metrics of each run queue, from the highest priority to the lowest.
+    pub queues: Vec<QueueMetrics>,
+    /// Number of spawned tasks.
+    pub tasks_spawned: u64,
+    /// Number of spawned tasks which completed or were dropped.
+    pub tasks_completed: u64,
+    /// Number of task polls, including the polls of the `block_on` future.
+    pub polls: u64,
+    /// Number of times the runtime parked waiting for events.
+    pub parks: u64,
+    /// Total time spent parked.
+    pub park_duration: Duration,
+    /// Number of in-flight ops in the driver. With uring, it includes ops
+    /// whose future has been dropped but which the kernel has not completed.
+    pub in_flight_ops: usize,
+    /// Number of pending ops per op type, sorted by name.
+    pub ops: Vec<(&'static str, usize)>,
+    /// Number of submissions of the uring submission queue.
+    pub submit_batches: u64,
+    /// Number of completions dropped because the uring completion queue was
+    /// full.
+    pub cq_overflow: u64,
+    /// Number of timers registered in the timer wheel.
+    pub timers: usize,
+}
+
+/// Metrics of the run queue of a [`Priority`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct QueueMetrics {
+    /// Priority of the tasks of the queue.
+    pub priority: Priority,
+    /// Number of tasks in the queue.
+    pub depth: usize,
+    /// Number of polls of tasks from the queue.
+    pub polls: u64,
+}
+
+/// Returns the metrics of the current runtime.
+///
+/// # Panics
+///
+/// Panics if called outside a runtime.
+///
+/// # Examples
+///
+/// ```no_run
+/// #[monoio::main(timer_enabled = true)]
+/// async fn main() {
+///     loop {
+///         monoio::time::sleep(std::time::Duration::from_secs(1)).await;
+///         let metrics = monoio::metrics::current();
+///         println!("queue depth: {}", metrics.task_queue_depth);
+///     }
+/// }
+/// ```
+pub fn current() -> RuntimeMetrics {
+    let mut metrics = crate::runtime::CURRENT.with(|ctx| ctx.metrics());
+    crate::driver::CURRENT.with(|inner| inner.fill_metrics(&mut metrics));
+    metrics
+}
+
+/// Counters of a runtime, owned by its context.
+#[derive(Default)]
+pub(crate) struct Counters {
+    pub(crate) tasks_spawned: Cell<u64>,
+    pub(crate) polls: Cell<u64>,
+    pub(crate) parks: Cell<u64>,
+    pub(crate) park_duration: Cell<Duration>,
+    // Keyed by the type name of the op.
+    ops: RefCell<FxHashMap<&'static str, usize>>,
+}
+
+impl Counters {
+    pub(crate) fn incr(counter: &Cell<u64>) {
+        counter.set(counter.get() + 1);
+    }
+
+    pub(crate) fn op_submitted(&self, name: &'static str) {
+        *self.ops.borrow_mut().entry(name).or_default() += 1;
+    }
+
+    pub(crate) fn op_dropped(&self, name: &'static str) {
+        // It may be dropped in another runtime than the one it was counted in.
+        if let Some(count) = self.ops.borrow_mut().get_mut(name) {
+            *count = count.saturating_sub(1);
+        }
+    }
+
+    pub(crate) fn fill(&self, metrics: &mut RuntimeMetrics) {
+        metrics.tasks_spawned = self.tasks_spawned.get();
+        metrics.polls = self.polls.get();
+        metrics.parks = self.parks.get();
+        metrics.park_duration = self.park_duration.get();
+
+        // Merge the monomorphizations of an op, e.g. `Read<Vec<u8>>` and
+        // `Read<Box<[u8]>>` are both `Read`.
+        let mut ops: Vec<(&'static str, usize)> = Vec::new();
+        for (name, count) in self.ops.borrow().iter() {
+            let name = crate::utils::short_name(name);
+            match ops.iter_mut().find(|(n, _)| *n == name) {
+                Some((_, c)) => *c += count,
+                None => ops.push((name, *count)),
+            }
+        }
+        ops.retain(|(_, count)| *count != 0);
+        ops.sort_unstable();
+        metrics.ops = ops;
+    }
+}
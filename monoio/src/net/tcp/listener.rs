@@ -147,6 +147,8 @@ impl TcpListener {
             #[cfg(all(target_os = "linux", feature = "iouring"))]
             crate::driver::Inner::Uring(_) => Ok(()),
             crate::driver::Inner::Legacy(_) => _socket.set_nonblocking(true),
+            #[cfg(feature = "sim")]
+            crate::driver::Inner::Sim(_) => Ok(()),
         })
     }
 }
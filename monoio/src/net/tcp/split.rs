@@ -5,7 +5,7 @@ use std::{
 
 use crate::{
     buf::{IoBuf, IoBufMut, IoVecBuf, IoVecBufMut},
-    io::{AsyncReadRent, AsyncWriteRent},
+    io::{AsyncReadRent, AsyncWriteRent, SpliceDestination, SpliceFd, SpliceSource},
 };
 
 use super::TcpStream;
@@ -221,3 +221,27 @@ impl Drop for OwnedWriteHalf {
         unsafe { libc::shutdown(fd, libc::SHUT_WR) };
     }
 }
+
+impl<'t> SpliceSource for ReadHalf<'t> {
+    fn splice_fd(&self) -> SpliceFd {
+        SpliceFd::new(self.0)
+    }
+}
+
+impl<'t> SpliceDestination for WriteHalf<'t> {
+    fn splice_fd(&self) -> SpliceFd {
+        SpliceFd::new(self.0)
+    }
+}
+
+impl SpliceSource for OwnedReadHalf {
+    fn splice_fd(&self) -> SpliceFd {
+        SpliceFd::new(unsafe { &*self.0.get() })
+    }
+}
+
+impl SpliceDestination for OwnedWriteHalf {
+    fn splice_fd(&self) -> SpliceFd {
+        SpliceFd::new(unsafe { &*self.0.get() })
+    }
+}
@@ -2,7 +2,7 @@ use std::{cell::UnsafeCell, error::Error, fmt, future::Future, io, rc::Rc};
 
 use crate::{
     buf::{IoBuf, IoBufMut, IoVecBuf, IoVecBufMut},
-    io::{AsyncReadRent, AsyncWriteRent},
+    io::{AsyncReadRent, AsyncWriteRent, SpliceDestination, SpliceFd, SpliceSource},
 };
 
 use super::{SocketAddr, UnixStream};
@@ -191,3 +191,27 @@ impl AsyncWriteRent for OwnedWriteHalf {
         raw_stream.shutdown()
     }
 }
+
+impl<'t> SpliceSource for ReadHalf<'t> {
+    fn splice_fd(&self) -> SpliceFd {
+        SpliceFd::new(self.0)
+    }
+}
+
+impl<'t> SpliceDestination for WriteHalf<'t> {
+    fn splice_fd(&self) -> SpliceFd {
+        SpliceFd::new(self.0)
+    }
+}
+
+impl SpliceSource for OwnedReadHalf {
+    fn splice_fd(&self) -> SpliceFd {
+        SpliceFd::new(unsafe { &*self.0.get() })
+    }
+}
+
+impl SpliceDestination for OwnedWriteHalf {
+    fn splice_fd(&self) -> SpliceFd {
+        SpliceFd::new(unsafe { &*self.0.get() })
+    }
+}
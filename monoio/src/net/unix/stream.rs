@@ -5,14 +5,20 @@ use super::{
 };
 use crate::{
     buf::{IoBuf, IoBufMut, IoVecBuf, IoVecBufMut},
-    driver::{op::Op, shared_fd::SharedFd},
-    io::{AsyncReadRent, AsyncWriteRent},
+    driver::{
+        op::{submit_with_timeout, Op},
+        shared_fd::{AsSharedFd, SharedFd},
+    },
+    fs::File,
+    io::{send_file, AsyncReadRent, AsyncWriteRent},
+    BufResult,
 };
 use std::{
     future::Future,
     io,
     os::unix::prelude::{AsRawFd, FromRawFd, IntoRawFd, RawFd},
     path::Path,
+    time::Duration,
 };
 
 /// UnixStream
@@ -89,6 +95,49 @@ impl UnixStream {
         peer_addr(self.as_raw_fd())
     }
 
+    /// Read some data from the stream into the buffer, failing with
+    /// `ErrorKind::TimedOut` if none arrived within `timeout`.
+    ///
+    /// Unlike wrapping [`read`](AsyncReadRent::read) with
+    /// [`time::timeout`](crate::time::timeout), the buffer is given back when
+    /// the timeout expires. With uring, the timeout is linked to the operation
+    /// itself(`IORING_OP_LINK_TIMEOUT`), so the kernel cancels it without a
+    /// round trip through the timer wheel. With the legacy driver, the timer
+    /// must be enabled, or it fails with `ErrorKind::Unsupported`.
+    pub async fn read_with_timeout<T: IoBufMut>(
+        &mut self,
+        buf: T,
+        timeout: Duration,
+    ) -> BufResult<usize, T> {
+        // Submit the recv request
+        let op = submit_with_timeout(timeout, || Op::recv(&self.fd, buf)).unwrap();
+        op.read().await
+    }
+
+    /// Write the buffer into the stream, failing with `ErrorKind::TimedOut` if
+    /// it could not be written within `timeout`.
+    ///
+    /// See [`UnixStream::read_with_timeout`] for the details.
+    pub async fn write_with_timeout<T: IoBuf>(
+        &mut self,
+        buf: T,
+        timeout: Duration,
+    ) -> BufResult<usize, T> {
+        // Submit the send request
+        let op = submit_with_timeout(timeout, || Op::send(&self.fd, buf)).unwrap();
+        op.write().await
+    }
+
+    /// Send `len` bytes of `file` starting at `offset` to the stream, without
+    /// copying them through userspace.
+    ///
+    /// With uring, data is spliced through an internal pipe. With the legacy
+    /// driver, `sendfile(2)` is used. Stops early at the end of the file and
+    /// returns the number of bytes sent.
+    pub async fn send_file(&mut self, file: &File, offset: u64, len: usize) -> io::Result<usize> {
+        send_file(&self.fd, file.as_shared_fd(), offset, len).await
+    }
+
     /// Split stream into read and write halves.
     #[allow(clippy::needless_lifetimes)]
     pub fn split<'a>(&'a mut self) -> (ReadHalf<'a>, WriteHalf<'a>) {
@@ -115,6 +164,12 @@ impl AsRawFd for UnixStream {
     }
 }
 
+impl AsSharedFd for UnixStream {
+    fn as_shared_fd(&self) -> &SharedFd {
+        &self.fd
+    }
+}
+
 impl std::fmt::Debug for UnixStream {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("UnixStream").field("fd", &self.fd).finish()
@@ -0,0 +1,400 @@
+//! Asynchronous child process management.
+//!
+//! [`Command`] mirrors [`std::process::Command`]. Piped stdio of the child
+//! implements [`AsyncReadRent`](crate::io::AsyncReadRent) and
+//! [`AsyncWriteRent`](crate::io::AsyncWriteRent), and waiting for the child to
+//! exit does not block the thread.
+
+use crate::{
+    buf::IoBufMut,
+    driver::{op::Op, shared_fd::SharedFd},
+    io::{AsyncReadRent, Receiver, Sender},
+    macros::support::{maybe_done, poll_fn},
+};
+use std::{
+    ffi::OsStr,
+    future::Future,
+    io,
+    os::unix::prelude::IntoRawFd,
+    path::Path,
+    process::{ExitStatus, Output, Stdio},
+    task::Poll,
+    time::Duration,
+};
+
+/// The stdin of a child process.
+pub type ChildStdin = Sender;
+/// The stdout of a child process.
+pub type ChildStdout = Receiver;
+/// The stderr of a child process.
+pub type ChildStderr = Receiver;
+
+/// A process builder, like [`std::process::Command`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use monoio::process::Command;
+///
+/// #[monoio::main]
+/// async fn main() -> std::io::Result<()> {
+///     let output = Command::new("echo").arg("hello").output().await?;
+///     assert!(output.status.success());
+///     assert_eq!(output.stdout, b"hello\n");
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Command {
+    inner: std::process::Command,
+    kill_on_drop: bool,
+}
+
+impl Command {
+    /// Create a new `Command` for launching the program at `program`.
+    pub fn new<S: AsRef<OsStr>>(program: S) -> Command {
+        Command {
+            inner: std::process::Command::new(program),
+            kill_on_drop: false,
+        }
+    }
+
+    /// Add an argument to pass to the program.
+    pub fn arg<S: AsRef<OsStr>>(&mut self, arg: S) -> &mut Command {
+        self.inner.arg(arg);
+        self
+    }
+
+    /// Add multiple arguments to pass to the program.
+    pub fn args<I, S>(&mut self, args: I) -> &mut Command
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        self.inner.args(args);
+        self
+    }
+
+    /// Insert or update an environment variable.
+    pub fn env<K, V>(&mut self, key: K, val: V) -> &mut Command
+    where
+        K: AsRef<OsStr>,
+        V: AsRef<OsStr>,
+    {
+        self.inner.env(key, val);
+        self
+    }
+
+    /// Insert or update multiple environment variables.
+    pub fn envs<I, K, V>(&mut self, vars: I) -> &mut Command
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<OsStr>,
+        V: AsRef<OsStr>,
+    {
+        self.inner.envs(vars);
+        self
+    }
+
+    /// Remove an environment variable.
+    pub fn env_remove<K: AsRef<OsStr>>(&mut self, key: K) -> &mut Command {
+        self.inner.env_remove(key);
+        self
+    }
+
+    /// Clear all the environment variables.
+    pub fn env_clear(&mut self) -> &mut Command {
+        self.inner.env_clear();
+        self
+    }
+
+    /// Set the working directory of the child process.
+    pub fn current_dir<P: AsRef<Path>>(&mut self, dir: P) -> &mut Command {
+        self.inner.current_dir(dir);
+        self
+    }
+
+    /// Configuration for the stdin of the child process.
+    pub fn stdin<T: Into<Stdio>>(&mut self, cfg: T) -> &mut Command {
+        self.inner.stdin(cfg);
+        self
+    }
+
+    /// Configuration for the stdout of the child process.
+    pub fn stdout<T: Into<Stdio>>(&mut self, cfg: T) -> &mut Command {
+        self.inner.stdout(cfg);
+        self
+    }
+
+    /// Configuration for the stderr of the child process.
+    pub fn stderr<T: Into<Stdio>>(&mut self, cfg: T) -> &mut Command {
+        self.inner.stderr(cfg);
+        self
+    }
+
+    /// Kill the child process when the [`Child`] is dropped before it exited.
+    pub fn kill_on_drop(&mut self, kill_on_drop: bool) -> &mut Command {
+        self.kill_on_drop = kill_on_drop;
+        self
+    }
+
+    /// Get the inner `std::process::Command`, for options not covered here.
+    pub fn as_std_mut(&mut self) -> &mut std::process::Command {
+        &mut self.inner
+    }
+
+    /// Execute the command as a child process.
+    pub fn spawn(&mut self) -> io::Result<Child> {
+        let mut child = self.inner.spawn()?;
+        let stdin = child.stdin.take();
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+
+        let mut child = Child {
+            pidfd: pidfd_open(child.id()),
+            child,
+            status: None,
+            kill_on_drop: self.kill_on_drop,
+            stdin: None,
+            stdout: None,
+            stderr: None,
+        };
+        // Safety: the fds are owned by the std handles.
+        unsafe {
+            child.stdin = stdin
+                .map(|s| Sender::from_pipe_fd(s.into_raw_fd()))
+                .transpose()?;
+            child.stdout = stdout
+                .map(|s| Receiver::from_pipe_fd(s.into_raw_fd()))
+                .transpose()?;
+            child.stderr = stderr
+                .map(|s| Receiver::from_pipe_fd(s.into_raw_fd()))
+                .transpose()?;
+        }
+        Ok(child)
+    }
+
+    /// Execute the command, wait for it to exit and collect its exit status.
+    ///
+    /// Stdio is inherited by default.
+    pub async fn status(&mut self) -> io::Result<ExitStatus> {
+        let mut child = self.spawn()?;
+        child.wait().await
+    }
+
+    /// Execute the command, wait for it to exit and collect its output.
+    ///
+    /// Stdout and stderr are piped, and stdin is null by default.
+    pub async fn output(&mut self) -> io::Result<Output> {
+        self.inner
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        self.spawn()?.wait_with_output().await
+    }
+}
+
+/// A child process spawned by [`Command::spawn`].
+///
+/// Unlike `std`, the child is killed on drop if it has not exited yet and
+/// [`Command::kill_on_drop`] is set.
+#[derive(Debug)]
+pub struct Child {
+    child: std::process::Child,
+    // Becomes readable when the child exits.
+    pidfd: Option<SharedFd>,
+    status: Option<ExitStatus>,
+    kill_on_drop: bool,
+
+    /// The stdin of the child, if piped.
+    pub stdin: Option<ChildStdin>,
+    /// The stdout of the child, if piped.
+    pub stdout: Option<ChildStdout>,
+    /// The stderr of the child, if piped.
+    pub stderr: Option<ChildStderr>,
+}
+
+impl Child {
+    /// Returns the OS-assigned process identifier.
+    pub fn id(&self) -> u32 {
+        self.child.id()
+    }
+
+    /// Send `SIGKILL` to the child process.
+    ///
+    /// Use [`Child::wait`] to wait for it to exit.
+    pub fn kill(&mut self) -> io::Result<()> {
+        if self.status.is_some() {
+            return Ok(());
+        }
+        self.child.kill()
+    }
+
+    /// Returns the exit status if the child has exited, without waiting.
+    pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
+        if self.status.is_none() {
+            self.status = self.child.try_wait()?;
+        }
+        Ok(self.status)
+    }
+
+    /// Wait for the child to exit, and return its exit status.
+    ///
+    /// The stdin of the child is closed before waiting, so that it does not
+    /// wait for input forever.
+    ///
+    /// On Linux 5.3+, the wait is driven by a pidfd polled by the driver.
+    /// Elsewhere, the child is polled periodically, which requires the timer
+    /// to be enabled.
+    pub async fn wait(&mut self) -> io::Result<ExitStatus> {
+        drop(self.stdin.take());
+        loop {
+            if let Some(status) = self.try_wait()? {
+                return Ok(status);
+            }
+            match &self.pidfd {
+                Some(pidfd) => {
+                    Op::poll_readable(pidfd)?.await.meta.result?;
+                }
+                None => crate::time::sleep(Duration::from_millis(10)).await,
+            }
+        }
+    }
+
+    /// Wait for the child to exit, and collect its exit status and all of
+    /// its stdout and stderr.
+    ///
+    /// The stdin of the child is closed first, as it would never exit while
+    /// waiting for more input.
+    pub async fn wait_with_output(mut self) -> io::Result<Output> {
+        drop(self.stdin.take());
+        let stdout = self.stdout.take();
+        let stderr = self.stderr.take();
+        // Read both concurrently, so that the child is not blocked writing
+        // to one of them while we wait on the other.
+        let stdout = maybe_done(read_to_end(stdout));
+        let stderr = maybe_done(read_to_end(stderr));
+        pin!(stdout, stderr);
+        poll_fn(|cx| {
+            let stdout_done = stdout.as_mut().poll(cx).is_ready();
+            let stderr_done = stderr.as_mut().poll(cx).is_ready();
+            if stdout_done && stderr_done {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await;
+        let stdout = stdout.take_output().expect("expected completed future");
+        let stderr = stderr.take_output().expect("expected completed future");
+        let status = self.wait().await?;
+        Ok(Output {
+            status,
+            stdout: stdout?,
+            stderr: stderr?,
+        })
+    }
+}
+
+impl Drop for Child {
+    fn drop(&mut self) {
+        if self.kill_on_drop && self.status.is_none() && self.child.kill().is_ok() {
+            // It takes a while to die, reap it in the background.
+            let reaper = Reaper {
+                pid: self.child.id() as libc::pid_t,
+                pidfd: self.pidfd.take(),
+                reaped: false,
+            };
+            if crate::runtime::CURRENT.is_set() {
+                crate::spawn(reaper.reap());
+            }
+        }
+    }
+}
+
+// Reaps a killed child, so that it does not stay a zombie.
+struct Reaper {
+    pid: libc::pid_t,
+    pidfd: Option<SharedFd>,
+    reaped: bool,
+}
+
+impl Reaper {
+    fn try_reap(&mut self) -> bool {
+        if !self.reaped {
+            let ret = unsafe { libc::waitpid(self.pid, std::ptr::null_mut(), libc::WNOHANG) };
+            self.reaped = ret != 0;
+        }
+        self.reaped
+    }
+
+    // Waits for the child to exit with its pidfd. Without one, it is reaped
+    // on drop.
+    async fn reap(mut self) {
+        while !self.try_reap() {
+            let readable = match &self.pidfd {
+                Some(pidfd) => Op::poll_readable(pidfd),
+                None => return,
+            };
+            let res = match readable {
+                Ok(op) => op.await.meta.result,
+                Err(e) => Err(e),
+            };
+            if res.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+impl Drop for Reaper {
+    fn drop(&mut self) {
+        if !self.try_reap() {
+            // No runtime is left to wait for it, block another thread.
+            let pid = self.pid;
+            std::thread::spawn(move || unsafe {
+                libc::waitpid(pid, std::ptr::null_mut(), 0);
+            });
+        }
+    }
+}
+
+async fn read_to_end(io: Option<Receiver>) -> io::Result<Vec<u8>> {
+    let mut io = match io {
+        Some(io) => io,
+        None => return Ok(Vec::new()),
+    };
+    let mut buf = Vec::with_capacity(4096);
+    loop {
+        if buf.len() == buf.capacity() {
+            buf.reserve(4096);
+        }
+        let len = buf.len();
+        let (res, slice) = io.read(buf.slice_mut(len..)).await;
+        buf = slice.into_inner();
+        if res? == 0 {
+            return Ok(buf);
+        }
+    }
+}
+
+// Returns None if pidfd is not supported.
+#[cfg(target_os = "linux")]
+fn pidfd_open(pid: u32) -> Option<SharedFd> {
+    let fd = unsafe { libc::syscall(libc::SYS_pidfd_open, pid as libc::pid_t, 0) };
+    if fd < 0 {
+        return None;
+    }
+    match SharedFd::new(fd as _) {
+        Ok(fd) => Some(fd),
+        Err(_) => {
+            unsafe { libc::close(fd as _) };
+            None
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn pidfd_open(_pid: u32) -> Option<SharedFd> {
+    None
+}
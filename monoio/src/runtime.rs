@@ -1,20 +1,30 @@
 use scoped_tls::scoped_thread_local;
 
+use crate::builder::Hooks;
 use crate::driver::Driver;
-use crate::scheduler::{LocalScheduler, TaskQueue};
+use crate::scheduler::{LocalScheduler, Priority, TaskQueue};
 #[cfg(all(target_os = "linux", feature = "iouring"))]
 use crate::IoUringDriver;
 #[cfg(feature = "legacy")]
 use crate::LegacyDriver;
 
+use crate::task::coop;
 use crate::task::waker_fn::{dummy_waker, set_poll, should_poll};
-use crate::task::{new_task, JoinHandle};
+use crate::task::{new_task, JoinHandle, TaskMeta};
 use crate::time::driver::Handle as TimeHandle;
 
 #[cfg(any(all(target_os = "linux", feature = "iouring"), feature = "legacy"))]
 use crate::time::TimeDriver;
 
-use std::future::Future;
+use std::{
+    cell::{Cell, RefCell},
+    collections::hash_map::Entry,
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Poll, Waker},
+    time::{Duration, Instant},
+};
 
 scoped_thread_local!(pub(crate) static CURRENT: Context);
 
@@ -33,10 +43,29 @@ pub(crate) struct Context {
     pub(crate) waker_sender_cache:
         std::cell::RefCell<fxhash::FxHashMap<usize, flume::Sender<std::task::Waker>>>,
 
+    /// Tasks spawned from other threads
+    #[cfg(feature = "sync")]
+    pub(crate) remote_tasks: (
+        flume::Sender<crate::handle::RemoteTask>,
+        flume::Receiver<crate::handle::RemoteTask>,
+    ),
+
     /// Owned task set and local run queue
     pub(crate) tasks: TaskQueue,
+    /// Live tasks, for task dumps
+    #[cfg(feature = "task-dump")]
+    pub(crate) owned: crate::task::OwnedTasks<LocalScheduler>,
+    /// Number of spawned tasks not finished yet
+    pub(crate) alive: Rc<Cell<usize>>,
+    /// Shutdown state shared with tokens
+    pub(crate) shutdown: Rc<ShutdownState>,
+    /// Metrics counters
+    #[cfg(feature = "metrics")]
+    pub(crate) counters: crate::metrics::Counters,
     /// Time Handle
     pub(crate) time_handle: Option<TimeHandle>,
+    /// Lifecycle hooks
+    pub(crate) hooks: Hooks,
 }
 
 impl Default for Context {
@@ -46,6 +75,27 @@ impl Default for Context {
 }
 
 impl Context {
+    // Metrics owned by the context, the driver ones are filled by the caller.
+    #[cfg(feature = "metrics")]
+    pub(crate) fn metrics(&self) -> crate::metrics::RuntimeMetrics {
+        let mut metrics = crate::metrics::RuntimeMetrics {
+            task_queue_depth: self.tasks.len(),
+            queues: Priority::ALL
+                .iter()
+                .map(|&priority| crate::metrics::QueueMetrics {
+                    priority,
+                    depth: self.tasks.len_of(priority),
+                    polls: self.tasks.popped(priority),
+                })
+                .collect(),
+            timers: self.time_handle.as_ref().map_or(0, |h| h.timers()),
+            ..Default::default()
+        };
+        self.counters.fill(&mut metrics);
+        metrics.tasks_completed = metrics.tasks_spawned - self.alive.get() as u64;
+        metrics
+    }
+
     pub(crate) fn new() -> Self {
         #[cfg(feature = "sync")]
         let thread_id = crate::builder::BUILD_THREAD_ID.with(|id| *id);
@@ -57,8 +107,17 @@ impl Context {
             unpark_cache: std::cell::RefCell::new(fxhash::FxHashMap::default()),
             #[cfg(feature = "sync")]
             waker_sender_cache: std::cell::RefCell::new(fxhash::FxHashMap::default()),
+            #[cfg(feature = "sync")]
+            remote_tasks: flume::unbounded(),
             tasks: TaskQueue::default(),
+            #[cfg(feature = "task-dump")]
+            owned: crate::task::OwnedTasks::new(),
+            alive: Rc::new(Cell::new(0)),
+            shutdown: Rc::new(ShutdownState::default()),
+            #[cfg(feature = "metrics")]
+            counters: Default::default(),
             time_handle: None,
+            hooks: Hooks::default(),
         }
     }
 
@@ -100,6 +159,157 @@ impl Context {
 
         debug_assert!(false, "sender has not been registered");
     }
+
+    #[cfg(feature = "sync")]
+    pub(crate) fn handle(&self) -> crate::RuntimeHandle {
+        crate::RuntimeHandle::new(self.thread_id, self.remote_tasks.0.clone())
+    }
+
+    // Spawn the tasks sent by RuntimeHandles.
+    #[cfg(feature = "sync")]
+    fn spawn_remote_tasks(&self) {
+        while let Ok(task) = self.remote_tasks.1.try_recv() {
+            task();
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct ShutdownState {
+    requested: Cell<bool>,
+    // Waker of each token waiting, by key
+    wakers: RefCell<fxhash::FxHashMap<u64, Waker>>,
+    next_key: Cell<u64>,
+}
+
+impl ShutdownState {
+    fn request(&self) {
+        self.requested.set(true);
+        for (_, waker) in self.wakers.take() {
+            waker.wake();
+        }
+    }
+
+    fn is_requested(&self) -> bool {
+        self.requested.get()
+    }
+}
+
+/// A token which completes when the runtime starts shutting down.
+///
+/// Get one with [`shutdown_token`], and await it to stop a task gracefully
+/// when [`Runtime::shutdown_timeout`] is called.
+pub struct ShutdownToken {
+    state: Rc<ShutdownState>,
+    // Key of the waker registered by the token
+    key: Option<u64>,
+}
+
+impl Clone for ShutdownToken {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+            key: None,
+        }
+    }
+}
+
+impl ShutdownToken {
+    /// Returns true if the runtime is shutting down.
+    pub fn is_shutdown(&self) -> bool {
+        self.state.is_requested()
+    }
+}
+
+impl Future for ShutdownToken {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
+        let ShutdownToken { state, key } = self.get_mut();
+        if state.is_requested() {
+            return Poll::Ready(());
+        }
+        // Keep a single waker per token, the last one.
+        let key = *key.get_or_insert_with(|| {
+            let key = state.next_key.get();
+            state.next_key.set(key + 1);
+            key
+        });
+        match state.wakers.borrow_mut().entry(key) {
+            Entry::Occupied(mut entry) => {
+                if !entry.get().will_wake(cx.waker()) {
+                    entry.insert(cx.waker().clone());
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(cx.waker().clone());
+            }
+        }
+        Poll::Pending
+    }
+}
+
+impl Drop for ShutdownToken {
+    fn drop(&mut self) {
+        if let Some(key) = self.key {
+            self.state.wakers.borrow_mut().remove(&key);
+        }
+    }
+}
+
+impl std::fmt::Debug for ShutdownToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ShutdownToken")
+            .field("shutdown", &self.is_shutdown())
+            .finish()
+    }
+}
+
+/// Returns a [`ShutdownToken`] of the current runtime.
+///
+/// # Panics
+///
+/// Panics if called outside a runtime.
+///
+/// # Examples
+///
+/// ```no_run
+/// let mut rt = monoio::RuntimeBuilder::<monoio::FusionDriver>::new()
+///     .enable_timer()
+///     .build()
+///     .unwrap();
+/// rt.block_on(async {
+///     monoio::spawn(async {
+///         let token = monoio::shutdown_token();
+///         monoio::select! {
+///             _ = token => println!("shutting down"),
+///             _ = monoio::time::sleep(std::time::Duration::from_secs(60)) => {}
+///         }
+///     });
+/// });
+/// rt.shutdown_timeout(std::time::Duration::from_secs(1));
+/// ```
+pub fn shutdown_token() -> ShutdownToken {
+    CURRENT.with(|ctx| ShutdownToken {
+        state: ctx.shutdown.clone(),
+        key: None,
+    })
+}
+
+// Counts a spawned task as alive until its future is dropped.
+struct AliveGuard(Rc<Cell<usize>>);
+
+impl AliveGuard {
+    fn new(alive: &Rc<Cell<usize>>) -> Self {
+        alive.set(alive.get() + 1);
+        Self(alive.clone())
+    }
+}
+
+impl Drop for AliveGuard {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() - 1);
+    }
 }
 
 /// Monoio runtime
@@ -109,6 +319,26 @@ pub struct Runtime<D> {
 }
 
 impl<D> Runtime<D> {
+    /// Returns a handle to spawn tasks onto this runtime from other threads.
+    #[cfg(feature = "sync")]
+    pub fn handle(&self) -> crate::RuntimeHandle {
+        self.context.handle()
+    }
+
+    /// Returns the metrics of this runtime.
+    ///
+    /// Use [`metrics::current`](crate::metrics::current) inside the runtime.
+    #[cfg(feature = "metrics")]
+    pub fn metrics(&self) -> crate::metrics::RuntimeMetrics
+    where
+        D: Driver,
+    {
+        let mut metrics = self.context.metrics();
+        self.driver
+            .with(|| crate::driver::CURRENT.with(|inner| inner.fill_metrics(&mut metrics)));
+        metrics
+    }
+
     /// Block on
     pub fn block_on<F>(&mut self, future: F) -> F::Output
     where
@@ -134,10 +364,15 @@ impl<D> Runtime<D> {
                 set_poll();
                 loop {
                     loop {
+                        #[cfg(feature = "sync")]
+                        self.context.spawn_remote_tasks();
+
                         // Consume all tasks(with max round to prevent io starvation)
                         let mut max_round = self.context.tasks.len() * 2;
                         while let Some(t) = self.context.tasks.pop() {
-                            t.run();
+                            #[cfg(feature = "metrics")]
+                            crate::metrics::Counters::incr(&self.context.counters.polls);
+                            coop::budget(|| t.run());
                             if max_round == 0 {
                                 // maybe there's a looping task
                                 break;
@@ -148,8 +383,12 @@ impl<D> Runtime<D> {
 
                         // Check main future
                         if should_poll() {
+                            #[cfg(feature = "metrics")]
+                            crate::metrics::Counters::incr(&self.context.counters.polls);
                             // check if ready
-                            if let std::task::Poll::Ready(t) = join.as_mut().poll(cx) {
+                            if let std::task::Poll::Ready(t) =
+                                coop::budget(|| join.as_mut().poll(cx))
+                            {
                                 return t;
                             }
                         }
@@ -164,18 +403,127 @@ impl<D> Runtime<D> {
                         let _ = self.driver.submit();
                     }
 
-                    // Wait and Process CQ(the error is ignored for not debug mode)
+                    Hooks::call(&self.context.hooks.before_park);
+                    #[cfg(feature = "tracing")]
+                    tracing::trace!(target: "monoio::runtime", "park");
+                    #[cfg(feature = "metrics")]
+                    let park_begin = Instant::now();
+
+                    // Wait and Process CQ(the error is ignored for not debug mode).
+                    // The main future woke itself when out of budget, so only
+                    // process the completions without waiting.
+                    let res = if should_poll() {
+                        self.driver.park_timeout(Duration::ZERO)
+                    } else {
+                        self.driver.park()
+                    };
                     #[cfg(not(all(debug_assertions, feature = "debug")))]
-                    let _ = self.driver.park();
+                    let _ = res;
 
                     #[cfg(all(debug_assertions, feature = "debug"))]
-                    if let Err(e) = self.driver.park() {
+                    if let Err(e) = res {
                         tracing!("park error: {:?}", e);
                     }
+
+                    #[cfg(feature = "metrics")]
+                    {
+                        let counters = &self.context.counters;
+                        crate::metrics::Counters::incr(&counters.parks);
+                        counters
+                            .park_duration
+                            .set(counters.park_duration.get() + park_begin.elapsed());
+                    }
+
+                    #[cfg(feature = "tracing")]
+                    tracing::trace!(
+                        target: "monoio::runtime",
+                        tasks = self.context.tasks.len(),
+                        "unpark"
+                    );
+                    Hooks::call(&self.context.hooks.after_unpark);
                 }
             })
         })
     }
+
+    // Time given to the canceled ops to complete once the tasks are done.
+    const CANCEL_GRACE: Duration = Duration::from_millis(100);
+
+    /// Shutdown the runtime gracefully.
+    ///
+    /// [`ShutdownToken`]s complete and new spawned tasks are cancelled right
+    /// away. Spawned tasks are then run until all of them finish or `timeout`
+    /// elapses. Remaining in-flight ops are canceled, and briefly waited for
+    /// so that the kernel does not write to their buffers once freed. The
+    /// buffers of the ops which still have not completed are leaked.
+    /// Remaining tasks are dropped with the runtime.
+    pub fn shutdown_timeout(self, timeout: Duration)
+    where
+        D: Driver,
+    {
+        assert!(
+            !CURRENT.is_set(),
+            "Can not shutdown a runtime inside a runtime"
+        );
+
+        let deadline = Instant::now() + timeout;
+        self.context.shutdown.request();
+
+        self.driver.with(|| {
+            CURRENT.set(&self.context, || {
+                loop {
+                    let mut max_round = self.context.tasks.len() * 2;
+                    while let Some(t) = self.context.tasks.pop() {
+                        coop::budget(|| t.run());
+                        if max_round == 0 {
+                            break;
+                        } else {
+                            max_round -= 1;
+                        }
+                    }
+
+                    if self.context.alive.get() == 0 {
+                        break;
+                    }
+                    let now = Instant::now();
+                    if now >= deadline {
+                        break;
+                    }
+                    if self.context.tasks.is_empty() {
+                        Hooks::call(&self.context.hooks.before_park);
+                        let _ = self.driver.park_timeout(deadline - now);
+                        Hooks::call(&self.context.hooks.after_unpark);
+                    } else {
+                        let _ = self.driver.submit();
+                    }
+                }
+
+                crate::driver::CURRENT.with(|inner| {
+                    if inner.cancel_all() == 0 {
+                        return;
+                    }
+                    // Some ops ignore the cancellation, e.g. blocking file
+                    // reads, so stop waiting and leak their buffers.
+                    let deadline = deadline.max(Instant::now()) + Self::CANCEL_GRACE;
+                    while inner.in_flight() != 0 {
+                        let now = Instant::now();
+                        if now >= deadline || self.driver.park_timeout(deadline - now).is_err() {
+                            inner.leak_in_flight();
+                            break;
+                        }
+                    }
+                });
+            })
+        })
+    }
+}
+
+impl<D: std::os::unix::prelude::AsRawFd> std::os::unix::prelude::AsRawFd for Runtime<D> {
+    /// Returns the fd of the io_uring of the runtime, e.g. to share its
+    /// workers with [`UringConfig::attach_wq`](crate::UringConfig::attach_wq).
+    fn as_raw_fd(&self) -> std::os::unix::prelude::RawFd {
+        self.driver.as_raw_fd()
+    }
 }
 
 /// Fusion Runtime is a wrapper of io_uring driver or legacy driver based runtime.
@@ -211,6 +559,32 @@ where
             FusionRuntime::Legacy(inner) => inner.block_on(future),
         }
     }
+
+    /// Shutdown the runtime gracefully, see [`Runtime::shutdown_timeout`].
+    pub fn shutdown_timeout(self, timeout: Duration) {
+        match self {
+            FusionRuntime::Uring(inner) => inner.shutdown_timeout(timeout),
+            FusionRuntime::Legacy(inner) => inner.shutdown_timeout(timeout),
+        }
+    }
+
+    /// Returns a handle to spawn tasks onto this runtime from other threads.
+    #[cfg(feature = "sync")]
+    pub fn handle(&self) -> crate::RuntimeHandle {
+        match self {
+            FusionRuntime::Uring(inner) => inner.handle(),
+            FusionRuntime::Legacy(inner) => inner.handle(),
+        }
+    }
+
+    /// Returns the metrics of this runtime.
+    #[cfg(feature = "metrics")]
+    pub fn metrics(&self) -> crate::metrics::RuntimeMetrics {
+        match self {
+            FusionRuntime::Uring(inner) => inner.metrics(),
+            FusionRuntime::Legacy(inner) => inner.metrics(),
+        }
+    }
 }
 
 #[cfg(all(feature = "legacy", not(all(target_os = "linux", feature = "iouring"))))]
@@ -227,6 +601,29 @@ where
             FusionRuntime::Legacy(inner) => inner.block_on(future),
         }
     }
+
+    /// Shutdown the runtime gracefully, see [`Runtime::shutdown_timeout`].
+    pub fn shutdown_timeout(self, timeout: Duration) {
+        match self {
+            FusionRuntime::Legacy(inner) => inner.shutdown_timeout(timeout),
+        }
+    }
+
+    /// Returns a handle to spawn tasks onto this runtime from other threads.
+    #[cfg(feature = "sync")]
+    pub fn handle(&self) -> crate::RuntimeHandle {
+        match self {
+            FusionRuntime::Legacy(inner) => inner.handle(),
+        }
+    }
+
+    /// Returns the metrics of this runtime.
+    #[cfg(feature = "metrics")]
+    pub fn metrics(&self) -> crate::metrics::RuntimeMetrics {
+        match self {
+            FusionRuntime::Legacy(inner) => inner.metrics(),
+        }
+    }
 }
 
 #[cfg(all(not(feature = "legacy"), all(target_os = "linux", feature = "iouring")))]
@@ -243,6 +640,29 @@ where
             FusionRuntime::Uring(inner) => inner.block_on(future),
         }
     }
+
+    /// Shutdown the runtime gracefully, see [`Runtime::shutdown_timeout`].
+    pub fn shutdown_timeout(self, timeout: Duration) {
+        match self {
+            FusionRuntime::Uring(inner) => inner.shutdown_timeout(timeout),
+        }
+    }
+
+    /// Returns a handle to spawn tasks onto this runtime from other threads.
+    #[cfg(feature = "sync")]
+    pub fn handle(&self) -> crate::RuntimeHandle {
+        match self {
+            FusionRuntime::Uring(inner) => inner.handle(),
+        }
+    }
+
+    /// Returns the metrics of this runtime.
+    #[cfg(feature = "metrics")]
+    pub fn metrics(&self) -> crate::metrics::RuntimeMetrics {
+        match self {
+            FusionRuntime::Uring(inner) => inner.metrics(),
+        }
+    }
 }
 
 // L -> Fusion<L, R>
@@ -318,10 +738,12 @@ impl From<Runtime<TimeDriver<IoUringDriver>>> for FusionRuntime<TimeDriver<IoUri
 /// Spawning a task enables the task to execute concurrently to other tasks.
 /// There is no guarantee that a spawned task will execute to completion. When a
 /// runtime is shutdown, all outstanding tasks are dropped, regardless of the
-/// lifecycle of that task.
+/// lifecycle of that task. Tasks spawned after [`Runtime::shutdown_timeout`] is
+/// called are cancelled right away, see [`JoinHandle::is_cancelled`].
 ///
 ///
 /// [`JoinHandle`]: monoio::task::JoinHandle
+/// [`JoinHandle::is_cancelled`]: monoio::task::JoinHandle::is_cancelled
 ///
 /// # Examples
 ///
@@ -339,24 +761,112 @@ impl From<Runtime<TimeDriver<IoUringDriver>>> for FusionRuntime<TimeDriver<IoUri
 ///     handle.await;
 /// }
 /// ```
+#[track_caller]
 pub fn spawn<T>(future: T) -> JoinHandle<T::Output>
 where
     T: Future + 'static,
     T::Output: 'static,
 {
-    #[cfg(not(feature = "sync"))]
-    let (task, join) = new_task(future, LocalScheduler);
-    #[cfg(feature = "sync")]
-    let (task, join) = new_task(
-        crate::utils::thread_id::get_current_thread_id(),
-        future,
-        LocalScheduler,
-    );
+    spawn_with_priority(Priority::Normal, future)
+}
+
+/// Spawns a new asynchronous task with the given [`Priority`], returning a
+/// [`JoinHandle`] for it. See [`spawn`] for details.
+///
+/// [`JoinHandle`]: monoio::task::JoinHandle
+///
+/// # Examples
+///
+/// ```no_run
+/// use monoio::task::Priority;
+///
+/// #[monoio::main]
+/// async fn main() {
+///     // Health checks run before the bulk transfers queued on the runtime.
+///     monoio::spawn_with_priority(Priority::High, async {
+///         println!("healthy");
+///     })
+///     .await;
+/// }
+/// ```
+#[track_caller]
+pub fn spawn_with_priority<T>(priority: Priority, future: T) -> JoinHandle<T::Output>
+where
+    T: Future + 'static,
+    T::Output: 'static,
+{
+    spawn_inner(future, priority, None)
+}
+
+#[track_caller]
+pub(crate) fn spawn_inner<T>(
+    future: T,
+    priority: Priority,
+    name: Option<&str>,
+) -> JoinHandle<T::Output>
+where
+    T: Future + 'static,
+    T::Output: 'static,
+{
+    let (id, location) = (crate::task::next_id(), std::panic::Location::caller());
+    #[cfg(feature = "tracing")]
+    let future = tracing::Instrument::instrument(future, task_span(id, priority, name, location));
 
     CURRENT.with(|ctx| {
-        ctx.tasks.push(task);
-    });
-    join
+        // New tasks are not accepted once shutting down.
+        if ctx.shutdown.is_requested() {
+            return JoinHandle::cancelled();
+        }
+
+        let alive = AliveGuard::new(&ctx.alive);
+        let future = async move {
+            let _alive = alive;
+            future.await
+        };
+
+        #[cfg(not(feature = "sync"))]
+        let (task, join) = new_task(future, LocalScheduler::new(priority));
+        #[cfg(feature = "sync")]
+        let (task, join) = new_task(
+            crate::utils::thread_id::get_current_thread_id(),
+            future,
+            LocalScheduler::new(priority),
+        );
+
+        if let Some(hook) = &ctx.hooks.on_task_spawn {
+            hook(&TaskMeta {
+                id,
+                name,
+                location,
+                priority,
+            });
+        }
+        #[cfg(feature = "metrics")]
+        crate::metrics::Counters::incr(&ctx.counters.tasks_spawned);
+        #[cfg(feature = "task-dump")]
+        ctx.owned.insert(&task, id, name, location);
+        ctx.tasks.push(priority, task);
+        join
+    })
+}
+
+// The span of a task, entered each time it is polled.
+#[cfg(feature = "tracing")]
+fn task_span(
+    id: u64,
+    priority: Priority,
+    name: Option<&str>,
+    location: &std::panic::Location<'_>,
+) -> tracing::Span {
+    tracing::trace_span!(
+        target: "monoio::task",
+        "task",
+        task.id = id,
+        task.name = name,
+        task.priority = ?priority,
+        loc.file = location.file(),
+        loc.line = location.line(),
+    )
 }
 
 #[cfg(feature = "sync")]
@@ -368,11 +878,11 @@ where
     let (task, join) = new_task_holding(
         crate::utils::thread_id::get_current_thread_id(),
         future,
-        LocalScheduler,
+        LocalScheduler::default(),
     );
 
     CURRENT.with(|ctx| {
-        ctx.tasks.push(task);
+        ctx.tasks.push(Priority::Normal, task);
     });
     join
 }
@@ -1,21 +1,81 @@
 use crate::task::{Schedule, Task};
-use std::{cell::UnsafeCell, collections::VecDeque, marker::PhantomData};
+use std::{
+    cell::{Cell, UnsafeCell},
+    collections::VecDeque,
+    marker::PhantomData,
+};
 
-pub(crate) struct LocalScheduler;
+/// Priority of a task, which chooses the run queue it is scheduled on.
+///
+/// Tasks of higher priorities run first, but only up to a number of tasks per
+/// round: 8 high, 4 normal and 1 low. So tasks of lower priorities are never
+/// starved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub enum Priority {
+    /// For latency sensitive tasks, e.g. control-plane or health-check ones.
+    High,
+    /// The priority of tasks spawned with [`spawn`](crate::spawn).
+    #[default]
+    Normal,
+    /// For throughput oriented tasks, e.g. bulk transfers.
+    Low,
+}
+
+impl Priority {
+    pub(crate) const ALL: [Priority; 3] = [Priority::High, Priority::Normal, Priority::Low];
+
+    // Number of tasks run from the queue in a round.
+    const fn weight(self) -> usize {
+        match self {
+            Priority::High => 8,
+            Priority::Normal => 4,
+            Priority::Low => 1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+pub(crate) struct LocalScheduler {
+    priority: Priority,
+}
+
+impl LocalScheduler {
+    pub(crate) fn new(priority: Priority) -> Self {
+        Self { priority }
+    }
+}
 
 impl Schedule for LocalScheduler {
     fn schedule(&self, task: Task<Self>) {
-        crate::runtime::CURRENT.with(|cx| cx.tasks.push(task));
+        crate::runtime::CURRENT.with(|cx| cx.tasks.push(self.priority, task));
     }
 
     fn yield_now(&self, task: Task<Self>) {
-        crate::runtime::CURRENT.with(|cx| cx.tasks.push_front(task));
+        // A task yielding on purpose goes after the others.
+        if crate::task::coop::take_yielded() {
+            crate::runtime::CURRENT.with(|cx| cx.tasks.push(self.priority, task));
+        } else {
+            crate::runtime::CURRENT.with(|cx| cx.tasks.push_front(self.priority, task));
+        }
+    }
+
+    #[cfg(feature = "task-dump")]
+    fn release(&self, header: std::ptr::NonNull<crate::task::Header>) -> Option<Task<Self>> {
+        crate::runtime::CURRENT.with(|cx| cx.owned.remove(header))
     }
 }
 
 pub(crate) struct TaskQueue {
-    // Local queue.
-    queue: UnsafeCell<VecDeque<Task<LocalScheduler>>>,
+    // Local queues, indexed by priority.
+    queues: UnsafeCell<[VecDeque<Task<LocalScheduler>>; 3]>,
+    // Number of tasks each queue can still run in the round.
+    credits: Cell<[usize; 3]>,
+    // Number of tasks popped from each queue.
+    #[cfg(feature = "metrics")]
+    popped: [Cell<u64>; 3],
+    // Picks the tasks in a random order, set by the simulation driver.
+    #[cfg(feature = "sim")]
+    shuffle: Option<crate::utils::rand::FastRand>,
     // Make sure the type is `!Send` and `!Sync`.
     _marker: PhantomData<*const ()>,
 }
@@ -33,32 +93,79 @@ impl TaskQueue {
     }
     pub(crate) fn new_with_capacity(capacity: usize) -> Self {
         Self {
-            queue: UnsafeCell::new(VecDeque::with_capacity(capacity)),
+            // Most tasks have the normal priority.
+            queues: UnsafeCell::new([
+                VecDeque::new(),
+                VecDeque::with_capacity(capacity),
+                VecDeque::new(),
+            ]),
+            credits: Cell::new(Priority::ALL.map(Priority::weight)),
+            #[cfg(feature = "metrics")]
+            popped: Default::default(),
+            #[cfg(feature = "sim")]
+            shuffle: None,
             _marker: PhantomData,
         }
     }
 
+    /// Pops the tasks of each queue in an order chosen by a rng seeded with
+    /// `seed` instead of in FIFO order.
+    #[cfg(feature = "sim")]
+    pub(crate) fn shuffle(&mut self, seed: u64) {
+        self.shuffle = Some(crate::utils::rand::FastRand::new(seed));
+    }
+
     pub(crate) fn len(&self) -> usize {
-        unsafe { (*self.queue.get()).len() }
+        unsafe { (*self.queues.get()).iter().map(VecDeque::len).sum() }
+    }
+
+    #[cfg(feature = "metrics")]
+    pub(crate) fn len_of(&self, priority: Priority) -> usize {
+        unsafe { (*self.queues.get())[priority as usize].len() }
     }
 
     pub(crate) fn is_empty(&self) -> bool {
         self.len() == 0
     }
 
-    pub(crate) fn push(&self, runnable: Task<LocalScheduler>) {
+    pub(crate) fn push(&self, priority: Priority, runnable: Task<LocalScheduler>) {
         unsafe {
-            (*self.queue.get()).push_back(runnable);
+            (*self.queues.get())[priority as usize].push_back(runnable);
         }
     }
 
-    pub(crate) fn push_front(&self, runnable: Task<LocalScheduler>) {
+    pub(crate) fn push_front(&self, priority: Priority, runnable: Task<LocalScheduler>) {
         unsafe {
-            (*self.queue.get()).push_front(runnable);
+            (*self.queues.get())[priority as usize].push_front(runnable);
         }
     }
 
     pub(crate) fn pop(&self) -> Option<Task<LocalScheduler>> {
-        unsafe { (*self.queue.get()).pop_front() }
+        let queues = unsafe { &mut *self.queues.get() };
+        let mut credits = self.credits.get();
+        // Pop from the highest priority queue with credit left, and start a new
+        // round once there is none.
+        let index = match (0..queues.len()).find(|&i| credits[i] != 0 && !queues[i].is_empty()) {
+            Some(index) => index,
+            None => {
+                credits = Priority::ALL.map(Priority::weight);
+                (0..queues.len()).find(|&i| !queues[i].is_empty())?
+            }
+        };
+        credits[index] -= 1;
+        self.credits.set(credits);
+        #[cfg(feature = "metrics")]
+        crate::metrics::Counters::incr(&self.popped[index]);
+        #[cfg(feature = "sim")]
+        if let Some(rng) = &self.shuffle {
+            let i = rng.fastrand_n(queues[index].len() as u32) as usize;
+            return queues[index].swap_remove_front(i);
+        }
+        queues[index].pop_front()
+    }
+
+    #[cfg(feature = "metrics")]
+    pub(crate) fn popped(&self, priority: Priority) -> u64 {
+        self.popped[priority as usize].get()
     }
 }
@@ -0,0 +1,375 @@
+//! Asynchronous signal handling.
+//!
+//! Every [`Signal`] listener gets every delivered signal of its kind, no
+//! matter which runtime or thread it lives on, so each thread-per-core runtime
+//! can listen to the same signal.
+//!
+//! A `signalfd` only receives signals which are blocked in every thread of the
+//! process, which a library can not guarantee once threads are running.
+//! Instead, a signal handler is installed on first use, which writes to a pipe
+//! per listener. The pipe is then read through the driver, like a `signalfd`
+//! would be. Once a handler is installed for a signal, its default action is
+//! never restored. A handler installed before, e.g. by the application, is
+//! still called.
+
+use crate::{
+    io::{stream::Stream, AsyncReadRent, Receiver},
+    syscall,
+};
+use std::{
+    future::Future,
+    io,
+    os::unix::prelude::RawFd,
+    sync::{
+        atomic::{AtomicBool, AtomicI32, AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+/// Kind of a signal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SignalKind(libc::c_int);
+
+impl SignalKind {
+    /// Create a kind from a raw signal number.
+    pub const fn from_raw(signum: libc::c_int) -> Self {
+        Self(signum)
+    }
+
+    /// Returns the raw signal number.
+    pub const fn as_raw(&self) -> libc::c_int {
+        self.0
+    }
+
+    /// `SIGALRM`
+    pub const fn alarm() -> Self {
+        Self(libc::SIGALRM)
+    }
+
+    /// `SIGCHLD`
+    pub const fn child() -> Self {
+        Self(libc::SIGCHLD)
+    }
+
+    /// `SIGHUP`
+    pub const fn hangup() -> Self {
+        Self(libc::SIGHUP)
+    }
+
+    /// `SIGINT`, sent by ctrl-c.
+    pub const fn interrupt() -> Self {
+        Self(libc::SIGINT)
+    }
+
+    /// `SIGPIPE`
+    pub const fn pipe() -> Self {
+        Self(libc::SIGPIPE)
+    }
+
+    /// `SIGQUIT`
+    pub const fn quit() -> Self {
+        Self(libc::SIGQUIT)
+    }
+
+    /// `SIGTERM`
+    pub const fn terminate() -> Self {
+        Self(libc::SIGTERM)
+    }
+
+    /// `SIGUSR1`
+    pub const fn user_defined1() -> Self {
+        Self(libc::SIGUSR1)
+    }
+
+    /// `SIGUSR2`
+    pub const fn user_defined2() -> Self {
+        Self(libc::SIGUSR2)
+    }
+
+    /// `SIGWINCH`
+    pub const fn window_change() -> Self {
+        Self(libc::SIGWINCH)
+    }
+}
+
+/// Wait for ctrl-c(`SIGINT`).
+///
+/// # Examples
+///
+/// ```no_run
+/// #[monoio::main]
+/// async fn main() -> std::io::Result<()> {
+///     monoio::signal::ctrl_c().await?;
+///     println!("ctrl-c received!");
+///     Ok(())
+/// }
+/// ```
+pub async fn ctrl_c() -> io::Result<()> {
+    let mut signal = signal(SignalKind::interrupt())?;
+    signal.recv().await;
+    Ok(())
+}
+
+/// Listen to signals of the given kind.
+///
+/// Signals delivered between two calls of [`Signal::recv`] are coalesced.
+///
+/// # Examples
+///
+/// ```no_run
+/// use monoio::signal::{signal, SignalKind};
+///
+/// #[monoio::main]
+/// async fn main() -> std::io::Result<()> {
+///     let mut hangup = signal(SignalKind::hangup())?;
+///     while hangup.recv().await.is_some() {
+///         println!("reloading config");
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn signal(kind: SignalKind) -> io::Result<Signal> {
+    let signum = kind.as_raw();
+    if signum <= 0 || signum >= MAX_SIGNUM as libc::c_int || FORBIDDEN.contains(&signum) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "signal can not be listened to",
+        ));
+    }
+    install_handler(signum)?;
+
+    let (read_fd, write_fd) = new_pipe()?;
+    // Safety: the fd has just been created.
+    let receiver = match unsafe { Receiver::from_pipe_fd(read_fd) } {
+        Ok(receiver) => receiver,
+        Err(e) => {
+            let _ = syscall!(close(write_fd));
+            return Err(e);
+        }
+    };
+    let slot = match register(signum, write_fd) {
+        Some(slot) => slot,
+        None => {
+            let _ = syscall!(close(write_fd));
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "too many signal listeners",
+            ));
+        }
+    };
+
+    Ok(Signal {
+        kind,
+        receiver,
+        slot,
+        buf: Some(vec![0; 64]),
+    })
+}
+
+/// A listener of signals of one kind, created by [`signal`].
+#[derive(Debug)]
+pub struct Signal {
+    kind: SignalKind,
+    receiver: Receiver,
+    slot: usize,
+    buf: Option<Vec<u8>>,
+}
+
+impl Signal {
+    /// Returns the kind of signals listened to.
+    pub fn kind(&self) -> SignalKind {
+        self.kind
+    }
+
+    /// Wait for the next signal.
+    ///
+    /// Returns `None` if no more signals can be received.
+    pub async fn recv(&mut self) -> Option<()> {
+        loop {
+            let buf = self.buf.take()?;
+            let (res, buf) = self.receiver.read(buf).await;
+            self.buf = Some(buf);
+            match res {
+                Ok(0) => return None,
+                Ok(_) => return Some(()),
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(_) => return None,
+            }
+        }
+    }
+}
+
+impl Stream for Signal {
+    type Item = ();
+
+    type NextFuture<'a> = impl Future<Output = Option<Self::Item>>;
+
+    fn next(&mut self) -> Self::NextFuture<'_> {
+        self.recv()
+    }
+}
+
+impl Drop for Signal {
+    fn drop(&mut self) {
+        unregister(self.slot);
+    }
+}
+
+const MAX_SIGNUM: usize = 65;
+const MAX_LISTENERS: usize = 256;
+
+// Signals whose handling can not be changed, or must not be.
+const FORBIDDEN: [libc::c_int; 6] = [
+    libc::SIGKILL,
+    libc::SIGSTOP,
+    libc::SIGILL,
+    libc::SIGFPE,
+    libc::SIGSEGV,
+    libc::SIGBUS,
+];
+
+#[allow(clippy::declare_interior_mutable_const)]
+const UNSET: AtomicI32 = AtomicI32::new(-1);
+#[allow(clippy::declare_interior_mutable_const)]
+const FREE: AtomicBool = AtomicBool::new(false);
+
+// A listener slot is claimed, then its signal number is set, then its fd.
+// The handler only reads slots whose fd is set.
+static CLAIMED: [AtomicBool; MAX_LISTENERS] = [FREE; MAX_LISTENERS];
+static SIGNUMS: [AtomicI32; MAX_LISTENERS] = [UNSET; MAX_LISTENERS];
+static FDS: [AtomicI32; MAX_LISTENERS] = [UNSET; MAX_LISTENERS];
+// Number of handlers running, so a fd is not closed while being written to.
+static HANDLERS_RUNNING: AtomicUsize = AtomicUsize::new(0);
+
+static INSTALLED: Mutex<[bool; MAX_SIGNUM]> = Mutex::new([false; MAX_SIGNUM]);
+
+#[allow(clippy::declare_interior_mutable_const)]
+const NO_HANDLER: AtomicUsize = AtomicUsize::new(0);
+
+// Handler installed before ours for each signal, e.g. by the application,
+// which ours calls. 0 if there was none.
+static PREVIOUS: [AtomicUsize; MAX_SIGNUM] = [NO_HANDLER; MAX_SIGNUM];
+// Whether the previous handler takes a siginfo.
+static PREVIOUS_SIGINFO: [AtomicBool; MAX_SIGNUM] = [FREE; MAX_SIGNUM];
+
+extern "C" fn handler(signum: libc::c_int, info: *mut libc::siginfo_t, context: *mut libc::c_void) {
+    let errno = io::Error::last_os_error().raw_os_error();
+    HANDLERS_RUNNING.fetch_add(1, Ordering::SeqCst);
+    for (fd, slot_signum) in FDS.iter().zip(SIGNUMS.iter()) {
+        let fd = fd.load(Ordering::SeqCst);
+        if fd >= 0 && slot_signum.load(Ordering::SeqCst) == signum {
+            // The pipe being full is fine, the listener is woken anyway.
+            unsafe { libc::write(fd, &1u8 as *const u8 as *const libc::c_void, 1) };
+        }
+    }
+    HANDLERS_RUNNING.fetch_sub(1, Ordering::SeqCst);
+
+    let previous = PREVIOUS[signum as usize].load(Ordering::SeqCst);
+    if previous != 0 {
+        if PREVIOUS_SIGINFO[signum as usize].load(Ordering::SeqCst) {
+            let previous: extern "C" fn(libc::c_int, *mut libc::siginfo_t, *mut libc::c_void) =
+                unsafe { std::mem::transmute(previous) };
+            previous(signum, info, context);
+        } else {
+            let previous: extern "C" fn(libc::c_int) = unsafe { std::mem::transmute(previous) };
+            previous(signum);
+        }
+    }
+    if let Some(errno) = errno {
+        set_errno(errno);
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn set_errno(errno: libc::c_int) {
+    unsafe { *libc::__errno_location() = errno };
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd"))]
+fn set_errno(errno: libc::c_int) {
+    unsafe { *libc::__error() = errno };
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd"
+)))]
+fn set_errno(_errno: libc::c_int) {}
+
+fn install_handler(signum: libc::c_int) -> io::Result<()> {
+    let mut installed = INSTALLED.lock().unwrap();
+    if installed[signum as usize] {
+        return Ok(());
+    }
+    // Keep the handler already installed, to call it from ours.
+    let mut previous: libc::sigaction = unsafe { std::mem::zeroed() };
+    syscall!(sigaction(signum, std::ptr::null(), &mut previous))?;
+    if previous.sa_sigaction != libc::SIG_DFL && previous.sa_sigaction != libc::SIG_IGN {
+        PREVIOUS_SIGINFO[signum as usize]
+            .store(previous.sa_flags & libc::SA_SIGINFO != 0, Ordering::SeqCst);
+        PREVIOUS[signum as usize].store(previous.sa_sigaction, Ordering::SeqCst);
+    }
+
+    let mut action: libc::sigaction = unsafe { std::mem::zeroed() };
+    action.sa_sigaction = handler
+        as extern "C" fn(libc::c_int, *mut libc::siginfo_t, *mut libc::c_void)
+        as libc::sighandler_t;
+    action.sa_flags = libc::SA_RESTART | libc::SA_SIGINFO;
+    syscall!(sigemptyset(&mut action.sa_mask))?;
+    syscall!(sigaction(signum, &action, std::ptr::null_mut()))?;
+    installed[signum as usize] = true;
+    Ok(())
+}
+
+fn register(signum: libc::c_int, fd: RawFd) -> Option<usize> {
+    let slot = CLAIMED.iter().position(|claimed| {
+        claimed
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok()
+    })?;
+    SIGNUMS[slot].store(signum, Ordering::SeqCst);
+    FDS[slot].store(fd, Ordering::SeqCst);
+    Some(slot)
+}
+
+fn unregister(slot: usize) {
+    let fd = FDS[slot].swap(-1, Ordering::SeqCst);
+    // A handler which loaded the fd before the swap may still be writing.
+    while HANDLERS_RUNNING.load(Ordering::SeqCst) != 0 {
+        std::hint::spin_loop();
+    }
+    let _ = syscall!(close(fd));
+    SIGNUMS[slot].store(-1, Ordering::SeqCst);
+    CLAIMED[slot].store(false, Ordering::SeqCst);
+}
+
+// The write end is non-blocking, so the handler never blocks.
+fn new_pipe() -> io::Result<(RawFd, RawFd)> {
+    let mut fds = [0; 2];
+
+    #[cfg(target_os = "linux")]
+    syscall!(pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC))?;
+
+    // Darwin doesn't have pipe2.
+    #[cfg(not(target_os = "linux"))]
+    {
+        syscall!(pipe(fds.as_mut_ptr()))?;
+        for fd in fds {
+            if let Err(e) = syscall!(fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC)) {
+                let _ = syscall!(close(fds[0]));
+                let _ = syscall!(close(fds[1]));
+                return Err(e);
+            }
+        }
+    }
+
+    if let Err(e) = syscall!(fcntl(fds[1], libc::F_SETFL, libc::O_NONBLOCK)) {
+        let _ = syscall!(close(fds[0]));
+        let _ = syscall!(close(fds[1]));
+        return Err(e);
+    }
+    Ok((fds[0], fds[1]))
+}
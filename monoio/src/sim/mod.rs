@@ -0,0 +1,158 @@
+//! Deterministic simulation, to test distributed systems reproducibly.
+//!
+//! A runtime built with the [`SimDriver`] runs everything on a virtual clock
+//! and an in-memory network, and makes the random choices below with a rng
+//! seeded by [`SimConfig::seed`]. So a failing run can be replayed with its
+//! seed:
+//! - the tasks are polled in a random order,
+//! - the network delivers with a random latency, so the completions of
+//!   different sockets are reordered,
+//! - packets are dropped, which delays them by a retransmit timeout,
+//! - reads are short,
+//! - connections are reset, failing with `ECONNRESET`.
+//!
+//! Only the sockets of [`net`] and the timers of [`time`](crate::time) are
+//! simulated, the other ops fail with [`ErrorKind::Unsupported`]. The
+//! simulation runs on a single thread: without the `sync` feature, it panics
+//! once all the tasks wait and no event is pending, as nothing can wake them.
+//!
+//! [`ErrorKind::Unsupported`]: std::io::ErrorKind::Unsupported
+//!
+//! # Examples
+//!
+//! ```
+//! use monoio::{
+//!     io::{AsyncReadRent, AsyncWriteRentExt},
+//!     sim::{
+//!         net::{TcpListener, TcpStream},
+//!         SimConfig,
+//!     },
+//!     time::Duration,
+//!     RuntimeBuilder, SimDriver,
+//! };
+//!
+//! let config = SimConfig::new().seed(42).short_read_rate(0.5);
+//! let mut rt = RuntimeBuilder::<SimDriver>::new()
+//!     .with_sim_config(config)
+//!     .enable_timer()
+//!     .build()
+//!     .unwrap();
+//! rt.block_on(async {
+//!     let listener = TcpListener::bind("127.0.0.1:8080").unwrap();
+//!     monoio::spawn(async move {
+//!         let (mut stream, _) = listener.accept().await.unwrap();
+//!         stream.write_all(b"hello").await.0.unwrap();
+//!     });
+//!     let mut stream = TcpStream::connect("127.0.0.1:8080").await.unwrap();
+//!     let (res, buf) = stream.read(Vec::with_capacity(8)).await;
+//!     assert!(res.unwrap() > 0);
+//!     assert!(b"hello".starts_with(&buf));
+//!     // Sleeping takes no real time.
+//!     monoio::time::sleep(Duration::from_secs(3600)).await;
+//! });
+//! ```
+
+pub mod net;
+
+use std::time::Duration;
+
+pub use crate::driver::SimDriver;
+
+/// Seed and faults of a simulation, set with
+/// [`RuntimeBuilder::with_sim_config`](crate::RuntimeBuilder::with_sim_config).
+///
+/// By default the seed is 0, the latency is between 1 and 5 ms and no fault
+/// is injected.
+#[derive(Debug, Clone, Copy)]
+pub struct SimConfig {
+    seed: u64,
+    pub(crate) min_latency: Duration,
+    pub(crate) max_latency: Duration,
+    pub(crate) drop_rate: f64,
+    pub(crate) retransmit_timeout: Duration,
+    pub(crate) short_read_rate: f64,
+    pub(crate) reset_rate: f64,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SimConfig {
+    /// Create a default simulation config.
+    pub fn new() -> Self {
+        Self {
+            seed: 0,
+            min_latency: Duration::from_millis(1),
+            max_latency: Duration::from_millis(5),
+            drop_rate: 0.0,
+            retransmit_timeout: Duration::from_millis(200),
+            short_read_rate: 0.0,
+            reset_rate: 0.0,
+        }
+    }
+
+    /// Set the seed of the random choices of the simulation.
+    #[must_use]
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Set the bounds of the latency of the network.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min` is greater than `max`.
+    #[must_use]
+    pub fn latency(mut self, min: Duration, max: Duration) -> Self {
+        assert!(min <= max, "min latency is greater than max latency");
+        self.min_latency = min;
+        self.max_latency = max;
+        self
+    }
+
+    /// Set the probability a packet is dropped. It is still delivered in
+    /// order, once the retransmit timeout elapses.
+    #[must_use]
+    pub fn drop_rate(mut self, rate: f64) -> Self {
+        self.drop_rate = rate;
+        self
+    }
+
+    /// Set the delay a dropped packet is retransmitted after, 200 ms by
+    /// default.
+    #[must_use]
+    pub fn retransmit_timeout(mut self, timeout: Duration) -> Self {
+        self.retransmit_timeout = timeout;
+        self
+    }
+
+    /// Set the probability a read returns only part of the data available.
+    #[must_use]
+    pub fn short_read_rate(mut self, rate: f64) -> Self {
+        self.short_read_rate = rate;
+        self
+    }
+
+    /// Set the probability a write resets its connection. The reset reaches
+    /// both ends after the latency of the network.
+    #[must_use]
+    pub fn reset_rate(mut self, rate: f64) -> Self {
+        self.reset_rate = rate;
+        self
+    }
+
+    // Derives the seed of a rng of the simulation from the user one, with
+    // splitmix64.
+    pub(crate) fn seed_for(&self, stream: u64) -> u64 {
+        let mut z = self
+            .seed
+            .wrapping_add((stream + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
@@ -0,0 +1,625 @@
+//! In-memory TCP and Unix stream sockets of the simulation.
+//!
+//! They mirror the API of [`monoio::net`](crate::net), but are only usable
+//! inside a runtime built with the [`SimDriver`](crate::SimDriver). All the
+//! hosts share a single network, so any listening address can be reached.
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    fmt,
+    future::Future,
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs},
+    path::{Path, PathBuf},
+    rc::Rc,
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+
+use fxhash::FxHashMap;
+
+use crate::{
+    buf::{IoBuf, IoBufMut, IoVecBuf, IoVecBufMut, RawBuf},
+    driver::sim::{with_current, SimInner},
+    io::{AsyncReadRent, AsyncWriteRent},
+    BufResult,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Addr {
+    Inet(SocketAddr),
+    Unix(PathBuf),
+}
+
+/// Listeners of the simulated network, by address.
+pub(crate) struct Network {
+    listeners: FxHashMap<Addr, Rc<RefCell<Backlog>>>,
+    next_port: u16,
+}
+
+impl Network {
+    const EPHEMERAL_PORTS: std::ops::RangeInclusive<u16> = 49152..=65535;
+
+    pub(crate) fn new() -> Self {
+        Self {
+            listeners: FxHashMap::default(),
+            next_port: *Self::EPHEMERAL_PORTS.start(),
+        }
+    }
+
+    fn ephemeral_port(&mut self) -> u16 {
+        let port = self.next_port;
+        self.next_port = if port == *Self::EPHEMERAL_PORTS.end() {
+            *Self::EPHEMERAL_PORTS.start()
+        } else {
+            port + 1
+        };
+        port
+    }
+
+    fn bind(&mut self, addr: Addr) -> io::Result<(Addr, Rc<RefCell<Backlog>>)> {
+        let addr = match addr {
+            Addr::Inet(inet) if inet.port() == 0 => {
+                Addr::Inet(SocketAddr::new(inet.ip(), self.ephemeral_port()))
+            }
+            addr => addr,
+        };
+        if self.listeners.contains_key(&addr) {
+            return Err(io::ErrorKind::AddrInUse.into());
+        }
+        let backlog = Rc::new(RefCell::new(Backlog {
+            streams: VecDeque::new(),
+            waker: None,
+            closed: false,
+        }));
+        self.listeners.insert(addr.clone(), backlog.clone());
+        Ok((addr, backlog))
+    }
+
+    // Finds the listener of `addr`, which may listen on any ip.
+    fn lookup(&self, addr: &Addr) -> Option<Rc<RefCell<Backlog>>> {
+        if let Some(backlog) = self.listeners.get(addr) {
+            return Some(backlog.clone());
+        }
+        match addr {
+            Addr::Inet(inet) => {
+                let any = match inet.ip() {
+                    IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+                    IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+                };
+                self.listeners
+                    .get(&Addr::Inet(SocketAddr::new(any, inet.port())))
+                    .cloned()
+            }
+            Addr::Unix(_) => None,
+        }
+    }
+}
+
+// Streams connected to a listener and not accepted yet.
+struct Backlog {
+    streams: VecDeque<Endpoint>,
+    waker: Option<Waker>,
+    closed: bool,
+}
+
+struct Listener {
+    addr: Addr,
+    backlog: Rc<RefCell<Backlog>>,
+}
+
+impl Listener {
+    fn bind(addr: Addr) -> io::Result<Self> {
+        let (addr, backlog) = with_current(|sim| sim.net.bind(addr))??;
+        Ok(Self { addr, backlog })
+    }
+
+    async fn accept(&self) -> Endpoint {
+        std::future::poll_fn(|cx| {
+            let mut backlog = self.backlog.borrow_mut();
+            match backlog.streams.pop_front() {
+                Some(stream) => Poll::Ready(stream),
+                None => {
+                    backlog.waker = Some(cx.waker().clone());
+                    Poll::Pending
+                }
+            }
+        })
+        .await
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        let _ = with_current(|sim| sim.net.listeners.remove(&self.addr));
+        let streams = {
+            let mut backlog = self.backlog.borrow_mut();
+            backlog.closed = true;
+            std::mem::take(&mut backlog.streams)
+        };
+        // Like the kernel, reset the connections which were not accepted.
+        for stream in streams {
+            stream.conn.reset();
+        }
+    }
+}
+
+// One direction of a connection.
+#[derive(Default)]
+struct Pipe {
+    // Data delivered and not read yet.
+    data: VecDeque<u8>,
+    // Time the last packet sent is delivered at, so packets stay in order.
+    last: Duration,
+    // The writer has shut down and all its data has been delivered.
+    fin: bool,
+    reader: Option<Waker>,
+}
+
+struct Conn {
+    pipes: [RefCell<Pipe>; 2],
+    reset: Cell<bool>,
+}
+
+impl Conn {
+    fn reset(&self) {
+        self.reset.set(true);
+        self.pipes.iter().for_each(wake);
+    }
+}
+
+fn wake(pipe: &RefCell<Pipe>) {
+    let waker = pipe.borrow_mut().reader.take();
+    if let Some(waker) = waker {
+        waker.wake();
+    }
+}
+
+// An end of a connection, which reads from one pipe and writes to the other.
+struct Endpoint {
+    conn: Rc<Conn>,
+    side: usize,
+    local: Addr,
+    peer: Addr,
+    shutdown: bool,
+}
+
+impl Endpoint {
+    async fn connect(addr: Addr) -> io::Result<Self> {
+        struct Connecting {
+            result: Option<io::Result<()>>,
+            waker: Option<Waker>,
+        }
+
+        let connecting = Rc::new(RefCell::new(Connecting {
+            result: None,
+            waker: None,
+        }));
+        let endpoint = with_current(|sim| {
+            let local = match &addr {
+                Addr::Inet(peer) => {
+                    let ip = match peer.ip() {
+                        IpAddr::V4(ip) if ip.is_unspecified() => Ipv4Addr::LOCALHOST.into(),
+                        IpAddr::V6(ip) if ip.is_unspecified() => Ipv6Addr::LOCALHOST.into(),
+                        ip => ip,
+                    };
+                    Addr::Inet(SocketAddr::new(ip, sim.net.ephemeral_port()))
+                }
+                // Unnamed
+                Addr::Unix(_) => Addr::Unix(PathBuf::new()),
+            };
+            let conn = Rc::new(Conn {
+                pipes: Default::default(),
+                reset: Cell::new(false),
+            });
+            let server = Endpoint {
+                conn: conn.clone(),
+                side: 1,
+                local: addr.clone(),
+                peer: local.clone(),
+                shutdown: false,
+            };
+            let listener = sim.net.lookup(&addr);
+            let connecting = connecting.clone();
+            let at = sim.now() + sim.latency();
+            sim.schedule_at(at, move || {
+                let result = match listener {
+                    Some(listener) if !listener.borrow().closed => {
+                        let waker = {
+                            let mut backlog = listener.borrow_mut();
+                            backlog.streams.push_back(server);
+                            backlog.waker.take()
+                        };
+                        if let Some(waker) = waker {
+                            waker.wake();
+                        }
+                        Ok(())
+                    }
+                    _ => Err(io::ErrorKind::ConnectionRefused.into()),
+                };
+                let waker = {
+                    let mut connecting = connecting.borrow_mut();
+                    connecting.result = Some(result);
+                    connecting.waker.take()
+                };
+                if let Some(waker) = waker {
+                    waker.wake();
+                }
+            });
+            Endpoint {
+                conn,
+                side: 0,
+                local,
+                peer: addr,
+                shutdown: false,
+            }
+        })?;
+
+        std::future::poll_fn(|cx| {
+            let mut connecting = connecting.borrow_mut();
+            match connecting.result.take() {
+                Some(result) => Poll::Ready(result),
+                None => {
+                    connecting.waker = Some(cx.waker().clone());
+                    Poll::Pending
+                }
+            }
+        })
+        .await?;
+        Ok(endpoint)
+    }
+
+    fn rx(&self) -> &RefCell<Pipe> {
+        &self.conn.pipes[self.side]
+    }
+
+    fn tx(&self) -> &RefCell<Pipe> {
+        &self.conn.pipes[self.side ^ 1]
+    }
+
+    // Returns the time a packet sent now is delivered at.
+    fn deliver_at(&self, sim: &SimInner) -> Duration {
+        let mut tx = self.tx().borrow_mut();
+        tx.last = tx.last.max(sim.now() + sim.latency());
+        tx.last
+    }
+
+    fn poll_read(&self, cx: &mut Context<'_>, dst: *mut u8, len: usize) -> Poll<io::Result<usize>> {
+        if self.conn.reset.get() {
+            return Poll::Ready(Err(io::ErrorKind::ConnectionReset.into()));
+        }
+        if len == 0 {
+            return Poll::Ready(Ok(0));
+        }
+        let mut rx = self.rx().borrow_mut();
+        if rx.data.is_empty() {
+            if rx.fin {
+                return Poll::Ready(Ok(0));
+            }
+            rx.reader = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+
+        let mut n = len.min(rx.data.len());
+        if n > 1 {
+            n = with_current(|sim| {
+                if sim.chance(sim.config().short_read_rate) {
+                    1 + sim.rand_n(n as u32 - 1) as usize
+                } else {
+                    n
+                }
+            })
+            .unwrap_or(n);
+        }
+        for (i, byte) in rx.data.drain(..n).enumerate() {
+            unsafe { dst.add(i).write(byte) };
+        }
+        Poll::Ready(Ok(n))
+    }
+
+    fn send(&self, src: &[u8]) -> io::Result<usize> {
+        if self.conn.reset.get() {
+            return Err(io::ErrorKind::ConnectionReset.into());
+        }
+        if self.shutdown {
+            return Err(io::ErrorKind::BrokenPipe.into());
+        }
+        if src.is_empty() {
+            return Ok(0);
+        }
+        with_current(|sim| {
+            if sim.chance(sim.config().reset_rate) {
+                let conn = self.conn.clone();
+                let at = sim.now() + sim.latency();
+                sim.schedule_at(at, move || conn.reset());
+            }
+            let at = self.deliver_at(sim);
+            let conn = self.conn.clone();
+            let side = self.side ^ 1;
+            let data = src.to_vec();
+            sim.schedule_at(at, move || {
+                conn.pipes[side].borrow_mut().data.extend(data);
+                wake(&conn.pipes[side]);
+            });
+            src.len()
+        })
+    }
+
+    // Sends a FIN after the data written.
+    fn shutdown_write(&mut self) {
+        if std::mem::replace(&mut self.shutdown, true) || self.conn.reset.get() {
+            return;
+        }
+        let conn = self.conn.clone();
+        let side = self.side ^ 1;
+        let fin = move || {
+            conn.pipes[side].borrow_mut().fin = true;
+            wake(&conn.pipes[side]);
+        };
+        // Out of the runtime, e.g. once it is dropped, deliver it right away.
+        let mut fin = Some(fin);
+        let _ = with_current(|sim| {
+            let at = self.deliver_at(sim);
+            sim.schedule_at(at, fin.take().unwrap());
+        });
+        if let Some(fin) = fin {
+            fin();
+        }
+    }
+}
+
+impl Drop for Endpoint {
+    fn drop(&mut self) {
+        self.shutdown_write();
+    }
+}
+
+impl AsyncReadRent for Endpoint {
+    type ReadFuture<'a, B> = impl Future<Output = BufResult<usize, B>> where
+        B: 'a;
+    type ReadvFuture<'a, B> = impl Future<Output = BufResult<usize, B>> where
+        B: 'a;
+
+    fn read<T: IoBufMut>(&mut self, mut buf: T) -> Self::ReadFuture<'_, T> {
+        async move {
+            let res =
+                std::future::poll_fn(|cx| self.poll_read(cx, buf.write_ptr(), buf.bytes_total()))
+                    .await;
+            if let Ok(n) = res {
+                unsafe { buf.set_init(n) };
+            }
+            (res, buf)
+        }
+    }
+
+    fn readv<T: IoVecBufMut>(&mut self, mut buf: T) -> Self::ReadvFuture<'_, T> {
+        async move {
+            let n = match unsafe { RawBuf::new_from_iovec_mut(&mut buf) } {
+                Some(raw_buf) => self.read(raw_buf).await.0,
+                None => Ok(0),
+            };
+            if let Ok(n) = n {
+                unsafe { buf.set_init(n) };
+            }
+            (n, buf)
+        }
+    }
+}
+
+impl AsyncWriteRent for Endpoint {
+    type WriteFuture<'a, B> = impl Future<Output = BufResult<usize, B>> where
+        B: 'a;
+    type WritevFuture<'a, B> = impl Future<Output = BufResult<usize, B>> where
+        B: 'a;
+    type FlushFuture<'a> = impl Future<Output = io::Result<()>>;
+    type ShutdownFuture<'a> = impl Future<Output = io::Result<()>>;
+
+    fn write<T: IoBuf>(&mut self, buf: T) -> Self::WriteFuture<'_, T> {
+        async move {
+            let src = unsafe { std::slice::from_raw_parts(buf.read_ptr(), buf.bytes_init()) };
+            let res = self.send(src);
+            (res, buf)
+        }
+    }
+
+    fn writev<T: IoVecBuf>(&mut self, buf_vec: T) -> Self::WritevFuture<'_, T> {
+        async move {
+            let res = match unsafe { RawBuf::new_from_iovec(&buf_vec) } {
+                Some(raw_buf) => self.write(raw_buf).await.0,
+                None => Ok(0),
+            };
+            (res, buf_vec)
+        }
+    }
+
+    fn flush(&mut self) -> Self::FlushFuture<'_> {
+        // Data is sent on write.
+        async move { Ok(()) }
+    }
+
+    fn shutdown(&mut self) -> Self::ShutdownFuture<'_> {
+        self.shutdown_write();
+        async move { Ok(()) }
+    }
+}
+
+macro_rules! impl_stream {
+    ($ty: ty) => {
+        impl AsyncReadRent for $ty {
+            type ReadFuture<'a, B> = <Endpoint as AsyncReadRent>::ReadFuture<'a, B> where
+                B: 'a;
+            type ReadvFuture<'a, B> = <Endpoint as AsyncReadRent>::ReadvFuture<'a, B> where
+                B: 'a;
+
+            fn read<T: IoBufMut>(&mut self, buf: T) -> Self::ReadFuture<'_, T> {
+                self.inner.read(buf)
+            }
+
+            fn readv<T: IoVecBufMut>(&mut self, buf: T) -> Self::ReadvFuture<'_, T> {
+                self.inner.readv(buf)
+            }
+        }
+
+        impl AsyncWriteRent for $ty {
+            type WriteFuture<'a, B> = <Endpoint as AsyncWriteRent>::WriteFuture<'a, B> where
+                B: 'a;
+            type WritevFuture<'a, B> = <Endpoint as AsyncWriteRent>::WritevFuture<'a, B> where
+                B: 'a;
+            type FlushFuture<'a> = <Endpoint as AsyncWriteRent>::FlushFuture<'a>;
+            type ShutdownFuture<'a> = <Endpoint as AsyncWriteRent>::ShutdownFuture<'a>;
+
+            fn write<T: IoBuf>(&mut self, buf: T) -> Self::WriteFuture<'_, T> {
+                self.inner.write(buf)
+            }
+
+            fn writev<T: IoVecBuf>(&mut self, buf_vec: T) -> Self::WritevFuture<'_, T> {
+                self.inner.writev(buf_vec)
+            }
+
+            fn flush(&mut self) -> Self::FlushFuture<'_> {
+                self.inner.flush()
+            }
+
+            fn shutdown(&mut self) -> Self::ShutdownFuture<'_> {
+                self.inner.shutdown()
+            }
+        }
+    };
+}
+
+fn inet(addr: &Addr) -> SocketAddr {
+    match addr {
+        Addr::Inet(addr) => *addr,
+        Addr::Unix(_) => unreachable!("tcp socket with a unix address"),
+    }
+}
+
+fn first_addr<A: ToSocketAddrs>(addr: A) -> io::Result<SocketAddr> {
+    addr.to_socket_addrs()?.next().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "could not resolve to any address",
+        )
+    })
+}
+
+/// A simulated TCP listener.
+pub struct TcpListener {
+    inner: Listener,
+}
+
+impl TcpListener {
+    /// Binds a listener to the address. Port 0 picks an ephemeral port.
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        let inner = Listener::bind(Addr::Inet(first_addr(addr)?))?;
+        Ok(Self { inner })
+    }
+
+    /// Accepts a connection.
+    pub async fn accept(&self) -> io::Result<(TcpStream, SocketAddr)> {
+        let inner = self.inner.accept().await;
+        let peer = inet(&inner.peer);
+        Ok((TcpStream { inner }, peer))
+    }
+
+    /// Returns the local address of the listener.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        Ok(inet(&self.inner.addr))
+    }
+}
+
+impl fmt::Debug for TcpListener {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TcpListener")
+            .field("addr", &self.inner.addr)
+            .finish()
+    }
+}
+
+/// A simulated TCP stream.
+pub struct TcpStream {
+    inner: Endpoint,
+}
+
+impl TcpStream {
+    /// Opens a connection to the address, which fails with
+    /// `ECONNREFUSED` if nothing listens on it.
+    pub async fn connect<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        let inner = Endpoint::connect(Addr::Inet(first_addr(addr)?)).await?;
+        Ok(Self { inner })
+    }
+
+    /// Returns the local address of the stream.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        Ok(inet(&self.inner.local))
+    }
+
+    /// Returns the address of the peer of the stream.
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        Ok(inet(&self.inner.peer))
+    }
+}
+
+impl fmt::Debug for TcpStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TcpStream")
+            .field("local", &self.inner.local)
+            .field("peer", &self.inner.peer)
+            .finish()
+    }
+}
+
+impl_stream!(TcpStream);
+
+/// A simulated Unix stream listener.
+pub struct UnixListener {
+    inner: Listener,
+}
+
+impl UnixListener {
+    /// Binds a listener to the path. No file is created.
+    pub fn bind<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let inner = Listener::bind(Addr::Unix(path.as_ref().to_path_buf()))?;
+        Ok(Self { inner })
+    }
+
+    /// Accepts a connection. The peer is unnamed, so unlike
+    /// [`TcpListener::accept`] no address is returned.
+    pub async fn accept(&self) -> io::Result<UnixStream> {
+        let inner = self.inner.accept().await;
+        Ok(UnixStream { inner })
+    }
+}
+
+impl fmt::Debug for UnixListener {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UnixListener")
+            .field("addr", &self.inner.addr)
+            .finish()
+    }
+}
+
+/// A simulated Unix stream.
+pub struct UnixStream {
+    inner: Endpoint,
+}
+
+impl UnixStream {
+    /// Opens a connection to the path, which fails with `ECONNREFUSED` if
+    /// nothing listens on it.
+    pub async fn connect<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let inner = Endpoint::connect(Addr::Unix(path.as_ref().to_path_buf())).await?;
+        Ok(Self { inner })
+    }
+}
+
+impl fmt::Debug for UnixStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UnixStream")
+            .field("peer", &self.inner.peer)
+            .finish()
+    }
+}
+
+impl_stream!(UnixStream);
@@ -0,0 +1,64 @@
+use std::future::Future;
+
+use crate::task::{JoinHandle, Priority};
+
+/// Factory which is used to configure the properties of a new task.
+///
+/// # Examples
+///
+/// ```no_run
+/// use monoio::task::{Builder, Priority};
+///
+/// #[monoio::main]
+/// async fn main() {
+///     Builder::new()
+///         .name("health-check")
+///         .priority(Priority::High)
+///         .spawn(async {
+///             println!("healthy");
+///         })
+///         .await;
+/// }
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Builder<'a> {
+    name: Option<&'a str>,
+    priority: Priority,
+}
+
+impl<'a> Builder<'a> {
+    /// Creates a new task builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Assigns a name to the task which will be spawned.
+    ///
+    /// The name is given to the
+    /// [`on_task_spawn`](crate::RuntimeBuilder::on_task_spawn) hook, and
+    /// recorded in the span and dumps of the task.
+    #[must_use]
+    pub fn name(self, name: &'a str) -> Self {
+        Self {
+            name: Some(name),
+            ..self
+        }
+    }
+
+    /// Sets the [`Priority`] of the task which will be spawned.
+    #[must_use]
+    pub fn priority(self, priority: Priority) -> Self {
+        Self { priority, ..self }
+    }
+
+    /// Spawns a task with this builder's settings, see
+    /// [`spawn`](crate::spawn).
+    #[track_caller]
+    pub fn spawn<T>(self, future: T) -> JoinHandle<T::Output>
+    where
+        T: Future + 'static,
+        T::Output: 'static,
+    {
+        crate::runtime::spawn_inner(future, self.priority, self.name)
+    }
+}
@@ -0,0 +1,178 @@
+//! Cooperative scheduling.
+//!
+//! A task whose resources are always ready, e.g. a socket which uring
+//! completes immediately, would never yield to the other tasks. Each poll of
+//! a task gets a budget, which is consumed by the ops and the join handles
+//! making progress. Once it is exhausted, they return `Pending` and wake the
+//! task right away, so it is scheduled again after the other tasks.
+// Heavily borrowed from tokio.
+// Copyright (c) 2021 Tokio Contributors, licensed under the MIT license.
+
+use std::{
+    cell::Cell,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+// Same as tokio, which is a good trade-off between fairness and throughput.
+const INITIAL: u8 = 128;
+
+/// Remaining budget of the task being polled, None for unconstrained.
+#[derive(Clone, Copy)]
+struct Budget(Option<u8>);
+
+impl Budget {
+    const fn initial() -> Self {
+        Self(Some(INITIAL))
+    }
+
+    const fn unconstrained() -> Self {
+        Self(None)
+    }
+
+    fn decrement(&mut self) -> bool {
+        match &mut self.0 {
+            Some(0) => false,
+            Some(n) => {
+                *n -= 1;
+                true
+            }
+            None => true,
+        }
+    }
+}
+
+thread_local! {
+    static CURRENT: Cell<Budget> = Cell::new(Budget::unconstrained());
+    // Set when the task being polled yields to the other tasks.
+    static YIELDED: Cell<bool> = Cell::new(false);
+}
+
+/// Returns true if the task which has just been polled yielded, so it must be
+/// scheduled after the other tasks.
+pub(crate) fn take_yielded() -> bool {
+    YIELDED.with(|yielded| yielded.replace(false))
+}
+
+fn wake_yielded(cx: &mut Context<'_>) {
+    YIELDED.with(|yielded| yielded.set(true));
+    cx.waker().wake_by_ref();
+}
+
+/// Run `f` with a fresh budget, used to poll a task.
+pub(crate) fn budget<R>(f: impl FnOnce() -> R) -> R {
+    YIELDED.with(|yielded| yielded.set(false));
+    with_budget(Budget::initial(), f)
+}
+
+fn with_budget<R>(budget: Budget, f: impl FnOnce() -> R) -> R {
+    struct Reset(Budget);
+
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            CURRENT.with(|cell| cell.set(self.0));
+        }
+    }
+
+    let _reset = Reset(CURRENT.with(|cell| cell.replace(budget)));
+    f()
+}
+
+/// Consume a unit of budget, or wake the task and return `Pending` if it is
+/// exhausted. The unit is given back when the returned guard is dropped,
+/// unless [`RestoreOnPending::made_progress`] is called.
+pub(crate) fn poll_proceed(cx: &mut Context<'_>) -> Poll<RestoreOnPending> {
+    CURRENT.with(|cell| {
+        let mut budget = cell.get();
+        if budget.decrement() {
+            let restore = RestoreOnPending(Cell::new(cell.get()));
+            cell.set(budget);
+            Poll::Ready(restore)
+        } else {
+            wake_yielded(cx);
+            Poll::Pending
+        }
+    })
+}
+
+pub(crate) struct RestoreOnPending(Cell<Budget>);
+
+impl RestoreOnPending {
+    pub(crate) fn made_progress(&self) {
+        self.0.set(Budget::unconstrained());
+    }
+}
+
+impl Drop for RestoreOnPending {
+    fn drop(&mut self) {
+        let budget = self.0.get();
+        if budget.0.is_some() {
+            CURRENT.with(|cell| cell.set(budget));
+        }
+    }
+}
+
+/// Yield the thread to the other tasks, the current task is scheduled again
+/// after them.
+///
+/// # Examples
+///
+/// ```no_run
+/// #[monoio::main]
+/// async fn main() {
+///     for i in 0..1_000_000 {
+///         if i % 1024 == 0 {
+///             monoio::task::yield_now().await;
+///         }
+///     }
+/// }
+/// ```
+pub async fn yield_now() {
+    let mut yielded = false;
+    std::future::poll_fn(|cx| {
+        if yielded {
+            return Poll::Ready(());
+        }
+        yielded = true;
+        wake_yielded(cx);
+        Poll::Pending
+    })
+    .await
+}
+
+/// Turn off the cooperative scheduling budget of a future, which then never
+/// yields because of it. It can starve the other tasks, so it should only
+/// wrap futures which yield by themselves.
+///
+/// # Examples
+///
+/// ```no_run
+/// #[monoio::main]
+/// async fn main() {
+///     monoio::task::unconstrained(async {
+///         // ...
+///     })
+///     .await;
+/// }
+/// ```
+pub fn unconstrained<F: Future>(inner: F) -> Unconstrained<F> {
+    Unconstrained { inner }
+}
+
+/// Future returned by [`unconstrained`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Unconstrained<F> {
+    inner: F,
+}
+
+impl<F: Future> Future for Unconstrained<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: the inner future is never moved.
+        let inner = unsafe { self.map_unchecked_mut(|me| &mut me.inner) };
+        with_budget(Budget::unconstrained(), || inner.poll(cx))
+    }
+}
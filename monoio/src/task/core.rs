@@ -45,6 +45,9 @@ pub(crate) struct Header {
     /// Thread ID(sync: used for wake task on its thread)
     #[cfg(feature = "sync")]
     pub(crate) owner_id: usize,
+    /// Tracking in the live tasks of the runtime
+    #[cfg(feature = "task-dump")]
+    pub(crate) trace: super::dump::Trace,
 }
 
 pub(crate) struct Trailer {
@@ -61,6 +64,8 @@ impl<T: Future, S: Schedule> Cell<T, S> {
             header: Header {
                 state: State::new(),
                 vtable: raw::vtable::<T, S>(),
+                #[cfg(feature = "task-dump")]
+                trace: super::dump::Trace::new(),
             },
             core: Core {
                 scheduler,
@@ -83,6 +88,8 @@ impl<T: Future, S: Schedule> Cell<T, S> {
                 state: State::new(),
                 vtable: raw::vtable::<T, S>(),
                 owner_id,
+                #[cfg(feature = "task-dump")]
+                trace: super::dump::Trace::new(),
             },
             core: Core {
                 scheduler,
@@ -0,0 +1,234 @@
+//! Dumps of the live tasks of a runtime.
+
+use std::{
+    cell::{Cell, OnceCell, RefCell},
+    fmt, io,
+    panic::Location,
+    ptr::NonNull,
+    time::{Duration, Instant},
+};
+
+use super::{core::Header, JoinHandle, Task};
+use crate::{
+    signal::SignalKind,
+    utils::linked_list::{Link, LinkedList, Pointers},
+};
+
+/// A snapshot of a live task, returned by [`dump`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct TaskDump {
+    /// Id of the task, unique in the process.
+    pub id: u64,
+    /// Name of the task, set with [`Builder::name`](crate::task::Builder::name).
+    pub name: Option<String>,
+    /// Location where the task was spawned.
+    pub location: &'static Location<'static>,
+    /// State of the task.
+    pub state: TaskState,
+    /// Time elapsed since the task was last polled, `None` if it has never
+    /// been.
+    pub since_last_poll: Option<Duration>,
+}
+
+impl fmt::Display for TaskDump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "task {}", self.id)?;
+        if let Some(name) = &self.name {
+            write!(f, " {name:?}")?;
+        }
+        write!(f, " spawned at {}, {:?}", self.location, self.state)?;
+        match self.since_last_poll {
+            Some(elapsed) => write!(f, ", last polled {elapsed:?} ago"),
+            None => write!(f, ", never polled"),
+        }
+    }
+}
+
+/// State of a live task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TaskState {
+    /// Waiting to be woken.
+    Idle,
+    /// Woken, and waiting in the run queue.
+    Scheduled,
+    /// Being polled, e.g. the task calling [`dump`].
+    Running,
+}
+
+/// Returns a snapshot of the live tasks of the current runtime, which are the
+/// spawned tasks which have not completed yet.
+///
+/// # Panics
+///
+/// Panics if called outside a runtime.
+///
+/// # Examples
+///
+/// ```no_run
+/// #[monoio::main]
+/// async fn main() {
+///     monoio::spawn(std::future::pending::<()>());
+///     for task in monoio::task::dump() {
+///         println!("{task}");
+///     }
+/// }
+/// ```
+pub fn dump() -> Vec<TaskDump> {
+    crate::runtime::CURRENT.with(|ctx| ctx.owned.dump())
+}
+
+/// Spawns a task passing a [`dump`] of the current runtime to `sink` each time
+/// the signal is received, e.g. to see what a stuck runtime is doing.
+///
+/// The dump is only taken once the runtime polls the task, so it can not
+/// show a task which never yields.
+///
+/// # Examples
+///
+/// ```no_run
+/// use monoio::signal::SignalKind;
+///
+/// #[monoio::main]
+/// async fn main() {
+///     monoio::task::dump_on_signal(SignalKind::user_defined1(), |tasks| {
+///         eprintln!("{} live tasks", tasks.len());
+///         for task in tasks {
+///             eprintln!("  {task}");
+///         }
+///     })
+///     .unwrap();
+/// }
+/// ```
+pub fn dump_on_signal(
+    kind: SignalKind,
+    sink: impl Fn(Vec<TaskDump>) + 'static,
+) -> io::Result<JoinHandle<()>> {
+    let mut signal = crate::signal::signal(kind)?;
+    Ok(super::Builder::new().name("task-dump").spawn(async move {
+        while signal.recv().await.is_some() {
+            sink(dump());
+        }
+    }))
+}
+
+/// Tracking of a task in the list of its runtime.
+pub(crate) struct Trace {
+    owned: Pointers<Header>,
+    meta: OnceCell<Meta>,
+    last_poll: Cell<Option<Instant>>,
+}
+
+struct Meta {
+    id: u64,
+    name: Option<Box<str>>,
+    location: &'static Location<'static>,
+}
+
+impl Trace {
+    pub(crate) fn new() -> Self {
+        Self {
+            owned: Pointers::new(),
+            meta: OnceCell::new(),
+            last_poll: Cell::new(None),
+        }
+    }
+
+    pub(crate) fn polled(&self) {
+        self.last_poll.set(Some(Instant::now()));
+    }
+}
+
+unsafe impl<S: 'static> Link for Task<S> {
+    type Handle = Task<S>;
+    type Target = Header;
+
+    fn as_raw(handle: &Task<S>) -> NonNull<Header> {
+        NonNull::from(handle.header())
+    }
+
+    unsafe fn from_raw(ptr: NonNull<Header>) -> Task<S> {
+        Task::from_raw(ptr)
+    }
+
+    unsafe fn pointers(target: NonNull<Header>) -> NonNull<Pointers<Header>> {
+        NonNull::new_unchecked(std::ptr::addr_of_mut!((*target.as_ptr()).trace.owned))
+    }
+}
+
+/// The live tasks of a runtime. Each of them is held by a reference, which is
+/// released once it completes.
+pub(crate) struct OwnedTasks<S: 'static> {
+    list: RefCell<LinkedList<Task<S>, Header>>,
+}
+
+impl<S: 'static> OwnedTasks<S> {
+    pub(crate) fn new() -> Self {
+        Self {
+            list: RefCell::new(LinkedList::new()),
+        }
+    }
+
+    pub(crate) fn insert(
+        &self,
+        task: &Task<S>,
+        id: u64,
+        name: Option<&str>,
+        location: &'static Location<'static>,
+    ) {
+        let header = task.header();
+        let _ = header.trace.meta.set(Meta {
+            id,
+            name: name.map(Into::into),
+            location,
+        });
+        header.state.ref_inc();
+        let task = unsafe { Task::from_raw(NonNull::from(header)) };
+        self.list.borrow_mut().push_front(task);
+    }
+
+    pub(crate) fn remove(&self, header: NonNull<Header>) -> Option<Task<S>> {
+        // Safety: tasks are only in the list of the runtime they are spawned
+        // on, which is the one completing them.
+        unsafe { self.list.borrow_mut().remove(header) }
+    }
+
+    fn dump(&self) -> Vec<TaskDump> {
+        let now = Instant::now();
+        let mut tasks = Vec::new();
+        self.list.borrow().for_each(|header| {
+            let meta = match header.trace.meta.get() {
+                Some(meta) => meta,
+                None => return,
+            };
+            let snapshot = header.state.load();
+            let state = if snapshot.is_running() {
+                TaskState::Running
+            } else if snapshot.is_notified() {
+                TaskState::Scheduled
+            } else {
+                TaskState::Idle
+            };
+            tasks.push(TaskDump {
+                id: meta.id,
+                name: meta.name.as_deref().map(Into::into),
+                location: meta.location,
+                state,
+                since_last_poll: header.trace.last_poll.get().map(|t| now - t),
+            });
+        });
+        // Oldest first
+        tasks.reverse();
+        tasks
+    }
+}
+
+impl<S: 'static> Drop for OwnedTasks<S> {
+    fn drop(&mut self) {
+        // Release the references, the tasks are deallocated with the runtime.
+        while let Some(task) = self.list.get_mut().pop_back() {
+            drop(task);
+        }
+    }
+}
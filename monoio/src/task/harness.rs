@@ -55,6 +55,10 @@ where
                 self.core().scheduler.yield_now(self.get_new_task());
             }
             PollFuture::Complete => {
+                // The task being run holds a reference, so this one is never
+                // the last.
+                #[cfg(feature = "task-dump")]
+                drop(self.core().scheduler.release(self.cell.cast()));
                 self.complete();
             }
             PollFuture::Done => (),
@@ -68,6 +72,8 @@ where
     fn poll_inner(&self) -> PollFuture {
         // notified -> running
         self.header().state.transition_to_running();
+        #[cfg(feature = "task-dump")]
+        self.header().trace.polled();
 
         // poll the future
         let waker_ref = waker_ref::<T, S>(self.header());
@@ -10,6 +10,7 @@ use super::raw::RawTask;
 
 /// JoinHandle
 pub struct JoinHandle<T> {
+    // None if the task was cancelled before being spawned
     raw: Option<RawTask>,
     _p: PhantomData<T>,
 }
@@ -21,20 +22,34 @@ impl<T> JoinHandle<T> {
             _p: PhantomData,
         }
     }
+
+    pub(crate) fn cancelled() -> JoinHandle<T> {
+        JoinHandle {
+            raw: None,
+            _p: PhantomData,
+        }
+    }
+
+    /// Returns true if the task was cancelled without being run, as it was
+    /// spawned once the runtime started shutting down. Awaiting the handle of
+    /// such a task panics.
+    pub fn is_cancelled(&self) -> bool {
+        self.raw.is_none()
+    }
 }
 
 impl<T> Future for JoinHandle<T> {
     type Output = T;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let coop = ready!(super::coop::poll_proceed(cx));
         let mut ret = Poll::Pending;
 
-        // Raw should always be set. If it is not, this is due to polling after
-        // completion
+        // Raw is only unset if the task was cancelled before being spawned
         let raw = self
             .raw
             .as_ref()
-            .expect("polling after `JoinHandle` already completed");
+            .expect("the task was cancelled, as it was spawned during shutdown");
 
         // Try to read the task output. If the task is not yet complete, the
         // waker is stored and is notified once the task does complete.
@@ -50,6 +65,9 @@ impl<T> Future for JoinHandle<T> {
         unsafe {
             raw.try_read_output(&mut ret as *mut _ as *mut (), cx.waker());
         }
+        if ret.is_ready() {
+            coop.made_progress();
+        }
         ret
     }
 }
@@ -0,0 +1,250 @@
+//! A collection of tasks spawned on the runtime.
+
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    fmt,
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+use fxhash::FxHashMap;
+use pin_project_lite::pin_project;
+
+use crate::task::Priority;
+
+/// A collection of tasks spawned on the runtime, whose outputs are returned in
+/// the order they complete.
+///
+/// Unlike the tasks spawned with [`spawn`](crate::spawn), which keep running
+/// when their [`JoinHandle`] is dropped, the tasks of a set are aborted when it
+/// is dropped.
+///
+/// [`JoinHandle`]: crate::task::JoinHandle
+///
+/// # Examples
+///
+/// ```
+/// use monoio::task::JoinSet;
+///
+/// #[monoio::main]
+/// async fn main() {
+///     let mut set = JoinSet::new();
+///     for i in 0..4 {
+///         set.spawn(async move { i * 2 });
+///     }
+///
+///     let mut sum = 0;
+///     while let Some(n) = set.join_next().await {
+///         sum += n;
+///     }
+///     assert_eq!(sum, 12);
+/// }
+/// ```
+pub struct JoinSet<T> {
+    shared: Rc<RefCell<Shared<T>>>,
+}
+
+struct Shared<T> {
+    // Outputs of the tasks completed but not joined yet.
+    completed: VecDeque<T>,
+    // Wakers of the running tasks, used to abort them.
+    running: FxHashMap<u64, Waker>,
+    next_id: u64,
+    // Tasks spawned before the epoch changed are aborted.
+    epoch: u64,
+    waker: Option<Waker>,
+}
+
+impl<T: 'static> JoinSet<T> {
+    /// Creates an empty `JoinSet`.
+    pub fn new() -> Self {
+        Self {
+            shared: Rc::new(RefCell::new(Shared {
+                completed: VecDeque::new(),
+                running: FxHashMap::default(),
+                next_id: 0,
+                epoch: 0,
+                waker: None,
+            })),
+        }
+    }
+
+    /// Returns the number of tasks which have not been joined yet, including
+    /// the ones being aborted.
+    pub fn len(&self) -> usize {
+        let shared = self.shared.borrow();
+        shared.running.len() + shared.completed.len()
+    }
+
+    /// Returns whether the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Spawns a task in the set.
+    #[track_caller]
+    pub fn spawn<F>(&mut self, future: F)
+    where
+        F: Future<Output = T> + 'static,
+    {
+        self.spawn_with_priority(Priority::Normal, future);
+    }
+
+    /// Spawns a task in the set with the given [`Priority`].
+    #[track_caller]
+    pub fn spawn_with_priority<F>(&mut self, priority: Priority, future: F)
+    where
+        F: Future<Output = T> + 'static,
+    {
+        let (id, epoch) = {
+            let mut shared = self.shared.borrow_mut();
+            let id = shared.next_id;
+            shared.next_id += 1;
+            // The waker is set on the first poll, until then the task can be
+            // aborted without being woken.
+            shared
+                .running
+                .insert(id, crate::task::waker_fn::dummy_waker());
+            (id, shared.epoch)
+        };
+        let task = Member {
+            future,
+            guard: Guard {
+                id,
+                epoch,
+                shared: self.shared.clone(),
+            },
+        };
+        // The output is collected by the set.
+        drop(crate::spawn_with_priority(priority, task));
+    }
+
+    /// Waits for one of the tasks to complete and returns its output, or
+    /// returns `None` once the set is empty.
+    ///
+    /// Aborted tasks are removed from the set without returning anything.
+    pub async fn join_next(&mut self) -> Option<T> {
+        std::future::poll_fn(|cx| self.poll_join_next(cx)).await
+    }
+
+    /// Polls for one of the tasks to complete, see [`JoinSet::join_next`].
+    pub fn poll_join_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let coop = ready!(super::coop::poll_proceed(cx));
+        let mut shared = self.shared.borrow_mut();
+        if let Some(output) = shared.completed.pop_front() {
+            coop.made_progress();
+            return Poll::Ready(Some(output));
+        }
+        if shared.running.is_empty() {
+            return Poll::Ready(None);
+        }
+        match &mut shared.waker {
+            Some(waker) if waker.will_wake(cx.waker()) => {}
+            waker => *waker = Some(cx.waker().clone()),
+        }
+        Poll::Pending
+    }
+
+    /// Aborts all the tasks of the set. Their futures are dropped the next
+    /// time they would be polled, and the set is empty once they are.
+    ///
+    /// The outputs of the tasks which have already completed are still
+    /// returned by [`JoinSet::join_next`].
+    pub fn abort_all(&mut self) {
+        let wakers: Vec<_> = {
+            let mut shared = self.shared.borrow_mut();
+            shared.epoch += 1;
+            shared.running.values().cloned().collect()
+        };
+        wakers.into_iter().for_each(Waker::wake);
+    }
+
+    /// Aborts all the tasks of the set and waits for them to be dropped.
+    pub async fn shutdown(&mut self) {
+        self.abort_all();
+        while self.join_next().await.is_some() {}
+    }
+}
+
+impl<T: 'static> Default for JoinSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for JoinSet<T> {
+    fn drop(&mut self) {
+        let wakers: Vec<_> = {
+            let mut shared = self.shared.borrow_mut();
+            shared.epoch += 1;
+            shared.completed.clear();
+            shared.running.values().cloned().collect()
+        };
+        wakers.into_iter().for_each(Waker::wake);
+    }
+}
+
+impl<T> fmt::Debug for JoinSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let shared = self.shared.borrow();
+        f.debug_struct("JoinSet")
+            .field("running", &shared.running.len())
+            .field("completed", &shared.completed.len())
+            .finish()
+    }
+}
+
+pin_project! {
+    // A task of a set, which completes early once aborted.
+    struct Member<F, T> {
+        #[pin]
+        future: F,
+        guard: Guard<T>,
+    }
+}
+
+// Removes the task from the set, whether it completes, is aborted or is
+// dropped by the runtime.
+struct Guard<T> {
+    id: u64,
+    epoch: u64,
+    shared: Rc<RefCell<Shared<T>>>,
+}
+
+impl<T> Drop for Guard<T> {
+    fn drop(&mut self) {
+        let waker = {
+            let mut shared = self.shared.borrow_mut();
+            shared.running.remove(&self.id);
+            shared.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<F: Future<Output = T>, T> Future for Member<F, T> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        {
+            let mut shared = this.guard.shared.borrow_mut();
+            if shared.epoch != this.guard.epoch {
+                return Poll::Ready(());
+            }
+            if let Some(waker) = shared.running.get_mut(&this.guard.id) {
+                if !waker.will_wake(cx.waker()) {
+                    *waker = cx.waker().clone();
+                }
+            }
+        }
+        let output = ready!(this.future.poll(cx));
+        this.guard.shared.borrow_mut().completed.push_back(output);
+        Poll::Ready(())
+    }
+}
@@ -5,8 +5,33 @@
 mod utils;
 pub(crate) mod waker_fn;
 
+pub(crate) mod coop;
+pub use self::coop::{unconstrained, yield_now, Unconstrained};
+
+pub use crate::scheduler::Priority;
+
+mod task_local;
+pub use self::task_local::{AccessError, LocalKey, TaskLocalFuture};
+
+mod scope;
+pub use self::scope::{scope, Scope, ScopeFuture, ScopedJoinHandle};
+
+mod join_set;
+pub use self::join_set::JoinSet;
+
+mod builder;
+pub use self::builder::Builder;
+
+#[cfg(feature = "task-dump")]
+mod dump;
+#[cfg(feature = "task-dump")]
+pub(crate) use self::dump::OwnedTasks;
+#[cfg(feature = "task-dump")]
+pub use self::dump::{dump, dump_on_signal, TaskDump, TaskState};
+
 mod core;
-use self::core::{Cell, Header};
+use self::core::Cell;
+pub(crate) use self::core::Header;
 
 mod harness;
 use self::harness::Harness;
@@ -67,6 +92,34 @@ pub(crate) trait Schedule: Sized + 'static {
     fn yield_now(&self, task: Task<Self>) {
         self.schedule(task);
     }
+    /// Release the reference of the owner of the task, once it completes.
+    #[cfg(feature = "task-dump")]
+    fn release(&self, _header: NonNull<Header>) -> Option<Task<Self>> {
+        None
+    }
+}
+
+/// Metadata of a task being spawned, given to the
+/// [`on_task_spawn`](crate::RuntimeBuilder::on_task_spawn) hook.
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub struct TaskMeta<'a> {
+    /// Id of the task, unique in the process.
+    pub id: u64,
+    /// Name of the task, set with [`Builder::name`].
+    pub name: Option<&'a str>,
+    /// Location where the task was spawned.
+    pub location: &'static std::panic::Location<'static>,
+    /// Priority of the task.
+    pub priority: Priority,
+}
+
+// Ids of the tasks, shared by their spawn hook, spans and dumps.
+pub(crate) fn next_id() -> u64 {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
 }
 
 #[cfg(not(feature = "sync"))]
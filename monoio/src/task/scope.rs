@@ -0,0 +1,280 @@
+//! Scoped tasks, which can borrow from their environment.
+
+use std::{
+    cell::RefCell,
+    fmt,
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Wake, Waker},
+};
+
+type BoxFuture<'env> = Pin<Box<dyn Future<Output = ()> + 'env>>;
+
+/// Creates a scope for spawning tasks which borrow from the environment.
+///
+/// The tasks spawned with the [`Scope`] passed to `f` run concurrently with
+/// the future returned by `f`, and the scope only completes once all of them
+/// have. If the scope is dropped before, the tasks which are still running are
+/// cancelled.
+///
+/// Unlike [`spawn`](crate::spawn), scoped tasks are polled by the task awaiting
+/// the scope rather than scheduled on their own. This is what makes borrowing
+/// sound: the borrowed data outlives the scope future, which owns the tasks.
+///
+/// # Examples
+///
+/// ```
+/// #[monoio::main]
+/// async fn main() {
+///     let names = vec!["monoio", "uring"];
+///
+///     let lens = monoio::task::scope(|s| {
+///         let names = &names;
+///         async move {
+///             let handles: Vec<_> = names
+///                 .iter()
+///                 .map(|name| s.spawn(async move { name.len() }))
+///                 .collect();
+///             let mut lens = Vec::new();
+///             for handle in handles {
+///                 lens.push(handle.await);
+///             }
+///             lens
+///         }
+///     })
+///     .await;
+///     assert_eq!(lens, [6, 5]);
+/// }
+/// ```
+pub fn scope<'env, F, Fut>(f: F) -> ScopeFuture<'env, Fut::Output>
+where
+    F: FnOnce(Scope<'env>) -> Fut,
+    Fut: Future + 'env,
+{
+    let shared = Rc::new(RefCell::new(Shared {
+        spawned: Vec::new(),
+        closed: false,
+    }));
+    let body = f(Scope {
+        shared: shared.clone(),
+    });
+    ScopeFuture {
+        body: Some(Box::pin(body)),
+        output: None,
+        children: Vec::new(),
+        shared,
+    }
+}
+
+struct Shared<'env> {
+    // Tasks spawned since the scope was last polled.
+    spawned: Vec<BoxFuture<'env>>,
+    closed: bool,
+}
+
+/// A handle to spawn tasks in a scope, created by [`scope`].
+#[derive(Clone)]
+pub struct Scope<'env> {
+    shared: Rc<RefCell<Shared<'env>>>,
+}
+
+impl<'env> Scope<'env> {
+    /// Spawns a task in the scope, returning a [`ScopedJoinHandle`] for it.
+    ///
+    /// The task runs until it completes even if the handle is dropped, unless
+    /// the scope is cancelled. A task spawned once the scope has completed or
+    /// been dropped is cancelled right away.
+    pub fn spawn<T>(&self, future: T) -> ScopedJoinHandle<T::Output>
+    where
+        T: Future + 'env,
+        T::Output: 'env,
+    {
+        let slot = Rc::new(RefCell::new(Slot {
+            output: None,
+            waker: None,
+        }));
+        let handle = ScopedJoinHandle { slot: slot.clone() };
+        let future = async move {
+            let output = future.await;
+            let mut slot = slot.borrow_mut();
+            slot.output = Some(output);
+            if let Some(waker) = slot.waker.take() {
+                waker.wake();
+            }
+        };
+
+        let mut shared = self.shared.borrow_mut();
+        if !shared.closed {
+            shared.spawned.push(Box::pin(future));
+        }
+        handle
+    }
+}
+
+impl fmt::Debug for Scope<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Scope { .. }")
+    }
+}
+
+struct Slot<T> {
+    output: Option<T>,
+    waker: Option<Waker>,
+}
+
+/// Future to await the output of a scoped task, created by [`Scope::spawn`].
+pub struct ScopedJoinHandle<T> {
+    slot: Rc<RefCell<Slot<T>>>,
+}
+
+impl<T> Future for ScopedJoinHandle<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut slot = self.slot.borrow_mut();
+        match slot.output.take() {
+            Some(output) => Poll::Ready(output),
+            None => {
+                slot.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<T> fmt::Debug for ScopedJoinHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("ScopedJoinHandle { .. }")
+    }
+}
+
+// Wakes a scoped task, which is then polled the next time the scope is.
+struct ChildWaker {
+    woken: AtomicBool,
+    parent: Mutex<Waker>,
+}
+
+impl Wake for ChildWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.woken.store(true, Ordering::Release);
+        self.parent.lock().unwrap().wake_by_ref();
+    }
+}
+
+struct Child<'env> {
+    future: BoxFuture<'env>,
+    waker: Arc<ChildWaker>,
+}
+
+/// Future returned by [`scope`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct ScopeFuture<'env, R> {
+    body: Option<Pin<Box<dyn Future<Output = R> + 'env>>>,
+    output: Option<R>,
+    children: Vec<Child<'env>>,
+    shared: Rc<RefCell<Shared<'env>>>,
+}
+
+impl<R> ScopeFuture<'_, R> {
+    // Polls the woken children, and the ones spawned meanwhile, returning
+    // true once all of them have completed.
+    fn poll_children(&mut self, cx: &mut Context<'_>) -> bool {
+        loop {
+            let spawned = std::mem::take(&mut self.shared.borrow_mut().spawned);
+            self.children
+                .extend(spawned.into_iter().map(|future| Child {
+                    future,
+                    waker: Arc::new(ChildWaker {
+                        woken: AtomicBool::new(true),
+                        parent: Mutex::new(cx.waker().clone()),
+                    }),
+                }));
+
+            let mut i = 0;
+            while i < self.children.len() {
+                let child = &mut self.children[i];
+                {
+                    let mut parent = child.waker.parent.lock().unwrap();
+                    if !parent.will_wake(cx.waker()) {
+                        *parent = cx.waker().clone();
+                    }
+                }
+                if !child.waker.woken.swap(false, Ordering::Acquire) {
+                    i += 1;
+                    continue;
+                }
+                let waker = Waker::from(child.waker.clone());
+                if child
+                    .future
+                    .as_mut()
+                    .poll(&mut Context::from_waker(&waker))
+                    .is_ready()
+                {
+                    self.children.swap_remove(i);
+                } else {
+                    i += 1;
+                }
+            }
+
+            if self.shared.borrow().spawned.is_empty() {
+                return self.children.is_empty();
+            }
+        }
+    }
+}
+
+// The body and the tasks are boxed, and the output is never pinned.
+impl<R> Unpin for ScopeFuture<'_, R> {}
+
+impl<R> Future for ScopeFuture<'_, R> {
+    type Output = R;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        if let Some(body) = this.body.as_mut() {
+            if let Poll::Ready(output) = body.as_mut().poll(cx) {
+                this.body = None;
+                this.output = Some(output);
+            }
+        }
+        // Close the scope as soon as the body and the tasks have completed, so
+        // no task is left behind.
+        if this.poll_children(cx) && this.body.is_none() {
+            this.shared.borrow_mut().closed = true;
+            return Poll::Ready(
+                this.output
+                    .take()
+                    .expect("`ScopeFuture` polled after completion"),
+            );
+        }
+        Poll::Pending
+    }
+}
+
+impl<R> Drop for ScopeFuture<'_, R> {
+    fn drop(&mut self) {
+        // Cancel the tasks still running. Tasks spawned from now on are
+        // dropped by `spawn`.
+        self.shared.borrow_mut().closed = true;
+        let spawned = std::mem::take(&mut self.shared.borrow_mut().spawned);
+        drop(spawned);
+        self.children.clear();
+    }
+}
+
+impl<R> fmt::Debug for ScopeFuture<'_, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ScopeFuture")
+            .field("tasks", &self.children.len())
+            .finish_non_exhaustive()
+    }
+}
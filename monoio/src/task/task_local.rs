@@ -0,0 +1,399 @@
+//! Task-local storage.
+// Heavily borrowed from tokio.
+// Copyright (c) 2021 Tokio Contributors, licensed under the MIT license.
+
+use std::{
+    cell::RefCell,
+    error::Error,
+    fmt,
+    future::Future,
+    marker::PhantomPinned,
+    pin::Pin,
+    task::{Context, Poll},
+    thread,
+};
+
+/// Declares a new task-local key of type [`task::LocalKey`].
+///
+/// The syntax is the same as `thread_local!`, except that the keys have no
+/// initializer: a value is only set inside [`LocalKey::scope`]. Unlike thread
+/// locals, the value is not shared by the tasks interleaved on the thread.
+///
+/// [`task::LocalKey`]: crate::task::LocalKey
+/// [`LocalKey::scope`]: crate::task::LocalKey::scope
+///
+/// # Examples
+///
+/// ```
+/// monoio::task_local! {
+///     pub static TRACE_ID: u64;
+///     static TENANT: String;
+/// }
+///
+/// #[monoio::main]
+/// async fn main() {
+///     TRACE_ID
+///         .scope(42, async {
+///             assert_eq!(TRACE_ID.get(), 42);
+///         })
+///         .await;
+/// }
+/// ```
+#[macro_export]
+macro_rules! task_local {
+    // empty (base case for the recursion)
+    () => {};
+
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty; $($rest:tt)*) => {
+        $crate::__task_local_inner!($(#[$attr])* $vis $name, $t);
+        $crate::task_local!($($rest)*);
+    };
+
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty) => {
+        $crate::__task_local_inner!($(#[$attr])* $vis $name, $t);
+    }
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __task_local_inner {
+    ($(#[$attr:meta])* $vis:vis $name:ident, $t:ty) => {
+        $(#[$attr])*
+        $vis static $name: $crate::task::LocalKey<$t> = {
+            std::thread_local! {
+                static __KEY: std::cell::RefCell<Option<$t>> = const { std::cell::RefCell::new(None) };
+            }
+
+            $crate::task::LocalKey { inner: __KEY }
+        };
+    };
+}
+
+/// A key for task-local data, declared with [`task_local!`].
+///
+/// The value is set for the duration of a future with [`scope`], and read
+/// with [`with`] or [`try_with`]. A spawned task does not see the values of
+/// its parent, unless the key is passed down with [`inherit`].
+///
+/// [`task_local!`]: crate::task_local
+/// [`scope`]: LocalKey::scope
+/// [`with`]: LocalKey::with
+/// [`try_with`]: LocalKey::try_with
+/// [`inherit`]: LocalKey::inherit
+pub struct LocalKey<T: 'static> {
+    #[doc(hidden)]
+    pub inner: thread::LocalKey<RefCell<Option<T>>>,
+}
+
+impl<T: 'static> LocalKey<T> {
+    /// Sets a value `T` as the task-local value for the future `F`.
+    ///
+    /// On completion of `scope`, the task-local will be dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// monoio::task_local! {
+    ///     static NUMBER: u32;
+    /// }
+    ///
+    /// #[monoio::main]
+    /// async fn main() {
+    ///     NUMBER
+    ///         .scope(1, async move {
+    ///             println!("task local value: {}", NUMBER.get());
+    ///         })
+    ///         .await;
+    /// }
+    /// ```
+    pub fn scope<F>(&'static self, value: T, f: F) -> TaskLocalFuture<T, F>
+    where
+        F: Future,
+    {
+        TaskLocalFuture {
+            local: self,
+            slot: Some(value),
+            future: Some(f),
+            _pinned: PhantomPinned,
+        }
+    }
+
+    /// Sets a value `T` as the task-local value for the closure `F`.
+    ///
+    /// On completion of `sync_scope`, the task-local will be dropped.
+    #[track_caller]
+    pub fn sync_scope<F, R>(&'static self, value: T, f: F) -> R
+    where
+        F: FnOnce() -> R,
+    {
+        let mut value = Some(value);
+        match self.scope_inner(&mut value, f) {
+            Ok(res) => res,
+            Err(err) => err.panic(),
+        }
+    }
+
+    fn scope_inner<F, R>(&'static self, slot: &mut Option<T>, f: F) -> Result<R, ScopeInnerErr>
+    where
+        F: FnOnce() -> R,
+    {
+        struct Guard<'a, T: 'static> {
+            local: &'static LocalKey<T>,
+            slot: &'a mut Option<T>,
+        }
+
+        impl<'a, T: 'static> Drop for Guard<'a, T> {
+            fn drop(&mut self) {
+                // This should not panic.
+                //
+                // We know that the RefCell was not borrowed before the call to
+                // `scope_inner`, so the only way for this to panic is if the
+                // closure has created but not destroyed a RefCell guard.
+                // However, we never give user-code access to the guards, so
+                // this is impossible.
+                //
+                // Similarly, the thread local has already been accessed in
+                // `scope_inner`, so it has not been destroyed.
+                self.local.inner.with(|inner| {
+                    let mut ref_mut = inner.borrow_mut();
+                    std::mem::swap(self.slot, &mut *ref_mut);
+                });
+            }
+        }
+
+        self.inner.try_with(|inner| {
+            inner
+                .try_borrow_mut()
+                .map(|mut ref_mut| std::mem::swap(slot, &mut *ref_mut))
+        })??;
+
+        let guard = Guard { local: self, slot };
+
+        let res = f();
+
+        drop(guard);
+
+        Ok(res)
+    }
+
+    /// Accesses the current task-local and runs the provided closure.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the task local doesn't have a value set.
+    #[track_caller]
+    pub fn with<F, R>(&'static self, f: F) -> R
+    where
+        F: FnOnce(&T) -> R,
+    {
+        match self.try_with(f) {
+            Ok(res) => res,
+            Err(_) => panic!("cannot access a task-local storage value without setting it first"),
+        }
+    }
+
+    /// Accesses the current task-local and runs the provided closure.
+    ///
+    /// If the task-local with the associated key is not present, this
+    /// method will return an `AccessError`.
+    pub fn try_with<F, R>(&'static self, f: F) -> Result<R, AccessError>
+    where
+        F: FnOnce(&T) -> R,
+    {
+        // If called after the thread-local storing the task-local is destroyed,
+        // then we are outside of a closure where the task-local is set.
+        //
+        // Therefore, it is correct to return an AccessError if `try_with`
+        // returns an error.
+        let try_with_res = self.inner.try_with(|v| {
+            // This call to `borrow` cannot panic because no user-defined code
+            // runs while a `borrow_mut` call is active.
+            v.borrow().as_ref().map(f)
+        });
+
+        match try_with_res {
+            Ok(Some(res)) => Ok(res),
+            Ok(None) | Err(_) => Err(AccessError { _private: () }),
+        }
+    }
+}
+
+impl<T: Clone + 'static> LocalKey<T> {
+    /// Returns a copy of the task-local value if the task-local value
+    /// implements `Clone`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the task local doesn't have a value set.
+    #[track_caller]
+    pub fn get(&'static self) -> T {
+        self.with(|v| v.clone())
+    }
+
+    /// Sets a copy of the current task-local value, if any, as the task-local
+    /// value for the future `F`.
+    ///
+    /// Task-locals are not inherited by spawned tasks, this is how a key is
+    /// passed down to one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// monoio::task_local! {
+    ///     static TRACE_ID: u64;
+    /// }
+    ///
+    /// #[monoio::main]
+    /// async fn main() {
+    ///     TRACE_ID
+    ///         .scope(42, async {
+    ///             let child = monoio::spawn(TRACE_ID.inherit(async {
+    ///                 assert_eq!(TRACE_ID.get(), 42);
+    ///             }));
+    ///             child.await;
+    ///         })
+    ///         .await;
+    /// }
+    /// ```
+    pub fn inherit<F>(&'static self, f: F) -> TaskLocalFuture<T, F>
+    where
+        F: Future,
+    {
+        TaskLocalFuture {
+            local: self,
+            slot: self.try_with(T::clone).ok(),
+            future: Some(f),
+            _pinned: PhantomPinned,
+        }
+    }
+}
+
+impl<T: 'static> fmt::Debug for LocalKey<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("LocalKey { .. }")
+    }
+}
+
+/// A future that sets a value `T` of a task local for the future `F` during
+/// its execution.
+///
+/// The value of the task-local must be `'static` and will be dropped on the
+/// completion of the future.
+///
+/// Created by the function [`LocalKey::scope`](self::LocalKey::scope).
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct TaskLocalFuture<T, F>
+where
+    T: 'static,
+{
+    local: &'static LocalKey<T>,
+    slot: Option<T>,
+    future: Option<F>,
+    _pinned: PhantomPinned,
+}
+
+impl<T: 'static, F: Future> Future for TaskLocalFuture<T, F> {
+    type Output = F::Output;
+
+    #[track_caller]
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: the future is never moved, and the other fields are not
+        // structurally pinned.
+        let this = unsafe { self.get_unchecked_mut() };
+        let mut future_opt = unsafe { Pin::new_unchecked(&mut this.future) };
+
+        let res =
+            this.local
+                .scope_inner(&mut this.slot, || match future_opt.as_mut().as_pin_mut() {
+                    Some(fut) => {
+                        let res = fut.poll(cx);
+                        if res.is_ready() {
+                            future_opt.set(None);
+                        }
+                        Some(res)
+                    }
+                    None => None,
+                });
+
+        match res {
+            Ok(Some(res)) => res,
+            Ok(None) => panic!("`TaskLocalFuture` polled after completion"),
+            Err(err) => err.panic(),
+        }
+    }
+}
+
+impl<T: 'static, F> Drop for TaskLocalFuture<T, F> {
+    fn drop(&mut self) {
+        // Drop the future with the value set, so its destructors can still
+        // access the task-local.
+        if self.future.is_some() {
+            // Safety: the future is dropped in place.
+            let future = unsafe { Pin::new_unchecked(&mut self.future) };
+            let _ = self.local.scope_inner(&mut self.slot, || {
+                let mut future = future;
+                future.set(None);
+            });
+        }
+    }
+}
+
+impl<T: 'static + fmt::Debug, F> fmt::Debug for TaskLocalFuture<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TaskLocalFuture")
+            .field("value", &self.slot)
+            .finish_non_exhaustive()
+    }
+}
+
+/// An error returned by [`LocalKey::try_with`](method@LocalKey::try_with).
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub struct AccessError {
+    _private: (),
+}
+
+impl fmt::Debug for AccessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AccessError").finish()
+    }
+}
+
+impl fmt::Display for AccessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt("task-local value not set", f)
+    }
+}
+
+impl Error for AccessError {}
+
+enum ScopeInnerErr {
+    BorrowError,
+    AccessError,
+}
+
+impl ScopeInnerErr {
+    #[track_caller]
+    fn panic(&self) -> ! {
+        match self {
+            Self::BorrowError => {
+                panic!("cannot enter a task-local scope while the task-local storage is borrowed")
+            }
+            Self::AccessError => panic!(
+                "cannot enter a task-local scope during or after destruction of the underlying \
+                 thread-local"
+            ),
+        }
+    }
+}
+
+impl From<std::cell::BorrowMutError> for ScopeInnerErr {
+    fn from(_: std::cell::BorrowMutError) -> Self {
+        Self::BorrowError
+    }
+}
+
+impl From<thread::AccessError> for ScopeInnerErr {
+    fn from(_: thread::AccessError) -> Self {
+        Self::AccessError
+    }
+}
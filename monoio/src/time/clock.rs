@@ -1,15 +1,31 @@
 //! Source of time abstraction.
 //!
 //! By default, `std::time::Instant::now()` is used. However, when the
-//! `test-util` feature flag is enabled, the values returned for `now()` are
-//! configurable.
+//! `sim` feature flag is enabled, the simulation driver replaces it with a
+//! virtual clock on its thread, which only advances when the driver parks.
 
 use crate::time::Instant;
 
 #[derive(Default, Debug, Clone)]
 pub(crate) struct Clock {}
 
+#[cfg(feature = "sim")]
+thread_local! {
+    static VIRTUAL: std::cell::Cell<Option<std::time::Instant>> = const { std::cell::Cell::new(None) };
+}
+
+/// Sets the virtual time of the current thread, or goes back to the real one
+/// with `None`.
+#[cfg(feature = "sim")]
+pub(crate) fn set_virtual(now: Option<std::time::Instant>) {
+    VIRTUAL.with(|cell| cell.set(now));
+}
+
 pub(crate) fn now() -> Instant {
+    #[cfg(feature = "sim")]
+    if let Some(now) = VIRTUAL.with(std::cell::Cell::get) {
+        return Instant::from_std(now);
+    }
     Instant::from_std(std::time::Instant::now())
 }
 
@@ -24,6 +24,12 @@ impl Handle {
     pub(super) fn get(&self) -> &super::Inner {
         &*self.inner
     }
+
+    /// Returns the number of registered timers
+    #[allow(unused)]
+    pub(crate) fn timers(&self) -> usize {
+        self.inner.state.borrow().wheel.len()
+    }
 }
 
 impl Handle {
@@ -348,6 +348,12 @@ where
     }
 }
 
+impl<D: std::os::unix::prelude::AsRawFd> std::os::unix::prelude::AsRawFd for TimeDriver<D> {
+    fn as_raw_fd(&self) -> std::os::unix::prelude::RawFd {
+        self.park.as_raw_fd()
+    }
+}
+
 impl<D> Drop for TimeDriver<D>
 where
     D: 'static,
@@ -39,6 +39,9 @@ pub(crate) struct Wheel {
 
     /// Entries queued for firing
     pending: EntryList,
+
+    /// Number of registered entries
+    len: usize,
 }
 
 /// Number of levels. Each level has 64 slots. By using 6 levels with 64 slots
@@ -58,6 +61,7 @@ impl Wheel {
             elapsed: 0,
             levels,
             pending: EntryList::new(),
+            len: 0,
         }
     }
 
@@ -67,6 +71,12 @@ impl Wheel {
         self.elapsed
     }
 
+    /// Return the number of registered entries.
+    #[allow(unused)]
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
     /// Insert an entry into the timing wheel.
     ///
     /// # Arguments
@@ -104,6 +114,7 @@ impl Wheel {
         unsafe {
             self.levels[level].add_entry(item);
         }
+        self.len += 1;
 
         debug_assert!({
             self.levels[level]
@@ -134,6 +145,7 @@ impl Wheel {
                 self.levels[level].remove_entry(item);
             }
         }
+        self.len -= 1;
     }
 
     /// Instant at which to poll
@@ -145,6 +157,7 @@ impl Wheel {
     pub(crate) fn poll(&mut self, now: u64) -> Option<TimerHandle> {
         loop {
             if let Some(handle) = self.pending.pop_back() {
+                self.len -= 1;
                 return Some(handle);
             }
 
@@ -175,7 +188,11 @@ impl Wheel {
             }
         }
 
-        self.pending.pop_back()
+        let handle = self.pending.pop_back();
+        if handle.is_some() {
+            self.len -= 1;
+        }
+        handle
     }
 
     /// Returns the instant at which the next timeout expires.
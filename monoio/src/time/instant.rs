@@ -216,6 +216,6 @@ mod variant {
     use super::Instant;
 
     pub(super) fn now() -> Instant {
-        Instant::from_std(std::time::Instant::now())
+        crate::time::clock::now()
     }
 }
@@ -219,6 +219,21 @@ impl<L: Link> LinkedList<L, L::Target> {
         let tail = self.tail.as_ref()?;
         unsafe { Some(&*tail.as_ptr()) }
     }
+
+    /// Calls `f` on each node, from the head to the tail.
+    pub(crate) fn for_each<F>(&self, mut f: F)
+    where
+        F: FnMut(&L::Target),
+    {
+        let mut curr = self.head;
+        while let Some(node) = curr {
+            // safety: the pointer references data contained by the list
+            unsafe {
+                curr = L::pointers(node).as_ref().get_next();
+                f(node.as_ref());
+            }
+        }
+    }
 }
 
 impl<L: Link> Default for LinkedList<L, L::Target> {
@@ -4,9 +4,9 @@ pub(crate) mod linked_list;
 pub(crate) mod slab;
 pub(crate) mod uring_detect;
 
-mod rand;
+pub(crate) mod rand;
 pub use rand::thread_rng_n;
-pub use uring_detect::detect_uring;
+pub use uring_detect::{detect_uring, is_uring_op_supported};
 
 #[cfg(feature = "sync")]
 pub(crate) mod thread_id;
@@ -15,3 +15,10 @@ pub(crate) mod thread_id;
 mod bind_to_cpu_set;
 #[cfg(feature = "utils")]
 pub use bind_to_cpu_set::{bind_to_cpu_set, BindError};
+
+// `monoio::driver::op::read::Read<alloc::vec::Vec<u8>>` -> `Read`
+#[allow(unused)]
+pub(crate) fn short_name(name: &'static str) -> &'static str {
+    let name = name.split('<').next().unwrap_or(name);
+    name.rsplit("::").next().unwrap_or(name)
+}
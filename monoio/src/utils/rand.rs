@@ -39,6 +39,13 @@ impl FastRand {
         (mul >> 32) as u32
     }
 
+    /// Returns true with the probability `rate`.
+    #[allow(unused)]
+    pub(crate) fn chance(&self, rate: f64) -> bool {
+        const SCALE: u32 = 1 << 24;
+        rate > 0.0 && (self.fastrand_n(SCALE) as f64) < rate * SCALE as f64
+    }
+
     fn fastrand(&self) -> u32 {
         let mut s1 = self.one.get();
         let s0 = self.two.get();
@@ -43,6 +43,15 @@ impl<T> Slab<T> {
         })
     }
 
+    /// Iterate over keys and values of occupied slots.
+    #[allow(unused)]
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (usize, &T)> {
+        self.pages.iter().flatten().flat_map(|page| {
+            (0..page.initialized)
+                .filter_map(move |slot| page.get(slot).map(|val| (slot + page.prev_len, val)))
+        })
+    }
+
     pub(crate) fn get(&mut self, key: usize) -> Option<Ref<'_, T>> {
         let page_id = get_page_id(key);
         // here we make 2 mut ref so we must make it safe.
@@ -398,4 +407,25 @@ mod tests {
         });
         assert_eq!(slab.len(), 0);
     }
+
+    #[test]
+    fn iter() {
+        let mut slab = Slab::default();
+        let keys = (0..100).map(|i| slab.insert(i)).collect::<Vec<_>>();
+        for key in keys.iter().step_by(2) {
+            slab.remove(*key);
+        }
+        let left = slab
+            .iter()
+            .map(|(key, val)| (key, *val))
+            .collect::<Vec<_>>();
+        let expected = keys
+            .iter()
+            .copied()
+            .zip(0..100)
+            .skip(1)
+            .step_by(2)
+            .collect::<Vec<_>>();
+        assert_eq!(left, expected);
+    }
 }
@@ -20,18 +20,14 @@ macro_rules! err_to_false {
 }
 #[cfg(all(target_os = "linux", feature = "iouring"))]
 fn detect_uring_inner() -> bool {
-    const USED_OP: [u8; 14] = op_codes![
-        Accept,
+    // Other ops fall back to syscalls when their opcode is not supported.
+    const USED_OP: [u8; 9] = op_codes![
         AsyncCancel,
-        Close,
-        Connect,
         Fsync,
-        OpenAt,
+        PollAdd,
         ProvideBuffers,
         Read,
         Readv,
-        Recv,
-        Send,
         Timeout,
         Write,
         Writev
@@ -74,3 +70,17 @@ mod tests {
         )
     }
 }
+
+/// Returns whether the current driver supports the io_uring opcode `code`,
+/// e.g. `io_uring::opcode::Accept::CODE`. Opcodes are probed once per driver,
+/// and ops whose opcode is not supported fall back to a syscall waiting for
+/// readiness, or blocking for ops which do not wait for long.
+///
+/// Always false with the legacy driver.
+///
+/// # Panics
+///
+/// Panics if called outside a runtime.
+pub fn is_uring_op_supported(code: u8) -> bool {
+    crate::driver::CURRENT.with(|inner| inner.is_uring_op_supported(code))
+}
@@ -0,0 +1,65 @@
+use std::{cell::Cell, io::Write, rc::Rc};
+
+#[monoio::test_all]
+async fn yield_now() {
+    let ran = Rc::new(Cell::new(false));
+    let ran_clone = ran.clone();
+    monoio::spawn(async move { ran_clone.set(true) });
+
+    while !ran.get() {
+        monoio::task::yield_now().await;
+    }
+}
+
+// Legacy file reads are always ready, so the reading task only yields
+// because of its budget.
+#[monoio::test_all]
+async fn budget_prevents_starvation() {
+    let mut tempfile = tempfile::NamedTempFile::new().unwrap();
+    tempfile.write_all(b"hello world").unwrap();
+    let file = monoio::fs::File::open(tempfile.path()).await.unwrap();
+
+    let stop = Rc::new(Cell::new(false));
+    let stop_clone = stop.clone();
+    monoio::spawn(async move { stop_clone.set(true) });
+
+    let mut reads = 0;
+    let mut buf = vec![0; 11];
+    while !stop.get() {
+        let (res, b) = file.read_at(buf, 0).await;
+        assert_eq!(res.unwrap(), 11);
+        buf = b;
+        reads += 1;
+    }
+    assert!(reads > 0);
+}
+
+// The handles are ready, so the main future only yields because of its
+// budget, while no other task is queued.
+#[monoio::test_all]
+async fn budget_main_future() {
+    let handles: Vec<_> = (0..200).map(|i| monoio::spawn(async move { i })).collect();
+    monoio::task::yield_now().await;
+    for (i, handle) in handles.into_iter().enumerate() {
+        assert_eq!(handle.await, i);
+    }
+}
+
+#[monoio::test_all]
+async fn unconstrained() {
+    let mut tempfile = tempfile::NamedTempFile::new().unwrap();
+    tempfile.write_all(b"hello world").unwrap();
+    let file = monoio::fs::File::open(tempfile.path()).await.unwrap();
+
+    let reads = monoio::task::unconstrained(async {
+        let mut buf = vec![0; 11];
+        for _ in 0..1024 {
+            let (res, b) = file.read_at(buf, 0).await;
+            assert_eq!(res.unwrap(), 11);
+            buf = b;
+        }
+        1024
+    })
+    .await;
+    assert_eq!(reads, 1024);
+}
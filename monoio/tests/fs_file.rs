@@ -133,6 +133,140 @@ async fn sync_doesnt_kill_anything() {
     file.sync_data().await.unwrap();
 }
 
+#[monoio::test_all]
+async fn sync_range() {
+    use monoio::fs::SyncRangeFlags;
+
+    let tempfile = tempfile();
+
+    let file = File::create(tempfile.path()).await.unwrap();
+    file.write_at(HELLO, 0).await.0.unwrap();
+    file.sync_range(0, HELLO.len() as u64, SyncRangeFlags::WRITE)
+        .await
+        .unwrap();
+    file.sync_range(
+        0,
+        0,
+        SyncRangeFlags::WAIT_BEFORE | SyncRangeFlags::WRITE | SyncRangeFlags::WAIT_AFTER,
+    )
+    .await
+    .unwrap();
+    file.sync_data_range(0, HELLO.len() as u64).await.unwrap();
+    file.sync_data_range(0, u64::MAX).await.unwrap();
+}
+
+#[monoio::test_all]
+async fn write_and_sync() {
+    let tempfile = tempfile();
+
+    let file = File::create(tempfile.path()).await.unwrap();
+    let (res, buf) = file.write_and_sync_data_at(HELLO, 0).await;
+    assert_eq!(res.unwrap(), HELLO.len());
+    assert_eq!(buf, HELLO);
+
+    let file = std::fs::read(tempfile.path()).unwrap();
+    assert_eq!(file, HELLO);
+}
+
+#[monoio::test_all]
+async fn write_and_sync_read_only() {
+    let mut tempfile = tempfile();
+    tempfile.write_all(HELLO).unwrap();
+
+    let file = File::open(tempfile.path()).await.unwrap();
+    let (res, _) = file.write_and_sync_data_at(HELLO, 0).await;
+    assert!(res.is_err());
+}
+
+#[monoio::test_all]
+async fn set_len() {
+    let tempfile = tempfile();
+
+    let file = File::create(tempfile.path()).await.unwrap();
+    file.write_all_at(HELLO, 0).await.0.unwrap();
+
+    file.set_len(5).await.unwrap();
+    assert_eq!(std::fs::read(tempfile.path()).unwrap(), &HELLO[..5]);
+
+    file.set_len(8).await.unwrap();
+    assert_eq!(std::fs::read(tempfile.path()).unwrap(), b"hello\0\0\0");
+}
+
+#[cfg(target_os = "linux")]
+#[monoio::test_all]
+async fn allocate() {
+    use monoio::fs::AllocateMode;
+
+    let tempfile = tempfile();
+
+    let file = File::create(tempfile.path()).await.unwrap();
+    file.allocate(0, 4096, AllocateMode::KeepSize)
+        .await
+        .unwrap();
+    assert_eq!(std::fs::metadata(tempfile.path()).unwrap().len(), 0);
+
+    file.allocate(0, 4096, AllocateMode::Allocate)
+        .await
+        .unwrap();
+    assert_eq!(std::fs::metadata(tempfile.path()).unwrap().len(), 4096);
+
+    file.write_all_at(&[1; 4096][..], 0).await.0.unwrap();
+    file.allocate(0, 4096, AllocateMode::PunchHole)
+        .await
+        .unwrap();
+    assert_eq!(std::fs::read(tempfile.path()).unwrap(), vec![0; 4096]);
+}
+
+#[monoio::test_all]
+async fn advise() {
+    use monoio::fs::Advice;
+
+    let mut tempfile = tempfile();
+    tempfile.write_all(HELLO).unwrap();
+
+    let file = File::open(tempfile.path()).await.unwrap();
+    file.advise(0, 0, Advice::Sequential).await.unwrap();
+    file.advise(0, 0, Advice::WillNeed).await.unwrap();
+    read_hello(&file).await;
+    file.advise(0, 0, Advice::DontNeed).await.unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[monoio::test_all]
+async fn direct_io() {
+    use monoio::{buf::AlignedBuf, fs::OpenOptions};
+
+    // tmpfs may not support O_DIRECT, use the target dir instead.
+    let tempfile = NamedTempFile::new_in(env!("CARGO_TARGET_TMPDIR")).unwrap();
+    let file = match OpenOptions::new()
+        .read(true)
+        .write(true)
+        .custom_flags(libc::O_DIRECT)
+        .open(tempfile.path())
+        .await
+    {
+        Ok(file) => file,
+        Err(e) if e.raw_os_error() == Some(libc::EINVAL) => return,
+        Err(e) => panic!("open with O_DIRECT failed: {}", e),
+    };
+    let align = file.logical_block_size().unwrap();
+
+    let mut buf = AlignedBuf::new(align, align);
+    buf.extend_from_slice(&vec![b'x'; align]);
+    file.write_all_at(buf, 0).await.0.unwrap();
+
+    let buf = AlignedBuf::new(align, align);
+    let (res, buf) = file.read_at(buf, 0).await;
+    assert_eq!(res.unwrap(), align);
+    assert!(buf.iter().all(|b| *b == b'x'));
+
+    #[cfg(debug_assertions)]
+    {
+        let (res, _) = file.write_at(vec![0; align / 2], 0).await;
+        assert_eq!(res.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+    }
+}
+
 fn tempfile() -> NamedTempFile {
     NamedTempFile::new().expect("unable to create tempfile")
 }
@@ -0,0 +1,108 @@
+use monoio::fs::File;
+use std::io::prelude::*;
+use tempfile::NamedTempFile;
+
+const HELLO: &[u8] = b"hello world...";
+
+#[monoio::test_all]
+async fn write_then_sync() {
+    let tempfile = tempfile();
+    let file = File::create(tempfile.path()).await.unwrap();
+
+    let (results, bufs) = monoio::io::chain()
+        .write_at(&file, HELLO[..5].to_vec(), 0)
+        .write_at(&file, HELLO[5..].to_vec(), 5)
+        .sync_data(&file)
+        .submit()
+        .await;
+    assert_eq!(results.len(), 3);
+    assert_eq!(*results[0].as_ref().unwrap(), 5);
+    assert_eq!(*results[1].as_ref().unwrap(), HELLO.len() - 5);
+    assert_eq!(*results[2].as_ref().unwrap(), 0);
+    assert_eq!(
+        bufs,
+        vec![Some(HELLO[..5].to_vec()), Some(HELLO[5..].to_vec()), None]
+    );
+
+    let file = std::fs::read(tempfile.path()).unwrap();
+    assert_eq!(file, HELLO);
+}
+
+#[monoio::test_all]
+async fn read_after_write() {
+    let tempfile = tempfile();
+    let file = monoio::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(tempfile.path())
+        .await
+        .unwrap();
+
+    let (results, bufs) = monoio::io::chain()
+        .write_at(&file, HELLO.to_vec(), 0)
+        .read_at(&file, Vec::with_capacity(HELLO.len()), 0)
+        .submit()
+        .await;
+    assert!(results.iter().all(|r| r.is_ok()));
+    assert_eq!(bufs[1].as_deref(), Some(HELLO));
+}
+
+#[monoio::test_all]
+async fn failure_cancels_rest() {
+    let mut tempfile = tempfile();
+    tempfile.write_all(HELLO).unwrap();
+    // Writing to a read-only file fails.
+    let file = File::open(tempfile.path()).await.unwrap();
+
+    let (results, bufs) = monoio::io::chain()
+        .write_at(&file, HELLO.to_vec(), 0)
+        .read_at(&file, Vec::with_capacity(HELLO.len()), 0)
+        .submit()
+        .await;
+    assert!(results[0].is_err());
+    assert_eq!(
+        results[1].as_ref().unwrap_err().raw_os_error(),
+        Some(libc::ECANCELED)
+    );
+    assert_eq!(bufs.len(), 2);
+    assert_eq!(bufs[0].as_deref(), Some(HELLO));
+    assert!(bufs[1].as_ref().unwrap().is_empty());
+
+    let (results, bufs) = monoio::io::chain()
+        .hard_link(true)
+        .write_at(&file, HELLO.to_vec(), 0)
+        .read_at(&file, Vec::with_capacity(HELLO.len()), 0)
+        .submit()
+        .await;
+    assert!(results[0].is_err());
+    assert_eq!(*results[1].as_ref().unwrap(), HELLO.len());
+    assert_eq!(bufs[1].as_deref(), Some(HELLO));
+}
+
+#[monoio::test_all]
+async fn short_recv_keeps_going() {
+    use monoio::{
+        io::{AsyncReadRentExt, AsyncWriteRentExt},
+        net::UnixStream,
+    };
+
+    let (a, mut b) = UnixStream::pair().unwrap();
+    b.write_all(b"hi").await.0.unwrap();
+    let (results, bufs) = monoio::io::chain()
+        .recv(&a, Vec::with_capacity(8))
+        .send(&a, b"ok".to_vec())
+        .submit()
+        .await;
+    // As with the kernel, a short recv does not break the chain.
+    assert_eq!(*results[0].as_ref().unwrap(), 2);
+    assert_eq!(*results[1].as_ref().unwrap(), 2);
+    assert_eq!(bufs[0].as_deref(), Some(&b"hi"[..]));
+
+    let (res, buf) = b.read_exact(vec![0; 2]).await;
+    res.unwrap();
+    assert_eq!(buf, b"ok");
+}
+
+fn tempfile() -> NamedTempFile {
+    NamedTempFile::new().expect("unable to create tempfile")
+}
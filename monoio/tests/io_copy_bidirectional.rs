@@ -0,0 +1,48 @@
+use std::time::Duration;
+
+use monoio::{
+    io::{
+        copy_bidirectional, copy_bidirectional_with_idle_timeout, AsyncReadRent,
+        AsyncReadRentExt, AsyncWriteRent, AsyncWriteRentExt,
+    },
+    net::UnixStream,
+};
+
+#[monoio::test_all]
+async fn copy_both_ways() -> std::io::Result<()> {
+    let (mut client, a) = UnixStream::pair()?;
+    let (b, mut server) = UnixStream::pair()?;
+    let proxy = monoio::spawn(copy_bidirectional(a, b));
+
+    client.write_all(b"ping").await.0?;
+    let (res, buf) = server.read_exact(vec![0; 4]).await;
+    res?;
+    assert_eq!(&buf, b"ping");
+
+    server.write_all(b"pong!").await.0?;
+    let (res, buf) = client.read_exact(vec![0; 5]).await;
+    res?;
+    assert_eq!(&buf, b"pong!");
+
+    // The shutdown of the client is propagated to the server.
+    client.shutdown().await?;
+    assert_eq!(server.read(vec![0; 1]).await.0?, 0);
+    server.shutdown().await?;
+    assert_eq!(client.read(vec![0; 1]).await.0?, 0);
+
+    assert_eq!(proxy.await?, (4, 5));
+    Ok(())
+}
+
+#[monoio::test_all(timer_enabled = true)]
+async fn idle_timeout() -> std::io::Result<()> {
+    let (mut client, a) = UnixStream::pair()?;
+    let (b, _server) = UnixStream::pair()?;
+    client.write_all(b"ping").await.0?;
+
+    let err = copy_bidirectional_with_idle_timeout(a, b, Duration::from_millis(50))
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use monoio::io::{pipe, AsyncReadRent, AsyncReadRentExt, AsyncWriteRentExt, Receiver, Sender};
+
+#[monoio::test_all]
+async fn pipe_read_write() -> std::io::Result<()> {
+    let (mut tx, mut rx) = pipe()?;
+    tx.write_all(b"hello").await.0?;
+
+    let (res, buf) = rx.read_exact(vec![0; 5]).await;
+    res?;
+    assert_eq!(&buf, b"hello");
+
+    drop(tx);
+    assert_eq!(rx.read(vec![0; 1]).await.0?, 0);
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[monoio::test_all]
+async fn pipe_size() -> std::io::Result<()> {
+    let (tx, rx) = pipe()?;
+    let size = tx.set_pipe_size(128 * 1024)?;
+    assert!(size >= 128 * 1024);
+    assert_eq!(rx.pipe_size()?, size);
+    Ok(())
+}
+
+#[monoio::test_all]
+async fn fifo() -> std::io::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("fifo");
+    let c_path = std::ffi::CString::new(path.to_str().unwrap()).unwrap();
+    assert_eq!(unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) }, 0);
+
+    // Opening for writing requires a reader.
+    assert!(Sender::open(&path).is_err());
+    let mut rx = Receiver::open(&path)?;
+    let mut tx = Sender::open(&path)?;
+
+    tx.write_all(b"fifo").await.0?;
+    let (res, buf) = rx.read_exact(vec![0; 4]).await;
+    res?;
+    assert_eq!(&buf, b"fifo");
+
+    // Not a FIFO.
+    assert!(Receiver::open(dir.path()).is_err());
+    Ok(())
+}
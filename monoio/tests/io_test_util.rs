@@ -0,0 +1,145 @@
+#![cfg(feature = "test-util")]
+
+use std::io::ErrorKind;
+
+use monoio::{
+    io::{
+        test_util::{duplex, FaultConfig, FaultyIo},
+        AsyncReadRent, AsyncReadRentExt, AsyncWriteRent, AsyncWriteRentExt,
+    },
+    time::Duration,
+};
+
+#[monoio::test_all]
+async fn duplex_read_write() {
+    let (mut a, mut b) = duplex(8);
+    a.write_all(b"ping").await.0.unwrap();
+    let (res, buf) = b.read_exact(vec![0; 4]).await;
+    res.unwrap();
+    assert_eq!(buf, b"ping");
+
+    b.write_all(b"pong").await.0.unwrap();
+    b.shutdown().await.unwrap();
+    let (res, buf) = a.read_exact(vec![0; 4]).await;
+    res.unwrap();
+    assert_eq!(buf, b"pong");
+    assert_eq!(a.read(vec![0; 4]).await.0.unwrap(), 0);
+}
+
+#[monoio::test_all]
+async fn duplex_full() {
+    let (mut a, mut b) = duplex(4);
+    // Only the first 4 bytes fit, the rest waits for the reader.
+    let writer = monoio::spawn(async move {
+        a.write_all(b"hello world").await.0.unwrap();
+    });
+    let (res, buf) = b.read_exact(vec![0; 11]).await;
+    res.unwrap();
+    assert_eq!(buf, b"hello world");
+    writer.await;
+    assert_eq!(b.read(vec![0; 4]).await.0.unwrap(), 0);
+}
+
+#[monoio::test_all]
+async fn duplex_broken_pipe() {
+    let (mut a, b) = duplex(8);
+    drop(b);
+    let (res, _) = a.write(b"ping").await;
+    assert_eq!(res.unwrap_err().kind(), ErrorKind::BrokenPipe);
+}
+
+#[monoio::test_all]
+async fn short_io() {
+    let (a, mut b) = duplex(64);
+    let mut a = FaultyIo::new(a, FaultConfig::new().short_io_rate(1.0));
+    // Every write is short.
+    let (res, _) = a.write(b"hello world").await;
+    let n = res.unwrap();
+    assert!(n > 0 && n < 11);
+    a.write_all(&b"hello world"[n..]).await.0.unwrap();
+    let (res, buf) = b.read_exact(vec![0; 11]).await;
+    res.unwrap();
+    assert_eq!(buf, b"hello world");
+
+    b.write_all(b"hello world").await.0.unwrap();
+    let (res, buf) = a.read(vec![0; 11]).await;
+    assert!(res.unwrap() < 11);
+    assert!(b"hello world".starts_with(&buf));
+}
+
+#[monoio::test_all]
+async fn interrupted() {
+    let (a, mut b) = duplex(64);
+    let mut a = FaultyIo::new(a, FaultConfig::new().interrupted_rate(1.0));
+    let (res, _) = a.write(b"ping").await;
+    assert_eq!(res.unwrap_err().kind(), ErrorKind::Interrupted);
+    let (res, _) = a.read(vec![0; 4]).await;
+    assert_eq!(res.unwrap_err().kind(), ErrorKind::Interrupted);
+
+    // Nothing was written.
+    drop(a);
+    assert_eq!(b.read(vec![0; 4]).await.0.unwrap(), 0);
+}
+
+#[monoio::test_all]
+async fn would_block() {
+    let (a, _b) = duplex(64);
+    let mut a = FaultyIo::new(a, FaultConfig::new().would_block_rate(1.0));
+    let (res, _) = a.write(b"ping").await;
+    assert_eq!(res.unwrap_err().kind(), ErrorKind::WouldBlock);
+    assert_eq!(a.flush().await.unwrap_err().kind(), ErrorKind::WouldBlock);
+}
+
+#[monoio::test_all(timer_enabled = true)]
+async fn delay() {
+    let (a, mut b) = duplex(64);
+    let config = FaultConfig::new().max_delay(Duration::from_millis(20));
+    let mut a = FaultyIo::new(a, config);
+    // The ops complete once their delay elapses.
+    for _ in 0..10 {
+        a.write_all(b"ping").await.0.unwrap();
+    }
+    let (res, buf) = b.read_exact(vec![0; 40]).await;
+    res.unwrap();
+    assert_eq!(buf, b"ping".repeat(10));
+}
+
+// Returns the sizes of the reads, and the errors as 0.
+async fn read_sizes(seed: u64) -> Vec<usize> {
+    let (mut a, b) = duplex(1024);
+    let config = FaultConfig::new()
+        .seed(seed)
+        .short_io_rate(0.5)
+        .interrupted_rate(0.2);
+    let mut b = FaultyIo::new(b, config);
+    a.write_all(vec![7; 1000]).await.0.unwrap();
+    drop(a);
+
+    let mut sizes = Vec::new();
+    let mut buf = Vec::with_capacity(10);
+    loop {
+        let (res, buf_) = b.read(buf).await;
+        buf = buf_;
+        match res {
+            Ok(0) => return sizes,
+            Ok(n) => {
+                assert!(buf.iter().all(|&x| x == 7));
+                sizes.push(n);
+            }
+            Err(e) => {
+                assert_eq!(e.kind(), ErrorKind::Interrupted);
+                sizes.push(0);
+            }
+        }
+        buf.clear();
+    }
+}
+
+#[monoio::test_all]
+async fn seeded() {
+    let sizes = read_sizes(7).await;
+    assert_eq!(sizes.iter().sum::<usize>(), 1000);
+    assert!(sizes.contains(&0));
+    assert!(sizes.iter().any(|&n| n > 0 && n < 10));
+    assert_eq!(sizes, read_sizes(7).await);
+}
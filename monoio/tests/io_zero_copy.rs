@@ -0,0 +1,29 @@
+use monoio::{
+    io::{zero_copy, AsyncReadRentExt, AsyncWriteRent, AsyncWriteRentExt},
+    net::UnixStream,
+};
+
+#[monoio::test_all]
+async fn zero_copy_unix() -> std::io::Result<()> {
+    let (mut client, mut from) = UnixStream::pair()?;
+    let (mut to, mut server) = UnixStream::pair()?;
+
+    let data: Vec<u8> = (0..200 * 1024).map(|i| i as u8).collect();
+    let expected = data.clone();
+    let writer = monoio::spawn(async move {
+        let (res, _) = client.write_all(data).await;
+        res.unwrap();
+        client.shutdown().await.unwrap();
+    });
+    let reader = monoio::spawn(async move {
+        let (res, buf) = server.read_exact(vec![0; expected.len()]).await;
+        res.unwrap();
+        assert_eq!(buf, expected);
+    });
+
+    let n = zero_copy(&mut from, &mut to).await?;
+    assert_eq!(n, 200 * 1024);
+    writer.await;
+    reader.await;
+    Ok(())
+}
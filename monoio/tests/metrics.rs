@@ -0,0 +1,60 @@
+#![cfg(feature = "metrics")]
+
+use std::time::Duration;
+
+use monoio::RuntimeBuilder;
+
+#[monoio::test_all(timer_enabled = true)]
+async fn current() {
+    let before = monoio::metrics::current();
+    monoio::spawn(async {}).await;
+    let sleep = monoio::time::sleep(Duration::from_secs(60));
+    monoio::pin!(sleep);
+    let _ = monoio::time::timeout(Duration::from_millis(1), &mut sleep).await;
+
+    let after = monoio::metrics::current();
+    assert_eq!(after.tasks_spawned, before.tasks_spawned + 1);
+    assert_eq!(after.tasks_completed, before.tasks_completed + 1);
+    assert!(after.polls > before.polls);
+    assert!(after.parks > before.parks);
+    assert_eq!(after.timers, 1);
+}
+
+#[test]
+fn from_outside() {
+    let mut rt = RuntimeBuilder::<monoio::FusionDriver>::new()
+        .build()
+        .unwrap();
+    rt.block_on(async {
+        let (tx, mut rx) = monoio::io::pipe().unwrap();
+        monoio::spawn(async move {
+            use monoio::io::AsyncReadRent;
+            let _ = rx.read(vec![0; 8]).await;
+        });
+        monoio::spawn(async move {
+            let _tx = tx;
+            std::future::pending::<()>().await
+        });
+        // Let the reader submit its op.
+        monoio::spawn(async {}).await;
+        let metrics = monoio::metrics::current();
+        assert_eq!(metrics.ops, vec![("Read", 1)]);
+        assert_eq!(metrics.in_flight_ops, 1);
+    });
+    let metrics = rt.metrics();
+    assert_eq!(metrics.tasks_spawned, 3);
+    assert_eq!(metrics.task_queue_depth, 0);
+}
+
+#[monoio::test_all]
+async fn queues() {
+    use monoio::task::Priority;
+
+    let before = monoio::metrics::current();
+    monoio::spawn_with_priority(Priority::Low, async {}).await;
+    let after = monoio::metrics::current();
+    assert_eq!(after.queues.len(), 3);
+    assert_eq!(after.queues[2].priority, Priority::Low);
+    assert_eq!(after.queues[2].polls, before.queues[2].polls + 1);
+    assert_eq!(after.queues[0].polls, before.queues[0].polls);
+}
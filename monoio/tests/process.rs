@@ -0,0 +1,81 @@
+use std::process::Stdio;
+
+use monoio::{
+    io::{AsyncReadRentExt, AsyncWriteRentExt},
+    process::Command,
+};
+
+#[monoio::test_all(timer_enabled = true)]
+async fn output() -> std::io::Result<()> {
+    let output = Command::new("sh")
+        .args(["-c", "echo out; echo err >&2; exit 3"])
+        .output()
+        .await?;
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(output.stdout, b"out\n");
+    assert_eq!(output.stderr, b"err\n");
+    Ok(())
+}
+
+#[monoio::test_all(timer_enabled = true)]
+async fn piped_stdio() -> std::io::Result<()> {
+    let mut child = Command::new("cat")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+
+    let mut stdin = child.stdin.take().unwrap();
+    stdin.write_all(b"hello").await.0?;
+    drop(stdin);
+
+    let mut stdout = child.stdout.take().unwrap();
+    let (res, buf) = stdout.read_exact(vec![0; 5]).await;
+    res?;
+    assert_eq!(&buf, b"hello");
+
+    assert!(child.wait().await?.success());
+    Ok(())
+}
+
+#[monoio::test_all(timer_enabled = true)]
+async fn wait_with_output_piped_stdin() -> std::io::Result<()> {
+    let mut child = Command::new("cat")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+
+    child.stdin.as_mut().unwrap().write_all(b"hello").await.0?;
+    let output = child.wait_with_output().await?;
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"hello");
+    Ok(())
+}
+
+#[monoio::test_all(timer_enabled = true)]
+async fn kill() -> std::io::Result<()> {
+    let mut child = Command::new("sleep").arg("100").spawn()?;
+    assert!(child.try_wait()?.is_none());
+    child.kill()?;
+    let status = child.wait().await?;
+    assert!(!status.success());
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[monoio::test_all(timer_enabled = true)]
+async fn kill_on_drop() -> std::io::Result<()> {
+    let child = Command::new("sleep")
+        .arg("100")
+        .kill_on_drop(true)
+        .spawn()?;
+    let proc = format!("/proc/{}", child.id());
+    drop(child);
+    // The child is killed, then reaped so it does not stay a zombie.
+    for _ in 0..500 {
+        if !std::path::Path::new(&proc).exists() {
+            return Ok(());
+        }
+        monoio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    panic!("the child was not reaped");
+}
@@ -0,0 +1,38 @@
+#![cfg(feature = "sync")]
+
+use std::rc::Rc;
+
+use monoio::{RuntimeBuilder, RuntimeHandle};
+
+// The runtime thread runs until the test process exits.
+fn spawn_runtime() -> RuntimeHandle {
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let mut rt = RuntimeBuilder::<monoio::FusionDriver>::new()
+            .build()
+            .unwrap();
+        tx.send(rt.handle()).unwrap();
+        rt.block_on(std::future::pending::<()>());
+    });
+    rx.recv().unwrap()
+}
+
+#[test]
+fn block_on_from_outside() {
+    let handle = spawn_runtime();
+    let len = handle.block_on(|| async {
+        let rc = Rc::new(vec![1, 2, 3]);
+        monoio::spawn(async move { rc.len() }).await
+    });
+    assert_eq!(len, Some(3));
+}
+
+#[monoio::test_all]
+async fn spawn_from_runtime() {
+    let handle = spawn_runtime();
+    let id = handle
+        .clone()
+        .spawn(|| async { std::thread::current().id() })
+        .await;
+    assert_ne!(id, Some(std::thread::current().id()));
+}
@@ -0,0 +1,115 @@
+use std::{
+    cell::Cell,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use monoio::{
+    task::{Builder, Priority, TaskMeta},
+    RuntimeBuilder,
+};
+
+#[test]
+fn builder_hooks() {
+    let started = Arc::new(AtomicUsize::new(0));
+    let parks = Arc::new(AtomicUsize::new(0));
+    let unparks = Arc::new(AtomicUsize::new(0));
+    let spawns = Arc::new(AtomicUsize::new(0));
+
+    let mut rt = RuntimeBuilder::<monoio::FusionDriver>::new()
+        .on_thread_start({
+            let started = started.clone();
+            move || {
+                started.fetch_add(1, Ordering::Relaxed);
+            }
+        })
+        .before_park({
+            let parks = parks.clone();
+            move || {
+                parks.fetch_add(1, Ordering::Relaxed);
+            }
+        })
+        .after_unpark({
+            let unparks = unparks.clone();
+            move || {
+                unparks.fetch_add(1, Ordering::Relaxed);
+            }
+        })
+        .on_task_spawn({
+            let spawns = spawns.clone();
+            move |_| {
+                spawns.fetch_add(1, Ordering::Relaxed);
+            }
+        })
+        .enable_timer()
+        .build()
+        .unwrap();
+    assert_eq!(started.load(Ordering::Relaxed), 1);
+
+    rt.block_on(async {
+        monoio::spawn(async {}).await;
+        monoio::spawn(async {}).await;
+        monoio::time::sleep(std::time::Duration::from_millis(1)).await;
+    });
+    assert_eq!(spawns.load(Ordering::Relaxed), 2);
+    assert!(parks.load(Ordering::Relaxed) > 0);
+    assert_eq!(
+        parks.load(Ordering::Relaxed),
+        unparks.load(Ordering::Relaxed)
+    );
+}
+
+#[test]
+fn task_spawn_meta() {
+    let spawned = Arc::new(Mutex::new(Vec::new()));
+    let mut rt = RuntimeBuilder::<monoio::FusionDriver>::new()
+        .on_task_spawn({
+            let spawned = spawned.clone();
+            move |meta: &TaskMeta<'_>| {
+                spawned.lock().unwrap().push((
+                    meta.id,
+                    meta.name.map(str::to_owned),
+                    meta.location.file(),
+                    meta.location.line(),
+                    meta.priority,
+                ));
+            }
+        })
+        .build()
+        .unwrap();
+
+    let line = line!() + 2;
+    rt.block_on(async {
+        monoio::spawn(async {}).await;
+        Builder::new()
+            .name("named")
+            .priority(Priority::High)
+            .spawn(async {})
+            .await;
+    });
+    let spawned = spawned.lock().unwrap();
+    assert_eq!(spawned.len(), 2);
+    assert_ne!(spawned[0].0, spawned[1].0);
+    assert_eq!(spawned[0].1, None);
+    assert_eq!(spawned[0].2, file!());
+    assert_eq!(spawned[0].3, line);
+    assert_eq!(spawned[0].4, Priority::Normal);
+    assert_eq!(spawned[1].1.as_deref(), Some("named"));
+    assert_eq!(spawned[1].2, file!());
+    assert_eq!(spawned[1].4, Priority::High);
+}
+
+thread_local! {
+    static STARTED: Cell<bool> = Cell::new(false);
+}
+
+fn mark_started() {
+    STARTED.with(|s| s.set(true));
+}
+
+#[monoio::test_all(on_thread_start = "mark_started")]
+async fn macro_hooks() {
+    assert!(STARTED.with(|s| s.get()));
+}
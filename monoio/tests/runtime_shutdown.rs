@@ -0,0 +1,72 @@
+use std::{cell::Cell, rc::Rc, time::Duration};
+
+use monoio::{io::AsyncReadRent, RuntimeBuilder};
+
+#[test]
+fn token_completes_on_shutdown() {
+    let mut rt = RuntimeBuilder::<monoio::FusionDriver>::new()
+        .enable_timer()
+        .build()
+        .unwrap();
+    let cleaned = Rc::new(Cell::new(false));
+    let cleaned_clone = cleaned.clone();
+    rt.block_on(async move {
+        monoio::spawn(async move {
+            let token = monoio::shutdown_token();
+            assert!(!token.is_shutdown());
+            token.await;
+            // Cleanup may still do io.
+            monoio::time::sleep(Duration::from_millis(10)).await;
+            cleaned_clone.set(true);
+        });
+        monoio::time::sleep(Duration::from_millis(10)).await;
+    });
+    rt.shutdown_timeout(Duration::from_secs(5));
+    assert!(cleaned.get());
+}
+
+#[test]
+fn in_flight_ops_canceled() {
+    let mut rt = RuntimeBuilder::<monoio::FusionDriver>::new()
+        .enable_timer()
+        .build()
+        .unwrap();
+    let (tx, rx) = std::os::unix::net::UnixStream::pair().unwrap();
+    rt.block_on(async move {
+        monoio::spawn(async move {
+            let mut rx = monoio::net::UnixStream::from_std(rx).unwrap();
+            // Never completes, the peer never writes.
+            let _ = rx.read(vec![0; 16]).await;
+        });
+        monoio::time::sleep(Duration::from_millis(10)).await;
+    });
+    let begin = std::time::Instant::now();
+    rt.shutdown_timeout(Duration::from_millis(50));
+    assert!(begin.elapsed() < Duration::from_secs(5));
+    drop(tx);
+}
+
+#[test]
+fn spawn_during_shutdown() {
+    let mut rt = RuntimeBuilder::<monoio::FusionDriver>::new()
+        .enable_timer()
+        .build()
+        .unwrap();
+    let ran = Rc::new(Cell::new(false));
+    let cancelled = Rc::new(Cell::new(false));
+    let (ran_clone, cancelled_clone) = (ran.clone(), cancelled.clone());
+    rt.block_on(async move {
+        monoio::spawn(async move {
+            monoio::shutdown_token().await;
+            // Tasks spawned while shutting down are not run.
+            let handle = monoio::spawn(async move { ran_clone.set(true) });
+            cancelled_clone.set(handle.is_cancelled());
+        });
+        monoio::time::sleep(Duration::from_millis(10)).await;
+    });
+    let begin = std::time::Instant::now();
+    rt.shutdown_timeout(Duration::from_secs(5));
+    assert!(begin.elapsed() < Duration::from_secs(1));
+    assert!(cancelled.get());
+    assert!(!ran.get());
+}
@@ -0,0 +1,119 @@
+use std::{cell::Cell, rc::Rc};
+
+use monoio::task::JoinSet;
+
+#[monoio::test_all]
+async fn scope_borrows() {
+    let mut values = vec![1, 2, 3];
+    let sum = Cell::new(0);
+
+    monoio::task::scope(|s| {
+        let sum = &sum;
+        let values = &mut values;
+        async move {
+            let inner = s.clone();
+            s.spawn(async move {
+                monoio::task::yield_now().await;
+                // Tasks can spawn tasks in the same scope.
+                inner.spawn(async move { sum.set(sum.get() + 10) });
+            });
+            let len = s.spawn(async move {
+                values.push(4);
+                values.len()
+            });
+            assert_eq!(len.await, 4);
+        }
+    })
+    .await;
+
+    assert_eq!(sum.get(), 10);
+    assert_eq!(values, [1, 2, 3, 4]);
+}
+
+// The scope waits for the detached tasks before it completes.
+#[monoio::test_all(timer_enabled = true)]
+async fn scope_waits() {
+    let done = Cell::new(false);
+    let output = monoio::task::scope(|s| {
+        let done = &done;
+        async move {
+            s.spawn(async move {
+                monoio::time::sleep(std::time::Duration::from_millis(10)).await;
+                done.set(true);
+            });
+            "body"
+        }
+    })
+    .await;
+    assert_eq!(output, "body");
+    assert!(done.get());
+}
+
+#[monoio::test_all]
+async fn scope_cancel() {
+    let dropped = Rc::new(Cell::new(false));
+    struct SetOnDrop(Rc<Cell<bool>>);
+    impl Drop for SetOnDrop {
+        fn drop(&mut self) {
+            self.0.set(true);
+        }
+    }
+
+    let guard = SetOnDrop(dropped.clone());
+    let scope = monoio::task::scope(|s| async move {
+        s.spawn(async move {
+            let _guard = guard;
+            std::future::pending::<()>().await;
+        });
+        monoio::task::yield_now().await;
+    });
+    let mut scope = Box::pin(scope);
+    let _ = futures::poll!(scope.as_mut());
+    assert!(!dropped.get());
+    drop(scope);
+    assert!(dropped.get());
+}
+
+#[monoio::test_all]
+async fn join_set() {
+    let mut set = JoinSet::new();
+    for i in 0..8 {
+        set.spawn(async move {
+            for _ in 0..i {
+                monoio::task::yield_now().await;
+            }
+            i
+        });
+    }
+    assert_eq!(set.len(), 8);
+
+    let mut outputs = Vec::new();
+    while let Some(i) = set.join_next().await {
+        outputs.push(i);
+    }
+    assert_eq!(outputs, (0..8).collect::<Vec<_>>());
+    assert!(set.is_empty());
+}
+
+#[monoio::test_all]
+async fn join_set_abort_all() {
+    let mut set = JoinSet::new();
+    for _ in 0..4 {
+        set.spawn(std::future::pending::<()>());
+    }
+    set.spawn(async {});
+    monoio::task::yield_now().await;
+
+    set.abort_all();
+    let mut joined = 0;
+    while set.join_next().await.is_some() {
+        joined += 1;
+    }
+    // Only the task which completed before the abort is returned.
+    assert_eq!(joined, 1);
+    assert!(set.is_empty());
+
+    // The set can be reused after an abort.
+    set.spawn(async {});
+    assert!(set.join_next().await.is_some());
+}
@@ -0,0 +1,54 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Once,
+};
+
+use monoio::signal::{signal, SignalKind};
+
+#[monoio::test_all]
+async fn recv() -> std::io::Result<()> {
+    let mut sig = signal(SignalKind::user_defined1())?;
+    unsafe { libc::raise(libc::SIGUSR1) };
+    assert_eq!(sig.recv().await, Some(()));
+    Ok(())
+}
+
+#[monoio::test_all]
+async fn fan_out() -> std::io::Result<()> {
+    let mut first = signal(SignalKind::user_defined2())?;
+    let mut second = signal(SignalKind::user_defined2())?;
+    unsafe { libc::raise(libc::SIGUSR2) };
+    assert_eq!(first.recv().await, Some(()));
+    assert_eq!(second.recv().await, Some(()));
+    Ok(())
+}
+
+static CHAINED: AtomicUsize = AtomicUsize::new(0);
+
+extern "C" fn count(_: libc::c_int) {
+    CHAINED.fetch_add(1, Ordering::SeqCst);
+}
+
+#[monoio::test_all]
+async fn chain_previous_handler() -> std::io::Result<()> {
+    // Installed before the first listener only, it would replace ours after.
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| unsafe {
+        libc::signal(
+            libc::SIGWINCH,
+            count as extern "C" fn(libc::c_int) as libc::sighandler_t,
+        );
+    });
+
+    let mut sig = signal(SignalKind::window_change())?;
+    let before = CHAINED.load(Ordering::SeqCst);
+    unsafe { libc::raise(libc::SIGWINCH) };
+    assert_eq!(sig.recv().await, Some(()));
+    assert_eq!(CHAINED.load(Ordering::SeqCst), before + 1);
+    Ok(())
+}
+
+#[test]
+fn forbidden() {
+    assert!(signal(SignalKind::from_raw(libc::SIGKILL)).is_err());
+}
@@ -0,0 +1,203 @@
+#![cfg(feature = "sim")]
+
+use std::{cell::RefCell, future::Future, io::ErrorKind, rc::Rc};
+
+use monoio::{
+    io::{AsyncReadRent, AsyncReadRentExt, AsyncWriteRent, AsyncWriteRentExt},
+    sim::{
+        net::{TcpListener, TcpStream, UnixListener, UnixStream},
+        SimConfig,
+    },
+    time::{Duration, Instant},
+    RuntimeBuilder, SimDriver,
+};
+
+fn run<F: Future>(config: SimConfig, future: F) -> F::Output {
+    RuntimeBuilder::<SimDriver>::new()
+        .with_sim_config(config)
+        .enable_timer()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+async fn echo(listener: TcpListener) {
+    loop {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        monoio::spawn(async move {
+            let mut buf = Vec::with_capacity(64);
+            loop {
+                let (res, b) = stream.read(buf).await;
+                let n = res.unwrap();
+                if n == 0 {
+                    return;
+                }
+                let (res, b) = stream.write_all(b).await;
+                res.unwrap();
+                buf = b;
+                buf.clear();
+            }
+        });
+    }
+}
+
+// Returns the order the clients complete in, and when.
+fn clients(seed: u64) -> Vec<(usize, Duration)> {
+    let config = SimConfig::new()
+        .seed(seed)
+        .drop_rate(0.2)
+        .short_read_rate(0.5);
+    run(config, async {
+        let start = Instant::now();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        monoio::spawn(echo(listener));
+
+        let done = Rc::new(RefCell::new(Vec::new()));
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let done = done.clone();
+                monoio::spawn(async move {
+                    let mut stream = TcpStream::connect(addr).await.unwrap();
+                    let msg = format!("hello from client {i}").into_bytes();
+                    let (res, msg) = stream.write_all(msg).await;
+                    res.unwrap();
+                    let (res, buf) = stream.read_exact(vec![0; msg.len()]).await;
+                    res.unwrap();
+                    assert_eq!(buf, msg);
+                    done.borrow_mut().push((i, start.elapsed()));
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.await;
+        }
+        done.take()
+    })
+}
+
+#[test]
+fn deterministic() {
+    let first = clients(7);
+    assert_eq!(first.len(), 8);
+    assert_eq!(first, clients(7));
+}
+
+#[test]
+fn virtual_clock() {
+    let real = std::time::Instant::now();
+    run(SimConfig::new(), async {
+        let start = Instant::now();
+        monoio::time::sleep(Duration::from_secs(3600)).await;
+        assert!(start.elapsed() >= Duration::from_secs(3600));
+    });
+    assert!(real.elapsed() < Duration::from_secs(60));
+}
+
+#[test]
+fn dropped_packet() {
+    let config = SimConfig::new()
+        .latency(Duration::from_millis(1), Duration::from_millis(1))
+        .drop_rate(1.0)
+        .retransmit_timeout(Duration::from_secs(1));
+    run(config, async {
+        let listener = TcpListener::bind("127.0.0.1:9000").unwrap();
+        let start = Instant::now();
+        let (client, server) = futures::join!(TcpStream::connect("127.0.0.1:9000"), async {
+            listener.accept().await
+        });
+        let (mut client, (mut server, peer)) = (client.unwrap(), server.unwrap());
+        assert_eq!(client.local_addr().unwrap(), peer);
+        assert_eq!(server.peer_addr().unwrap(), peer);
+        // The SYN was dropped once.
+        assert!(start.elapsed() >= Duration::from_millis(1001));
+
+        client.write_all(b"ping").await.0.unwrap();
+        client.shutdown().await.unwrap();
+        let (res, buf) = server.read_exact(vec![0; 4]).await;
+        res.unwrap();
+        assert_eq!(buf, b"ping");
+        let (res, _) = server.read(vec![0; 4]).await;
+        assert_eq!(res.unwrap(), 0);
+    });
+}
+
+#[test]
+fn reset() {
+    run(SimConfig::new().reset_rate(1.0), async {
+        let listener = TcpListener::bind("0.0.0.0:9000").unwrap();
+        let mut client = TcpStream::connect("127.0.0.1:9000").await.unwrap();
+        let (mut server, _) = listener.accept().await.unwrap();
+        client.write_all(b"ping").await.0.unwrap();
+        // The data may be delivered before the reset.
+        let err = loop {
+            if let Err(err) = server.read(vec![0; 4]).await.0 {
+                break err;
+            }
+        };
+        assert_eq!(err.kind(), ErrorKind::ConnectionReset);
+        let (res, _) = client.write(b"ping").await;
+        assert_eq!(res.unwrap_err().kind(), ErrorKind::ConnectionReset);
+    });
+}
+
+#[test]
+fn refused() {
+    run(SimConfig::new(), async {
+        let err = TcpStream::connect("127.0.0.1:9000").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConnectionRefused);
+        let listener = TcpListener::bind("127.0.0.1:9000").unwrap();
+        let err = TcpListener::bind("127.0.0.1:9000").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AddrInUse);
+        drop(listener);
+        let err = TcpStream::connect("127.0.0.1:9000").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConnectionRefused);
+    });
+}
+
+#[test]
+fn unix_stream() {
+    run(SimConfig::new().short_read_rate(1.0), async {
+        let listener = UnixListener::bind("/tmp/sim.sock").unwrap();
+        monoio::spawn(async move {
+            let mut stream = listener.accept().await.unwrap();
+            stream.write_all(b"hello world").await.0.unwrap();
+        });
+        let mut stream = UnixStream::connect("/tmp/sim.sock").await.unwrap();
+        let (res, buf) = stream.read(vec![0; 11]).await;
+        // Every read is short.
+        assert!(res.unwrap() < 11);
+        assert!(b"hello world".starts_with(&buf));
+    });
+}
+
+#[test]
+fn unsupported_op() {
+    run(SimConfig::new(), async {
+        let err = monoio::fs::File::open("Cargo.toml").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+    });
+}
+
+// Ops on real fds are not simulated, and a chain of them stops at the first
+// failure as with the legacy driver.
+#[test]
+fn chain_real_fds() {
+    let (a, _b) = std::os::unix::net::UnixStream::pair().unwrap();
+    run(SimConfig::new(), async move {
+        let stream = monoio::net::UnixStream::from_std(a).unwrap();
+        let (results, _) = monoio::io::chain()
+            .send(&stream, b"ping".to_vec())
+            .recv(&stream, vec![0; 4])
+            .submit()
+            .await;
+        assert_eq!(
+            results[0].as_ref().unwrap_err().kind(),
+            ErrorKind::Unsupported
+        );
+        assert_eq!(
+            results[1].as_ref().unwrap_err().raw_os_error(),
+            Some(libc::ECANCELED)
+        );
+    });
+}
@@ -0,0 +1,60 @@
+#![cfg(feature = "task-dump")]
+
+use monoio::task::{Builder, TaskState};
+
+#[monoio::test_all]
+async fn dump() {
+    let pending = Builder::new()
+        .name("pending")
+        .spawn(std::future::pending::<()>());
+    let line = line!() - 1;
+    monoio::spawn(async {}).await;
+
+    let tasks = monoio::task::dump();
+    // The completed task is gone.
+    assert_eq!(tasks.len(), 1);
+    let task = &tasks[0];
+    assert_eq!(task.name.as_deref(), Some("pending"));
+    assert_eq!(task.location.file(), file!());
+    assert_eq!(task.location.line(), line);
+    assert_eq!(task.state, TaskState::Idle);
+    assert!(task.since_last_poll.is_some());
+    assert!(task.to_string().contains("\"pending\""));
+    drop(pending);
+}
+
+#[monoio::test_all]
+async fn dump_scheduled() {
+    let handle = monoio::spawn(async {});
+    let tasks = monoio::task::dump();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].state, TaskState::Scheduled);
+    assert!(tasks[0].since_last_poll.is_none());
+    handle.await;
+    assert!(monoio::task::dump().is_empty());
+}
+
+#[cfg(feature = "sync")]
+#[test]
+fn dump_from_handle() {
+    let (tx, rx) = std::sync::mpsc::channel();
+    let (done_tx, done_rx) = futures::channel::oneshot::channel::<()>();
+    let thread = std::thread::spawn(move || {
+        let mut rt = monoio::RuntimeBuilder::<monoio::FusionDriver>::new()
+            .build()
+            .unwrap();
+        rt.block_on(async move {
+            Builder::new()
+                .name("stuck")
+                .spawn(std::future::pending::<()>());
+            tx.send(monoio::RuntimeHandle::current()).unwrap();
+            let _ = done_rx.await;
+        });
+    });
+
+    let handle = rx.recv().unwrap();
+    let tasks = futures::executor::block_on(handle.dump()).unwrap();
+    assert!(tasks.iter().any(|t| t.name.as_deref() == Some("stuck")));
+    done_tx.send(()).unwrap();
+    thread.join().unwrap();
+}
@@ -0,0 +1,53 @@
+monoio::task_local! {
+    static REQ_ID: u32;
+    pub static TENANT: String;
+}
+
+#[monoio::test_all]
+async fn scope() {
+    assert!(REQ_ID.try_with(|_| ()).is_err());
+    REQ_ID
+        .scope(1, async {
+            assert_eq!(REQ_ID.get(), 1);
+            REQ_ID.scope(2, async { assert_eq!(REQ_ID.get(), 2) }).await;
+            assert_eq!(REQ_ID.get(), 1);
+        })
+        .await;
+    assert!(REQ_ID.try_with(|_| ()).is_err());
+}
+
+// Interleaved tasks each see their own value.
+#[monoio::test_all]
+async fn interleaved() {
+    let handles: Vec<_> = (0..4)
+        .map(|i| {
+            monoio::spawn(REQ_ID.scope(i, async move {
+                for _ in 0..4 {
+                    assert_eq!(REQ_ID.get(), i);
+                    monoio::task::yield_now().await;
+                }
+            }))
+        })
+        .collect();
+    for handle in handles {
+        handle.await;
+    }
+}
+
+#[monoio::test_all]
+async fn inherit() {
+    TENANT
+        .scope("acme".to_string(), async {
+            let plain = monoio::spawn(async { TENANT.try_with(|_| ()).is_err() });
+            assert!(plain.await);
+            let inherited = monoio::spawn(TENANT.inherit(async { TENANT.get() }));
+            assert_eq!(inherited.await, "acme");
+        })
+        .await;
+}
+
+#[test]
+fn sync_scope() {
+    REQ_ID.sync_scope(3, || assert_eq!(REQ_ID.with(|id| *id), 3));
+    assert!(REQ_ID.try_with(|_| ()).is_err());
+}
@@ -0,0 +1,62 @@
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use monoio::task::Priority;
+
+#[monoio::test_all]
+async fn high_priority_first() {
+    let order = Rc::new(RefCell::new(Vec::new()));
+    let handles: Vec<_> = [Priority::Low, Priority::Normal, Priority::High]
+        .into_iter()
+        .map(|priority| {
+            let order = order.clone();
+            monoio::spawn_with_priority(priority, async move {
+                order.borrow_mut().push(priority);
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.await;
+    }
+    assert_eq!(
+        *order.borrow(),
+        [Priority::High, Priority::Normal, Priority::Low]
+    );
+}
+
+// High priority tasks which keep yielding do not starve the low ones.
+#[monoio::test_all]
+async fn low_priority_not_starved() {
+    let stop = Rc::new(Cell::new(false));
+    for _ in 0..16 {
+        let stop = stop.clone();
+        monoio::spawn_with_priority(Priority::High, async move {
+            while !stop.get() {
+                monoio::task::yield_now().await;
+            }
+        });
+    }
+    monoio::spawn_with_priority(Priority::Low, async {}).await;
+    stop.set(true);
+}
+
+#[monoio::test_all]
+async fn builder() {
+    let order = Rc::new(RefCell::new(Vec::new()));
+    let normal = {
+        let order = order.clone();
+        monoio::spawn(async move { order.borrow_mut().push(Priority::Normal) })
+    };
+    let high = {
+        let order = order.clone();
+        monoio::task::Builder::new()
+            .name("high")
+            .priority(Priority::High)
+            .spawn(async move { order.borrow_mut().push(Priority::High) })
+    };
+    normal.await;
+    high.await;
+    assert_eq!(*order.borrow(), [Priority::High, Priority::Normal]);
+}
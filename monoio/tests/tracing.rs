@@ -0,0 +1,107 @@
+#![cfg(feature = "tracing")]
+
+use std::sync::{Arc, Mutex};
+
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Metadata, Subscriber,
+};
+
+// Records the names and fields of the spans.
+#[derive(Clone, Default)]
+struct Recorder {
+    spans: Arc<Mutex<Vec<String>>>,
+}
+
+struct Fields<'a>(&'a mut String);
+
+impl Visit for Fields<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.push_str(&format!(" {}={:?}", field.name(), value));
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, attrs: &span::Attributes<'_>) -> span::Id {
+        let mut spans = self.spans.lock().unwrap();
+        let mut span = attrs.metadata().name().to_string();
+        attrs.record(&mut Fields(&mut span));
+        spans.push(span);
+        span::Id::from_u64(spans.len() as u64)
+    }
+
+    fn record(&self, id: &span::Id, values: &span::Record<'_>) {
+        let mut spans = self.spans.lock().unwrap();
+        values.record(&mut Fields(&mut spans[id.into_u64() as usize - 1]));
+    }
+
+    fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+    fn event(&self, _: &Event<'_>) {}
+
+    fn enter(&self, _: &span::Id) {}
+
+    fn exit(&self, _: &span::Id) {}
+}
+
+#[test]
+fn task_and_op_spans() {
+    let recorder = Recorder::default();
+    tracing::subscriber::with_default(recorder.clone(), || {
+        let mut rt = monoio::RuntimeBuilder::<monoio::FusionDriver>::new()
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            monoio::task::Builder::new()
+                .name("reader")
+                .spawn(async {
+                    let file = monoio::fs::File::open("Cargo.toml").await.unwrap();
+                    file.close().await.unwrap();
+                })
+                .await;
+        });
+    });
+
+    let spans = recorder.spans.lock().unwrap();
+    let task = spans.iter().find(|s| s.starts_with("task ")).unwrap();
+    assert!(task.contains(r#"task.name="reader""#), "{task}");
+    assert!(task.contains("task.priority=Normal"), "{task}");
+    let open = spans.iter().find(|s| s.contains(r#"op="Open""#)).unwrap();
+    assert!(open.contains("result=Ok("), "{open}");
+}
+
+// Returns the span of a read of `Cargo.toml` and the fd of the file.
+fn read_span<D: monoio::Buildable + monoio::Driver>() -> (String, i32) {
+    use std::os::unix::io::AsRawFd;
+
+    let recorder = Recorder::default();
+    let fd = tracing::subscriber::with_default(recorder.clone(), || {
+        monoio::start::<D, _>(async {
+            let file = monoio::fs::File::open("Cargo.toml").await.unwrap();
+            file.read_at(vec![0; 8], 0).await.0.unwrap();
+            file.as_raw_fd()
+        })
+    });
+
+    let spans = recorder.spans.lock().unwrap();
+    let read = spans.iter().find(|s| s.contains(r#"op="Read""#)).unwrap();
+    (read.clone(), fd)
+}
+
+#[cfg(all(target_os = "linux", feature = "iouring"))]
+#[test]
+fn op_fd_uring() {
+    let (read, fd) = read_span::<monoio::IoUringDriver>();
+    assert!(read.contains(&format!(" fd={fd}")), "{read}");
+}
+
+#[cfg(feature = "legacy")]
+#[test]
+fn op_fd_legacy() {
+    let (read, fd) = read_span::<monoio::LegacyDriver>();
+    assert!(read.contains(&format!(" fd={fd}")), "{read}");
+}
@@ -3,6 +3,7 @@ use monoio::{
     io::{AsyncReadRent, AsyncReadRentExt, AsyncWriteRent, AsyncWriteRentExt},
     net::{UnixListener, UnixStream},
 };
+use std::time::Duration;
 
 #[monoio::test_all]
 async fn accept_read_write() -> std::io::Result<()> {
@@ -52,3 +53,54 @@ async fn shutdown() -> std::io::Result<()> {
     assert_eq!(n, 0);
     Ok(())
 }
+
+#[monoio::test_all(timer_enabled = true)]
+async fn read_with_timeout() -> std::io::Result<()> {
+    let (mut a, mut b) = UnixStream::pair()?;
+
+    let (res, buf) = a
+        .read_with_timeout(vec![0; 5], Duration::from_millis(50))
+        .await;
+    assert_eq!(res.unwrap_err().kind(), std::io::ErrorKind::TimedOut);
+    assert_eq!(buf.capacity(), 5);
+
+    b.write_all(b"hello").await.0?;
+    let (res, buf) = a.read_with_timeout(buf, Duration::from_secs(5)).await;
+    assert_eq!(res?, 5);
+    assert_eq!(&buf, b"hello");
+    Ok(())
+}
+
+#[cfg(feature = "legacy")]
+#[test]
+fn read_with_timeout_no_timer() {
+    let mut rt = monoio::RuntimeBuilder::<monoio::LegacyDriver>::new()
+        .build()
+        .unwrap();
+    rt.block_on(async {
+        let (mut a, _b) = UnixStream::pair().unwrap();
+        let (res, buf) = a
+            .read_with_timeout(vec![0; 5], Duration::from_millis(50))
+            .await;
+        assert_eq!(res.unwrap_err().kind(), std::io::ErrorKind::Unsupported);
+        assert_eq!(buf.capacity(), 5);
+    });
+}
+
+#[monoio::test_all]
+async fn send_file() -> std::io::Result<()> {
+    let mut tmp = tempfile::NamedTempFile::new()?;
+    std::io::Write::write_all(&mut tmp, b"hello world")?;
+    let file = monoio::fs::File::open(tmp.path()).await?;
+    let (mut a, mut b) = UnixStream::pair()?;
+
+    let sent = a.send_file(&file, 6, 100).await?;
+    assert_eq!(sent, 5);
+    let sent = a.send_file(&file, 0, 5).await?;
+    assert_eq!(sent, 5);
+
+    let (res, buf) = b.read_exact(vec![0; 10]).await;
+    res?;
+    assert_eq!(&buf, b"worldhello");
+    Ok(())
+}
@@ -0,0 +1,63 @@
+#![cfg(all(target_os = "linux", feature = "iouring"))]
+
+use std::{io::Write, os::unix::prelude::AsRawFd, time::Duration};
+
+use monoio::{IoUringDriver, RuntimeBuilder, UringConfig};
+
+// Build a runtime with the config and do some io on it. Kernels which do not
+// support an option are skipped.
+fn run_with(config: UringConfig) {
+    if !monoio::utils::detect_uring() {
+        return;
+    }
+    let mut rt = match RuntimeBuilder::<IoUringDriver>::new()
+        .with_uring_config(config)
+        .enable_timer()
+        .build()
+    {
+        Ok(rt) => rt,
+        Err(e) if matches!(e.raw_os_error(), Some(libc::EINVAL | libc::EPERM)) => return,
+        Err(e) => panic!("{e}"),
+    };
+
+    let mut tempfile = tempfile::NamedTempFile::new().unwrap();
+    tempfile.write_all(b"hello world").unwrap();
+    rt.block_on(async {
+        let file = monoio::fs::File::open(tempfile.path()).await.unwrap();
+        for _ in 0..64 {
+            let (res, buf) = file.read_at(vec![0; 11], 0).await;
+            assert_eq!(res.unwrap(), 11);
+            assert_eq!(&buf, b"hello world");
+        }
+        monoio::spawn(monoio::time::sleep(Duration::from_millis(1))).await;
+    });
+}
+
+#[test]
+fn cq_entries() {
+    run_with(UringConfig::new().cq_entries(4096));
+}
+
+#[test]
+fn coop_taskrun() {
+    run_with(UringConfig::new().coop_taskrun());
+}
+
+#[test]
+fn defer_taskrun() {
+    run_with(UringConfig::new().single_issuer().defer_taskrun());
+}
+
+#[test]
+fn sqpoll() {
+    run_with(UringConfig::new().sqpoll(Duration::from_millis(10)));
+}
+
+#[test]
+fn attach_wq() {
+    if !monoio::utils::detect_uring() {
+        return;
+    }
+    let rt = RuntimeBuilder::<IoUringDriver>::new().build().unwrap();
+    run_with(UringConfig::new().attach_wq(rt.as_raw_fd()));
+}
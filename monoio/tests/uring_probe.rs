@@ -0,0 +1,92 @@
+use monoio::utils::is_uring_op_supported;
+
+// NOP and READ are the opcodes every probed kernel supports.
+const IORING_OP_NOP: u8 = 0;
+const IORING_OP_READ: u8 = 22;
+
+#[cfg(all(target_os = "linux", feature = "iouring"))]
+#[test]
+fn uring_probe() {
+    if !monoio::utils::detect_uring() {
+        return;
+    }
+    let mut rt = monoio::RuntimeBuilder::<monoio::IoUringDriver>::new()
+        .build()
+        .unwrap();
+    rt.block_on(async {
+        assert!(is_uring_op_supported(IORING_OP_NOP));
+        assert!(is_uring_op_supported(IORING_OP_READ));
+        assert!(!is_uring_op_supported(u8::MAX));
+    });
+}
+
+#[cfg(feature = "legacy")]
+#[test]
+fn legacy_probe() {
+    let mut rt = monoio::RuntimeBuilder::<monoio::LegacyDriver>::new()
+        .build()
+        .unwrap();
+    rt.block_on(async {
+        assert!(!is_uring_op_supported(IORING_OP_NOP));
+        assert!(!is_uring_op_supported(IORING_OP_READ));
+    });
+}
+
+// Opcodes of the socket ops, which fall back to waiting for readiness.
+#[cfg(all(target_os = "linux", feature = "iouring"))]
+const IORING_OP_ACCEPT: u8 = 13;
+#[cfg(all(target_os = "linux", feature = "iouring"))]
+const IORING_OP_CONNECT: u8 = 16;
+#[cfg(all(target_os = "linux", feature = "iouring"))]
+const IORING_OP_SEND: u8 = 26;
+#[cfg(all(target_os = "linux", feature = "iouring"))]
+const IORING_OP_RECV: u8 = 27;
+
+#[cfg(all(target_os = "linux", feature = "iouring"))]
+#[test]
+fn uring_fallback() {
+    use monoio::{
+        io::{AsyncReadRentExt, AsyncWriteRentExt},
+        net::{TcpListener, TcpStream},
+        UringConfig,
+    };
+
+    if !monoio::utils::detect_uring() {
+        return;
+    }
+    let opcodes = [
+        IORING_OP_ACCEPT,
+        IORING_OP_CONNECT,
+        IORING_OP_SEND,
+        IORING_OP_RECV,
+    ];
+    let mut rt = monoio::RuntimeBuilder::<monoio::IoUringDriver>::new()
+        .with_uring_config(UringConfig::new().unsupported_opcodes(&opcodes))
+        .build()
+        .unwrap();
+    rt.block_on(async {
+        assert!(!is_uring_op_supported(IORING_OP_RECV));
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = monoio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let (res, buf) = stream.read_exact(vec![0; 4]).await;
+            res.unwrap();
+            stream.write_all(buf).await.0.unwrap();
+        });
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(b"ping").await.0.unwrap();
+        let (res, buf) = stream.read_exact(vec![0; 4]).await;
+        res.unwrap();
+        assert_eq!(&buf, b"ping");
+        server.await;
+
+        // A chain can not be linked with a fallback, so it fails.
+        let err = stream
+            .read_with_timeout(vec![0; 4], std::time::Duration::from_secs(1))
+            .await
+            .0
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::Unsupported);
+    });
+}